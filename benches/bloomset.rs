@@ -3,27 +3,16 @@ extern crate test;
 
 use toolshed::set::{BloomSet, Set};
 use toolshed::Arena;
+use toolshed::bench_support::{reset_each, WORDS, SET_WORDS};
 use test::{Bencher, black_box};
 use std::collections::HashSet;
 use rustc_hash::FxHashSet;
 
-static WORDS: &[&str] = &[
-    "ARENA_BLOCK", "Arena", "Cell", "Self", "String", "T", "Vec", "_unchecked", "a",
-    "alignment", "alloc", "alloc_bytes", "alloc_str", "alloc_str_zero_end", "alloc_string",
-    "as", "as_bytes", "as_mut_ptr", "as_ptr", "block", "cap", "cell", "const",
-    "copy_nonoverlapping", "else", "extend_from_slice", "fn", "from_raw_parts", "from_utf",
-    "get", "grow", "if", "impl", "inline", "into", "into_bytes", "isize", "len",
-    "len_with_zero", "let", "mem", "mut", "new", "offset", "ptr", "pub", "push",
-    "replace", "return", "self", "set", "size_of", "slice", "std", "store", "str",
-    "struct", "temp", "u", "unsafe", "use", "usize", "val", "vec", "with_capacity"
-];
-static SET_WORDS: &[&str] = &["alloc_bytes", "alloc", "Cell", "String", "yetAnother"];
-
 #[bench]
 fn set_read(b: &mut Bencher) {
     let arena = Arena::new();
     let a     = &arena;
-    let set = Set::new();
+    let mut set = Set::new();
 
     for word in SET_WORDS.iter() {
         set.insert(a, *word);
@@ -38,26 +27,24 @@ fn set_read(b: &mut Bencher) {
 
 #[bench]
 fn set_create(b: &mut Bencher) {
-    let arena = Arena::new();
-    let a     = &arena;
+    let mut arena = Arena::new();
 
-    b.iter(|| {
-        unsafe { a.clear() };
-        let set = Set::new();
+    b.iter(reset_each(&mut arena, |a| {
+        let mut set = Set::new();
 
         for word in SET_WORDS.iter() {
             set.insert(a, *word);
         }
 
-        black_box(set)
-    })
+        black_box(set);
+    }))
 }
 
 #[bench]
 fn bloom_set_read(b: &mut Bencher) {
     let arena = Arena::new();
     let a     = &arena;
-    let set = BloomSet::new();
+    let mut set = BloomSet::new();
 
     for word in SET_WORDS.iter() {
         set.insert(a, *word);
@@ -72,19 +59,17 @@ fn bloom_set_read(b: &mut Bencher) {
 
 #[bench]
 fn bloom_set_create(b: &mut Bencher) {
-    let arena = Arena::new();
-    let a     = &arena;
+    let mut arena = Arena::new();
 
-    b.iter(|| {
-        unsafe { a.clear() };
-        let set = BloomSet::new();
+    b.iter(reset_each(&mut arena, |a| {
+        let mut set = BloomSet::new();
 
         for word in SET_WORDS.iter() {
             set.insert(a, *word);
         }
 
-        black_box(set)
-    })
+        black_box(set);
+    }))
 }
 
 #[bench]
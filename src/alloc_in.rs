@@ -0,0 +1,399 @@
+use crate::arena::Arena;
+
+/// Types that can be deep-copied into the `Arena`, turning owned data into
+/// its `Copy` arena counterpart: `String` becomes `&'arena str`, `Vec<T>`
+/// becomes `&'arena [T::Output]`, `Box<T>` becomes `&'arena T::Output`, and
+/// so on.
+///
+/// With the `derive` feature enabled, `AllocIn` can be derived for structs
+/// and enums, given an arena counterpart with the same fields or variants:
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use toolshed::{Arena, AllocIn};
+///
+/// #[derive(AllocIn)]
+/// #[alloc_in(ArenaExpr)]
+/// enum Expr {
+///     Number(f64),
+///     Ident(String),
+///     Call { callee: Box<Expr>, args: Vec<Expr> },
+/// }
+///
+/// #[derive(Debug, PartialEq, Clone, Copy)]
+/// enum ArenaExpr<'arena> {
+///     Number(f64),
+///     Ident(&'arena str),
+///     Call { callee: &'arena ArenaExpr<'arena>, args: &'arena [ArenaExpr<'arena>] },
+/// }
+///
+/// let arena = Arena::new();
+/// let expr = Expr::Call {
+///     callee: Box::new(Expr::Ident("max".into())),
+///     args: vec![Expr::Number(1.0), Expr::Number(2.0)],
+/// };
+///
+/// assert_eq!(expr.alloc_in(&arena), ArenaExpr::Call {
+///     callee: &ArenaExpr::Ident("max"),
+///     args: &[ArenaExpr::Number(1.0), ArenaExpr::Number(2.0)],
+/// });
+/// # }
+/// ```
+///
+/// The arena counterpart has to take a single `'arena` lifetime parameter.
+pub trait AllocIn<'arena> {
+    /// The arena counterpart of this type.
+    type Output: Copy + 'arena;
+
+    /// Copy `self` into the `Arena`.
+    fn alloc_in(&self, arena: &'arena Arena) -> Self::Output;
+}
+
+macro_rules! impl_alloc_in_copy {
+    ($( $ty:ty ),*) => {
+        $(
+            impl<'arena> AllocIn<'arena> for $ty {
+                type Output = $ty;
+
+                #[inline]
+                fn alloc_in(&self, _: &'arena Arena) -> $ty {
+                    *self
+                }
+            }
+        )*
+    };
+}
+
+impl_alloc_in_copy!(
+    (), bool, char, f32, f64,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize
+);
+
+impl<'arena> AllocIn<'arena> for str {
+    type Output = &'arena str;
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> &'arena str {
+        arena.alloc_str(self)
+    }
+}
+
+impl<'arena> AllocIn<'arena> for String {
+    type Output = &'arena str;
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> &'arena str {
+        arena.alloc_str(self)
+    }
+}
+
+impl<'arena, T: AllocIn<'arena>> AllocIn<'arena> for [T] {
+    type Output = &'arena [T::Output];
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> &'arena [T::Output] {
+        arena
+            .alloc_uninitialized_slice(self.len())
+            .init_with(self.iter().map(|item| item.alloc_in(arena)))
+    }
+}
+
+impl<'arena, T: AllocIn<'arena>> AllocIn<'arena> for Vec<T> {
+    type Output = &'arena [T::Output];
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> &'arena [T::Output] {
+        self.as_slice().alloc_in(arena)
+    }
+}
+
+impl<'arena, T: AllocIn<'arena> + ?Sized> AllocIn<'arena> for Box<T> {
+    type Output = &'arena T::Output;
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> &'arena T::Output {
+        arena.alloc((**self).alloc_in(arena))
+    }
+}
+
+impl<'arena, T: AllocIn<'arena>> AllocIn<'arena> for Option<T> {
+    type Output = Option<T::Output>;
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> Option<T::Output> {
+        self.as_ref().map(|value| value.alloc_in(arena))
+    }
+}
+
+impl<'arena, A: AllocIn<'arena>, B: AllocIn<'arena>> AllocIn<'arena> for (A, B) {
+    type Output = (A::Output, B::Output);
+
+    #[inline]
+    fn alloc_in(&self, arena: &'arena Arena) -> Self::Output {
+        (self.0.alloc_in(arena), self.1.alloc_in(arena))
+    }
+}
+
+/// Types living on one `Arena` that can be deep-cloned into another, such as
+/// when extracting a small subtree out of a large scratch `Arena` into a
+/// long-lived one. Strings are copied with `alloc_str`, and the elements of
+/// lists, maps and sets are cloned recursively.
+///
+/// ```rust
+/// use toolshed::{Arena, CloneIn};
+/// use toolshed::list::List;
+///
+/// let keep = Arena::new();
+///
+/// let names = {
+///     let scratch = Arena::new();
+///     let names = List::from_iter(&scratch, vec![scratch.alloc_str("doge"), scratch.alloc_str("moon")]);
+///
+///     names.clone_in(&keep)
+/// };
+///
+/// assert_eq!(names.iter().copied().collect::<Vec<_>>(), ["doge", "moon"]);
+/// ```
+///
+/// This is also how an `Arena` is compacted. Cloning the roots of all live
+/// data into a fresh `Arena` packs it tightly, after which the old arena can
+/// be dropped, releasing the memory held by values that are no longer
+/// reachable, unused capacity of adopted `Vec`s and `String`s, and pages
+/// allocated for data that has since been replaced.
+///
+/// ```rust
+/// use toolshed::{Arena, CloneIn};
+/// use toolshed::list::List;
+///
+/// let arena = Arena::new();
+/// let mut names = List::empty();
+///
+/// for n in 0..10_000 {
+///     names = List::from_iter(&arena, vec![arena.alloc_fmt(format_args!("name{}", n))]);
+/// }
+///
+/// let compacted = Arena::new();
+/// let names = names.clone_in(&compacted);
+///
+/// drop(arena);
+///
+/// assert_eq!(names.iter().copied().collect::<Vec<_>>(), ["name9999"]);
+/// // Everything fits on the first page
+/// assert_eq!(compacted.allocated_bytes(), Arena::new().allocated_bytes());
+/// ```
+pub trait CloneIn<'target> {
+    /// The same type, living on the target `Arena`.
+    type Cloned: Copy + 'target;
+
+    /// Clone `self` into the `arena`.
+    fn clone_in(&self, arena: &'target Arena) -> Self::Cloned;
+}
+
+macro_rules! impl_clone_in_copy {
+    ($( $ty:ty ),*) => {
+        $(
+            impl<'target> CloneIn<'target> for $ty {
+                type Cloned = $ty;
+
+                #[inline]
+                fn clone_in(&self, _: &'target Arena) -> $ty {
+                    *self
+                }
+            }
+        )*
+    };
+}
+
+impl_clone_in_copy!(
+    (), bool, char, f32, f64,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize
+);
+
+impl<'target> CloneIn<'target> for &str {
+    type Cloned = &'target str;
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> &'target str {
+        arena.alloc_str(self)
+    }
+}
+
+impl<'target, T: CloneIn<'target>> CloneIn<'target> for &T {
+    type Cloned = &'target T::Cloned;
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> &'target T::Cloned {
+        arena.alloc((**self).clone_in(arena))
+    }
+}
+
+impl<'target, T: CloneIn<'target>> CloneIn<'target> for &[T] {
+    type Cloned = &'target [T::Cloned];
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> &'target [T::Cloned] {
+        arena.alloc_slice_map(self.iter(), |item| item.clone_in(arena))
+    }
+}
+
+impl<'target, T: CloneIn<'target>> CloneIn<'target> for Option<T> {
+    type Cloned = Option<T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> Option<T::Cloned> {
+        self.as_ref().map(|value| value.clone_in(arena))
+    }
+}
+
+impl<'target, A: CloneIn<'target>, B: CloneIn<'target>> CloneIn<'target> for (A, B) {
+    type Cloned = (A::Cloned, B::Cloned);
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> Self::Cloned {
+        (self.0.clone_in(arena), self.1.clone_in(arena))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "derive")]
+    use crate::AllocIn;
+
+    #[test]
+    fn clone_in_compacts() {
+        use crate::arena::ARENA_BLOCK;
+        use crate::list::List;
+        use crate::map::Map;
+
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        for n in 0..20_000u64 {
+            map.insert(&arena, n % 10, List::from_iter(&arena, vec![arena.alloc_vec(Vec::with_capacity(64)), arena.alloc_slice(&[n])]));
+        }
+
+        assert!(arena.allocated_bytes() > ARENA_BLOCK * 10);
+
+        let compacted = Arena::new();
+        let copy = map.clone_in(&compacted);
+
+        drop(arena);
+
+        assert_eq!(compacted.allocated_bytes(), ARENA_BLOCK);
+        assert_eq!(compacted.wasted_bytes(), 0);
+        assert_eq!(copy.get(3).unwrap().iter().copied().collect::<Vec<_>>(), [&[][..], &[19_993][..]]);
+    }
+
+    #[test]
+    fn alloc_in_std_types() {
+        let arena = Arena::new();
+
+        assert_eq!(String::from("foo").alloc_in(&arena), "foo");
+        assert_eq!(vec![1u32, 2, 3].alloc_in(&arena), &[1, 2, 3]);
+        assert_eq!(Some(Box::new(42u64)).alloc_in(&arena), Some(&42));
+        assert_eq!(None::<Box<u64>>.alloc_in(&arena), None);
+        assert_eq!((String::from("bar"), 7i32).alloc_in(&arena), ("bar", 7));
+    }
+
+    #[test]
+    fn alloc_in_nested() {
+        let arena = Arena::new();
+        let words = vec![vec![String::from("foo")], vec![], vec![String::from("bar"), String::from("baz")]];
+
+        let expected: &[&[&str]] = &[&["foo"], &[], &["bar", "baz"]];
+
+        assert_eq!(words.alloc_in(&arena), expected);
+    }
+
+    #[test]
+    fn clone_in_refs() {
+        let target = Arena::new();
+
+        let cloned = {
+            let source = Arena::new();
+            let words: &[&str] = source.alloc_slice(&[source.alloc_str("foo"), source.alloc_str("bar")]);
+            let pair = (source.alloc(7u8) as &u8, Some(words));
+
+            pair.clone_in(&target)
+        };
+
+        assert_eq!(cloned, (&7, Some(&["foo", "bar"][..])));
+        assert!(target.contains(cloned.0));
+        assert!(target.contains(cloned.1.unwrap()[1]));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_struct() {
+        #[derive(AllocIn)]
+        #[alloc_in(ArenaItem)]
+        struct Item {
+            name: String,
+            tags: Vec<String>,
+            weight: Option<Box<u32>>,
+        }
+
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        struct ArenaItem<'arena> {
+            name: &'arena str,
+            tags: &'arena [&'arena str],
+            weight: Option<&'arena u32>,
+        }
+
+        #[derive(AllocIn)]
+        #[alloc_in(ArenaPair)]
+        struct Pair(String, u8);
+
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        struct ArenaPair<'arena>(&'arena str, u8);
+
+        let arena = Arena::new();
+        let item = Item {
+            name: "doge".into(),
+            tags: vec!["to".into(), "the".into(), "moon".into()],
+            weight: Some(Box::new(10)),
+        };
+
+        assert_eq!(item.alloc_in(&arena), ArenaItem {
+            name: "doge",
+            tags: &["to", "the", "moon"],
+            weight: Some(&10),
+        });
+
+        assert_eq!(Pair("foo".into(), 1).alloc_in(&arena), ArenaPair("foo", 1));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_enum() {
+        #[derive(AllocIn)]
+        #[alloc_in(ArenaTree)]
+        enum Tree {
+            Leaf,
+            Node(Box<Tree>, Box<Tree>),
+            Named { name: String },
+        }
+
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        enum ArenaTree<'arena> {
+            Leaf,
+            Node(&'arena ArenaTree<'arena>, &'arena ArenaTree<'arena>),
+            Named { name: &'arena str },
+        }
+
+        let arena = Arena::new();
+        let tree = Tree::Node(
+            Box::new(Tree::Leaf),
+            Box::new(Tree::Named { name: "foo".into() }),
+        );
+
+        assert_eq!(tree.alloc_in(&arena), ArenaTree::Node(
+            &ArenaTree::Leaf,
+            &ArenaTree::Named { name: "foo" },
+        ));
+    }
+}
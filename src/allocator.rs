@@ -0,0 +1,88 @@
+//! `Allocator` implementation for the `Arena`, available with the
+//! `allocator-api` feature flag on nightly Rust.
+
+use std::alloc::{AllocError, Allocator, Layout};
+use std::ptr::NonNull;
+
+use crate::arena::Arena;
+
+/// Allows collections from the standard library to keep their memory on the
+/// `Arena`, which is handy for scratch collections that should die with it.
+///
+/// Memory is only ever freed when the arena is dropped or reset, so
+/// `deallocate` does nothing, and growing a collection always copies it to
+/// a new allocation. Destructors of the values in the collections still run
+/// as usual when the collections themselves are dropped.
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use toolshed::Arena;
+///
+/// let arena = Arena::new();
+/// let mut scratch = Vec::new_in(&arena);
+///
+/// scratch.extend_from_slice(&[1, 2, 3]);
+///
+/// assert_eq!(scratch, [1, 2, 3]);
+/// ```
+unsafe impl Allocator for &Arena {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.try_alloc_aligned(layout.size(), layout.align()).map_err(|_| AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn vec_in_arena() {
+        let arena = Arena::new();
+        let mut vec = Vec::new_in(&arena);
+
+        for n in 0..10_000u64 {
+            vec.push(n);
+        }
+
+        assert!(vec.iter().copied().eq(0..10_000));
+        assert!(arena.allocated_bytes() > 64 * 1024);
+    }
+
+    #[test]
+    fn non_copy_values() {
+        let arena = Arena::new();
+
+        let mut strings = VecDeque::new_in(&arena);
+
+        strings.push_back(String::from("foo"));
+        strings.push_front(String::from("bar"));
+
+        let boxed = Box::new_in(String::from("doge"), &arena);
+
+        assert_eq!(strings, ["bar", "foo"]);
+        assert_eq!(*boxed, "doge");
+    }
+
+    #[test]
+    fn over_aligned() {
+        #[repr(align(256))]
+        struct Aligned(u8);
+
+        let arena = Arena::new();
+
+        arena.alloc(1u8);
+
+        let boxed = Box::new_in(Aligned(7), &arena);
+
+        assert_eq!(&*boxed as *const Aligned as usize % 256, 0);
+        assert_eq!(boxed.0, 7);
+    }
+}
@@ -1,13 +1,36 @@
 //! Module containing the `Arena` and `Uninitialized` structs. For convenience the
 //! `Arena` is exported at the root of the crate.
 
-use std::mem::size_of;
+use std::alloc::{alloc, alloc_zeroed, dealloc, Layout};
+use std::mem::{align_of, needs_drop, size_of, ManuallyDrop, MaybeUninit};
 use std::ops::Deref;
-use std::cell::Cell;
+use std::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize};
+use std::ptr::NonNull;
+use std::cell::{Cell, UnsafeCell};
 use std::borrow::Cow;
+use std::error::Error;
+use std::ffi::CStr;
 use std::fmt;
+use std::sync::RwLock;
 
-const ARENA_BLOCK: usize = 64 * 1024;
+use crate::cell::CopyCell;
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) const ARENA_BLOCK: usize = 64 * 1024;
+
+/// With the `wasm` feature, pages leave room for the bookkeeping of the
+/// allocator, so that every page fits in a single 64KiB page of linear
+/// memory instead of spilling over into a second one.
+#[cfg(feature = "wasm")]
+pub(crate) const ARENA_BLOCK: usize = 64 * 1024 - 64;
+
+/// Number of size classes of recycled blocks, one for every multiple of the
+/// size of `usize`, so blocks of up to 128 bytes on 64-bit targets.
+const RECYCLE_CLASSES: usize = 16;
+
+/// Byte written over memory reclaimed by the arena with the `debug-poison`
+/// feature enabled, so that use-after-clear bugs are easy to spot.
+pub const POISON_BYTE: u8 = 0xDE;
 
 /// An arena implementation that uses preallocated 64KiB pages for all allocations.
 /// If a new allocation were to be pushed over the the boundaries of the page, a
@@ -17,9 +40,204 @@ const ARENA_BLOCK: usize = 64 * 1024;
 /// Allocating a type larger than the page size will result in a new heap allocation
 /// just for that type separate from the page mechanism.
 pub struct Arena {
-    store: Cell<Vec<Vec<u8>>>,
+    store: Cell<Vec<Page>>,
+    adopted: Cell<Vec<Page>>,
+    page: Cell<usize>,
     ptr: Cell<*mut u8>,
     offset: Cell<usize>,
+    allocated: Cell<usize>,
+    wasted: Cell<usize>,
+    limit: usize,
+    recycle_pages: bool,
+    free: [Cell<Option<NonNull<FreeBlock>>>; RECYCLE_CLASSES],
+    recycled: Cell<usize>,
+}
+
+/// A block of memory given back to the arena with `Arena::recycle`, linked
+/// into the free list of its size class.
+struct FreeBlock {
+    next: Option<NonNull<FreeBlock>>,
+}
+
+/// A heap allocation owned by the arena, either a page or an adopted buffer.
+///
+/// Adopted `Vec<T>`s keep the `Layout` they were allocated with, so that they
+/// are deallocated with the very same layout regardless of `T`.
+pub(crate) struct Page {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Page {
+    /// Allocate a new heap block of `size` bytes. The block is always aligned
+    /// to at least the size of `usize`, so that it can be used as a page.
+    pub(crate) fn alloc(size: usize, align: usize) -> Result<Self, AllocError> {
+        Self::alloc_with(size, align, alloc)
+    }
+
+    /// Same as `alloc`, but the block is filled with zeroes. For big blocks
+    /// the allocator can usually hand out freshly mapped memory that is
+    /// already zeroed, without touching it.
+    pub(crate) fn alloc_zeroed(size: usize, align: usize) -> Result<Self, AllocError> {
+        Self::alloc_with(size, align, alloc_zeroed)
+    }
+
+    #[inline]
+    fn alloc_with(size: usize, align: usize, alloc: unsafe fn(Layout) -> *mut u8) -> Result<Self, AllocError> {
+        let error = AllocError { size, limit_exceeded: false };
+        let layout = Layout::from_size_align(size, align.max(size_of::<usize>())).map_err(|_| error)?;
+        let ptr = NonNull::new(unsafe { alloc(layout) }).ok_or(error)?;
+
+        Ok(Page { ptr, layout })
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    pub(crate) fn size(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Pointer to the `address`, if it is within the first `used` bytes of
+    /// the page.
+    #[inline]
+    fn pointer_to(&self, address: usize, used: usize) -> Option<*mut u8> {
+        let start = self.ptr.as_ptr() as usize;
+
+        if address >= start && address < start + used {
+            Some(self.ptr.as_ptr().wrapping_add(address - start))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn from_vec<T>(vec: Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let layout = Layout::array::<T>(vec.capacity()).expect("capacity overflow");
+
+        Page {
+            ptr: unsafe { NonNull::new_unchecked(vec.as_mut_ptr() as *mut u8) },
+            layout,
+        }
+    }
+}
+
+impl Drop for Page {
+    #[inline]
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+        }
+    }
+}
+
+/// Error returned by the fallible `try_alloc*` methods of the `Arena` when
+/// the memory for an allocation could not be obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError {
+    size: usize,
+    limit_exceeded: bool,
+}
+
+impl AllocError {
+    /// Number of bytes requested by the failed allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the allocation failed because it would exceed the
+    /// limit set with `Arena::with_limit`, rather than the heap running out.
+    #[inline]
+    pub fn limit_exceeded(&self) -> bool {
+        self.limit_exceeded
+    }
+
+    /// Error for an allocation of `size` bytes exceeding a memory limit.
+    #[inline]
+    pub(crate) fn over_limit(size: usize) -> Self {
+        AllocError { size, limit_exceeded: true }
+    }
+
+    /// Used by infallible allocation methods, mirrors what `Vec` would do
+    /// after calling the hook set with `set_alloc_error_hook`.
+    #[cold]
+    pub(crate) fn handle(self) -> ! {
+        use std::alloc::{handle_alloc_error, Layout};
+
+        if let Some(hook) = &*ALLOC_ERROR_HOOK.read().unwrap_or_else(|err| err.into_inner()) {
+            hook(&self);
+        }
+
+        if self.limit_exceeded {
+            panic!("{}", self);
+        }
+
+        match Layout::from_size_align(self.size, 1) {
+            Ok(layout) => handle_alloc_error(layout),
+            Err(_)     => panic!("capacity overflow"),
+        }
+    }
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.limit_exceeded {
+            write!(f, "arena memory limit exceeded allocating {} bytes", self.size)
+        } else {
+            write!(f, "arena failed to allocate {} bytes", self.size)
+        }
+    }
+}
+
+impl Error for AllocError {}
+
+type AllocErrorHook = Box<dyn Fn(&AllocError) + Send + Sync>;
+
+static ALLOC_ERROR_HOOK: RwLock<Option<AllocErrorHook>> = RwLock::new(None);
+
+/// Register a hook called whenever an infallible allocation method of any
+/// arena in this crate fails, either because the heap ran out or because a
+/// memory limit was exceeded, replacing the previous hook.
+///
+/// The hook runs before the default behavior, which is to panic if a limit
+/// was exceeded, or to call `std::alloc::handle_alloc_error`, aborting the
+/// process, if the heap ran out. This leaves a chance to flush partial
+/// results or emit diagnostics, and the hook can panic or abort itself to
+/// replace the default behavior.
+///
+/// ```rust
+/// use std::panic::AssertUnwindSafe;
+/// use toolshed::{Arena, set_alloc_error_hook};
+///
+/// set_alloc_error_hook(|err| {
+///     if err.limit_exceeded() {
+///         eprintln!("out of memory for this file, giving up: {}", err);
+///     }
+/// });
+///
+/// let arena = Arena::with_limit(64 * 1024);
+/// arena.alloc(42u64);
+///
+/// let result = std::panic::catch_unwind(AssertUnwindSafe(|| arena.alloc([0u8; 64 * 1024]).len()));
+///
+/// assert!(result.is_err());
+/// # toolshed::take_alloc_error_hook();
+/// ```
+pub fn set_alloc_error_hook<F>(hook: F)
+where
+    F: Fn(&AllocError) + Send + Sync + 'static,
+{
+    *ALLOC_ERROR_HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Box::new(hook));
+}
+
+/// Unregister the hook set with `set_alloc_error_hook`, returning it.
+pub fn take_alloc_error_hook() -> Option<AllocErrorHook> {
+    ALLOC_ERROR_HOOK.write().unwrap_or_else(|err| err.into_inner()).take()
 }
 
 /// A pointer to an uninitialized region of memory.
@@ -27,20 +245,40 @@ pub struct Uninitialized<'arena, T: Copy> {
     pointer: &'arena mut MaybeUninit<T>,
 }
 
-/// Almost a copy of https://github.com/rust-lang/rust/issues/53491
-union MaybeUninit<T: Copy> {
-    value: T,
-    _uninit: (),
-}
-
 impl<'arena, T: Copy> Uninitialized<'arena, T> {
     /// Initialize the memory at the pointer with a given value.
     #[inline]
     pub fn init(self, value: T) -> &'arena mut T {
-        unsafe {
-            self.pointer.value = value;
-            &mut self.pointer.value
-        }
+        self.pointer.write(value)
+    }
+
+    /// Initialize the memory at the pointer with the value returned by `f`.
+    #[inline]
+    pub fn init_with<F>(self, f: F) -> &'arena mut T
+    where
+        F: FnOnce() -> T,
+    {
+        self.pointer.write(f())
+    }
+
+    /// Get a raw pointer to the memory. Individual fields of `T` can be
+    /// initialized through it with `std::ptr::addr_of_mut!`, before the
+    /// `Uninitialized` is converted with `assume_init`.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.pointer.as_mut_ptr()
+    }
+
+    /// Convert the `Uninitialized` to a regular mutable reference, after
+    /// it has been initialized through `as_mut_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// All of the memory must have been initialized with a valid value of
+    /// `T`, otherwise calling this method is undefined behavior.
+    #[inline]
+    pub unsafe fn assume_init(self) -> &'arena mut T {
+        self.pointer.assume_init_mut()
     }
 
     /// Get a reference to the pointer without writing to it.
@@ -48,7 +286,7 @@ impl<'arena, T: Copy> Uninitialized<'arena, T> {
     /// **Calling this method without calling `init` is undefined behavior.**
     #[inline]
     pub unsafe fn as_ref(&self) -> &'arena T {
-        &*(&self.pointer.value as *const T)
+        &*self.pointer.as_ptr()
     }
 
     /// Convert the `Uninitialized` to a regular mutable reference.
@@ -56,7 +294,7 @@ impl<'arena, T: Copy> Uninitialized<'arena, T> {
     /// **Calling this method without calling `init` is undefined behavior.**
     #[inline]
     pub unsafe fn as_mut_ref(self) -> &'arena mut T {
-        &mut self.pointer.value
+        self.assume_init()
     }
 
     /// Convert a raw pointer to an `Uninitialized`. This method is unsafe since it can
@@ -76,373 +314,2225 @@ impl<'arena, T: Copy> From<&'arena mut T> for Uninitialized<'arena, T> {
     }
 }
 
-/// A wrapper around a `str` slice that has an extra `0` byte allocated following
-/// its contents.
-#[derive(Clone, Copy, PartialEq)]
-pub struct NulTermStr<'arena>(&'arena str);
+/// A reference to a value on the `Arena` that is being put there with
+/// `alloc_cyclic`, similar to the `Weak` passed to `Rc::new_cyclic`. It can
+/// be stored in the value itself, or in other values on the arena, and
+/// resolves to the value once `alloc_cyclic` has returned.
+pub struct CyclicRef<'arena, T> {
+    slot: &'arena CyclicSlot<T>,
+}
 
-impl<'arena> fmt::Debug for NulTermStr<'arena> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self.0, f)
-    }
+struct CyclicSlot<T> {
+    value: UnsafeCell<MaybeUninit<T>>,
+    ready: CopyCell<bool>,
 }
 
-impl<'arena> fmt::Display for NulTermStr<'arena> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(self.0, f)
+impl<'arena, T> CyclicRef<'arena, T> {
+    /// Get a reference to the value, or `None` if it's still being
+    /// constructed by the closure passed to `alloc_cyclic`.
+    #[inline]
+    pub fn get(&self) -> Option<&'arena T> {
+        if self.slot.ready.get() {
+            // The value is written once before `ready` is set, and never
+            // modified afterwards.
+            Some(unsafe { &*(self.slot.value.get() as *const T) })
+        } else {
+            None
+        }
     }
-}
 
-impl<'arena> NulTermStr<'arena> {
-    /// Read byte at a given `index`. This does not check for length boundaries,
-    /// but is guaranteed to return `0` for `index` equal to the length.
-    ///
-    /// This can be a very useful optimization when reading a long string one
-    /// byte at a time until termination, if checking for `0` can replace what
-    /// would otherwise have to be length checks.
-    ///
-    /// ```rust
-    /// # use toolshed::Arena;
-    /// # fn main() {
-    /// let arena = Arena::new();
-    /// let str = arena.alloc_nul_term_str("foo");
-    ///
-    /// // We can safely get the underlying `&str` at any time.
-    /// assert_eq!(&str[..], "foo");
-    ///
-    /// unsafe {
-    ///     // First 3 bytes are known to us
-    ///     assert_eq!(str.byte_unchecked(0), b'f');
-    ///     assert_eq!(str.byte_unchecked(1), b'o');
-    ///     assert_eq!(str.byte_unchecked(2), b'o');
-    ///
-    ///     // Following is safe and guaranteed to be '0'
-    ///     assert_eq!(str.byte_unchecked(3), 0);
-    ///
-    ///     // Reading index 4 would be undefined behavior!
-    /// }
-    /// # }
-    /// ```
-    pub unsafe fn byte_unchecked(&self, index: usize) -> u8 {
-        *self.0.as_ptr().add(index)
+    /// Returns `true` if both references point to the same value.
+    #[inline]
+    pub fn ptr_eq(&self, other: &CyclicRef<'arena, T>) -> bool {
+        std::ptr::eq(self.slot, other.slot)
     }
 }
 
-impl<'arena> AsRef<str> for NulTermStr<'arena> {
-    fn as_ref(&self) -> &str {
-        self.0
+impl<T> Clone for CyclicRef<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
-impl<'arena> Deref for NulTermStr<'arena> {
-    type Target = &'arena str;
+impl<T> Copy for CyclicRef<'_, T> {}
 
-    fn deref(&self) -> &&'arena str {
-        &self.0
+impl<T: fmt::Debug> fmt::Debug for CyclicRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("CyclicRef").field(value).finish(),
+            None        => f.write_str("CyclicRef(<uninitialized>)"),
+        }
     }
 }
 
-impl<'arena> From<NulTermStr<'arena>> for &'arena str {
-    fn from(nts: NulTermStr<'arena>) -> &'arena str {
-        nts.0
-    }
+/// A pointer to an uninitialized region of memory large enough to hold
+/// a slice of `T`.
+pub struct UninitializedSlice<'arena, T: Copy> {
+    pointer: &'arena mut [MaybeUninit<T>],
 }
 
-impl Arena {
-    /// Create a new arena with a single preallocated 64KiB page.
-    pub fn new() -> Self {
-        let mut store = vec![Vec::with_capacity(ARENA_BLOCK)];
-        let ptr = store[0].as_mut_ptr();
-
-        Arena {
-            store: Cell::new(store),
-            ptr: Cell::new(ptr),
-            offset: Cell::new(0),
-        }
+impl<'arena, T: Copy> UninitializedSlice<'arena, T> {
+    /// Number of elements the slice can hold.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pointer.len()
     }
 
-    /// Put the value onto the page of the arena and return a reference to it.
+    /// Returns `true` if the slice has a length of 0.
     #[inline]
-    pub fn alloc<'arena, T: Sized + Copy>(&'arena self, value: T) -> &'arena mut T {
-        self.alloc_uninitialized().init(value)
+    pub fn is_empty(&self) -> bool {
+        self.pointer.is_empty()
     }
 
-    /// Allocate enough bytes for the type `T`, then return an `Uninitialized` pointer to the memory.
+    /// Write a value at a given `index`. Panics if `index` is out of bounds.
     #[inline]
-    pub fn alloc_uninitialized<'arena, T: Sized + Copy>(&'arena self) -> Uninitialized<'arena, T> {
-        Uninitialized {
-            pointer: unsafe { &mut *(self.require(size_of::<T>()) as *mut MaybeUninit<T>) },
-        }
+    pub fn write(&mut self, index: usize, value: T) {
+        self.pointer[index] = MaybeUninit::new(value);
     }
 
-    /// Allocate a slice of `T` slice onto the arena and return a reference to it.
-    /// This is useful when the original slice has an undefined lifetime.
+    /// Initialize the memory with elements from an iterator. Elements yielded
+    /// by the iterator past the length of the slice are ignored.
     ///
-    /// Note: static slices (`&'static [T]`) can be safely used in place of arena-bound
-    ///       slices without having to go through this method.
-    pub fn alloc_slice<'arena, T: Copy>(&'arena self, val: &[T]) -> &'arena [T] {
-        let ptr = self.require(val.len() * size_of::<T>()) as *mut T;
+    /// Panics if the iterator yields fewer elements than the length of the slice.
+    pub fn init_with<I>(self, iter: I) -> &'arena mut [T]
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
 
-        unsafe {
-            use std::ptr::copy_nonoverlapping;
-            use std::slice::from_raw_parts;
+        for slot in self.pointer.iter_mut() {
+            let value = iter.next().expect("Iterator yielded fewer elements than the length of the slice");
 
-            copy_nonoverlapping(val.as_ptr(), ptr, val.len());
-            from_raw_parts(ptr, val.len())
+            *slot = MaybeUninit::new(value);
         }
+
+        unsafe { self.as_mut_slice() }
     }
 
-    /// Allocate a statically-sized but lazily-generated slice `[T]` out of an iterator
-    /// This is useful if you're going to make a slice of something and put it on the arena,
-    /// but you don't want to make an allocation first just to have something to copy in.
+    /// Initialize the memory with a copy of `source`.
     ///
-    /// The slice will be at maximum length `n`, further elements of the iterator ignored and not evaluated.
-    /// If the iterator yields less than `n` elements, a shorter slice will simply be returned.
-    pub fn alloc_lazy_slice<'arena, T, I: Iterator<Item=T>>(&'arena self, vals: I, n: usize) -> &'arena [T] {
-      // Grab space for `n` elements even if it may turn out we have to walk it back
-      let ptr = self.require(n * size_of::<T>()) as *mut T;
-      let mut i: usize = 0; 
+    /// Panics if the length of `source` differs from the length of the slice.
+    #[inline]
+    pub fn copy_from_slice(self, source: &[T]) -> &'arena mut [T] {
+        assert_eq!(self.pointer.len(), source.len(), "source slice length doesn't match the length of the slice");
 
-      unsafe {
-        use std::slice::from_raw_parts;
+        unsafe {
+            std::ptr::copy_nonoverlapping(source.as_ptr(), self.pointer.as_mut_ptr() as *mut T, source.len());
 
-        for val in vals.take(n) {
-          *ptr.offset(i as isize) = val;
-          i += 1;
+            self.as_mut_slice()
         }
-        // Now fix the slice length and arena offset
-        let diff = n - i;
-        self.reset_to( self.offset() - diff * size_of::<T>() );
-        from_raw_parts(ptr, i)
-      }
     }
 
-    /// Put a `Vec<T>` on the arena without reallocating.
-    pub fn alloc_vec<'arena, T: Copy>(&'arena self, mut val: Vec<T>) -> &'arena [T] {
-        use std::{mem, slice};
-
-        let ptr = val.as_mut_ptr();
-        let cap = val.capacity();
-        let len = val.len();
+    /// Initialize all elements of the slice with copies of `value`.
+    pub fn fill(self, value: T) -> &'arena mut [T] {
+        for slot in self.pointer.iter_mut() {
+            *slot = MaybeUninit::new(value);
+        }
 
-        mem::forget(val);
+        unsafe { self.as_mut_slice() }
+    }
 
-        let out = self.alloc_byte_vec(unsafe {
-            Vec::from_raw_parts(ptr as _, 0, cap * size_of::<T>())
-        });
+    /// Get a reference to the slice without writing to it.
+    ///
+    /// # Safety
+    ///
+    /// All elements of the slice must have been initialized, otherwise
+    /// calling this method is undefined behavior.
+    #[inline]
+    pub unsafe fn as_slice(&self) -> &'arena [T] {
+        std::slice::from_raw_parts(self.pointer.as_ptr() as *const T, self.pointer.len())
+    }
 
-        unsafe { slice::from_raw_parts(out as _, len) }
+    /// Convert the `UninitializedSlice` to a regular mutable slice.
+    ///
+    /// # Safety
+    ///
+    /// All elements of the slice must have been initialized, otherwise
+    /// calling this method is undefined behavior.
+    #[inline]
+    pub unsafe fn as_mut_slice(self) -> &'arena mut [T] {
+        std::slice::from_raw_parts_mut(self.pointer.as_mut_ptr() as *mut T, self.pointer.len())
     }
 
-    /// Allocate many items at once, avoid allocation for owned values.
+    /// Create an `UninitializedSlice` of `len` elements starting at `pointer`,
+    /// which can point to memory outside of the arena.
+    ///
+    /// # Safety
+    ///
+    /// The `pointer` must be non-null, properly aligned, and valid for writes
+    /// of `len` elements for the whole `'arena` lifetime, which is picked by
+    /// the caller. Nothing else may access the memory during that lifetime.
     #[inline]
-    pub fn alloc_cow<'input, 'arena, T>(&'arena self, vals: Cow<'input, [T]>) -> &'arena [T]
-    where
-        T: Sized + Copy + 'input,
-    {
-        match vals {
-            Cow::Owned(vec)      => self.alloc_vec(vec),
-            Cow::Borrowed(slice) => self.alloc_slice(slice),
+    pub unsafe fn from_raw_parts(pointer: *mut T, len: usize) -> Self {
+        UninitializedSlice {
+            pointer: std::slice::from_raw_parts_mut(pointer as *mut MaybeUninit<T>, len),
         }
     }
+}
 
-    /// Allocate an `&str` slice onto the arena and return a reference to it. This is
-    /// useful when the original slice has an undefined lifetime.
+impl<'arena> UninitializedSlice<'arena, u8> {
+    /// Initialize the memory with a copy of the bytes of `source`.
     ///
-    /// Note: static slices (`&'static str`) can be safely used in place of arena-bound
-    ///       slices without having to go through this method.
-    pub fn alloc_str<'arena>(&'arena self, val: &str) -> &'arena str {
-        unsafe {
-            use std::str::from_utf8_unchecked;
+    /// Panics if the length of `source` differs from the length of the slice.
+    #[inline]
+    pub fn copy_from_str(self, source: &str) -> &'arena mut str {
+        unsafe { std::str::from_utf8_unchecked_mut(self.copy_from_slice(source.as_bytes())) }
+    }
+}
 
-            from_utf8_unchecked(self.alloc_slice(val.as_bytes()))
-        }
+impl<'arena, T: Copy> From<&'arena mut [T]> for UninitializedSlice<'arena, T> {
+    #[inline]
+    fn from(slice: &'arena mut [T]) -> Self {
+        unsafe { Self::from_raw_parts(slice.as_mut_ptr(), slice.len()) }
     }
+}
 
-    /// Allocate an `&str` slice onto the arena as null terminated C-style string.
-    /// No checks are performed on the source and whether or not it already contains
-    /// any nul bytes. While this does not create any memory issues, it assumes that
-    /// the reader of the source can deal with malformed source.
-    pub fn alloc_nul_term_str<'arena>(&'arena self, val: &str) -> NulTermStr {
-        let len_with_zero = val.len() + 1;
-        let ptr = self.require(len_with_zero);
+/// A writable region of bytes on the arena, returned by
+/// `Arena::alloc_str_uninit`, that becomes a `str` of any length up to the
+/// size of the region once written.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// let arena = Arena::new();
+/// let escaped = r"doge\tmoon";
+/// let mut region = arena.alloc_str_uninit(escaped.len());
+/// let mut len = 0;
+/// let mut chars = escaped.chars();
+///
+/// while let Some(ch) = chars.next() {
+///     let ch = match ch {
+///         '\\' => match chars.next() {
+///             Some('t') => '\t',
+///             other     => other.unwrap_or('\\'),
+///         },
+///         ch => ch,
+///     };
+///
+///     len = region.write_str(len, ch.encode_utf8(&mut [0; 4]));
+/// }
+///
+/// let decoded = unsafe { region.finish(len) };
+///
+/// assert_eq!(decoded, "doge\tmoon");
+/// ```
+pub struct UninitializedStr<'arena> {
+    arena: &'arena Arena,
+    bytes: &'arena mut [MaybeUninit<u8>],
+}
 
-        unsafe {
-            use std::ptr::copy_nonoverlapping;
-            use std::slice::from_raw_parts;
-            use std::str::from_utf8_unchecked;
+impl<'arena> UninitializedStr<'arena> {
+    /// Number of bytes the region can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
 
-            copy_nonoverlapping(val.as_ptr(), ptr, val.len());
-            *ptr.add(val.len()) = 0;
+    /// Write a byte at a given `index`. Panics if `index` is out of bounds.
+    #[inline]
+    pub fn write(&mut self, index: usize, byte: u8) {
+        self.bytes[index] = MaybeUninit::new(byte);
+    }
 
-            NulTermStr(from_utf8_unchecked(from_raw_parts(ptr, val.len())))
+    /// Copy the bytes of `source` into the region starting at `index`, and
+    /// return the index just past them. Panics if `source` doesn't fit.
+    #[inline]
+    pub fn write_str(&mut self, index: usize, source: &str) -> usize {
+        let end = index + source.len();
+
+        for (slot, byte) in self.bytes[index..end].iter_mut().zip(source.bytes()) {
+            *slot = MaybeUninit::new(byte);
         }
+
+        end
     }
 
-    /// Pushes the `String` as it's own page onto the arena and returns a reference to it.
-    /// This does not copy or reallocate the original `String`.
-    pub fn alloc_string<'arena>(&'arena self, val: String) -> &'arena str {
-        let len = val.len();
-        let ptr = self.alloc_byte_vec(val.into_bytes());
+    /// Get a raw pointer to the start of the region.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.bytes.as_mut_ptr() as *mut u8
+    }
+
+    /// Finish writing and return the first `len` bytes of the region as a
+    /// `str`. The rest of the region is returned to the arena, if nothing
+    /// else has been allocated since. Panics if `len` exceeds the capacity.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` bytes of the region must have been written, and must
+    /// be valid UTF-8.
+    #[inline]
+    pub unsafe fn finish(self, len: usize) -> &'arena str {
+        assert!(len <= self.bytes.len(), "length exceeds the capacity of the region");
+
+        let ptr = self.bytes.as_mut_ptr() as *mut u8;
+
+        self.arena.resize_in_place(ptr, padded_size(self.bytes.len()), padded_size(len));
+
+        std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+impl<'arena> fmt::Debug for UninitializedStr<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UninitializedStr")
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// Marker for `Copy` types that have every byte of their representation
+/// initialized, such as integers, references, or arrays of those. Values of
+/// such types can be viewed as bytes.
+///
+/// # Safety
+///
+/// The type must not contain any padding bytes, and must not contain
+/// `MaybeUninit`s or unions that might hold uninitialized bytes. Structs
+/// implementing this trait should be `#[repr(C)]` or `#[repr(transparent)]`,
+/// with fields laid out so that no padding is needed between them.
+pub unsafe trait NoUninit: Copy {}
+
+macro_rules! impl_no_uninit {
+    ($( $ty:ty ),*) => {
+        $(
+            unsafe impl NoUninit for $ty {}
+        )*
+    };
+}
+
+impl_no_uninit!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ());
+impl_no_uninit!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize);
+impl_no_uninit!(Option<NonZeroU8>, Option<NonZeroU16>, Option<NonZeroU32>, Option<NonZeroU64>, Option<NonZeroUsize>);
+
+unsafe impl<T: ?Sized> NoUninit for &T {}
+unsafe impl<T: Sized> NoUninit for Option<&T> {}
+unsafe impl<T: ?Sized> NoUninit for *const T {}
+unsafe impl<T: ?Sized> NoUninit for *mut T {}
+unsafe impl<T: Sized> NoUninit for NonNull<T> {}
+unsafe impl<T: Sized> NoUninit for Option<NonNull<T>> {}
+unsafe impl<T: NoUninit, const N: usize> NoUninit for [T; N] {}
+
+/// A cursor over a region of memory reserved on the arena with
+/// `Arena::reserve_region`. Values put through the cursor are laid out one
+/// after another within the region, only separated by padding required for
+/// their alignment, regardless of any other allocations made on the arena
+/// in the meantime.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// let arena = Arena::new();
+/// let mut cursor = arena.reserve_region(16);
+///
+/// cursor.put(4u32);
+/// arena.alloc(0u64); // doesn't break adjacency
+/// cursor.put_slice(b"doge");
+///
+/// assert_eq!(cursor.finish(), &[4, 0, 0, 0, b'd', b'o', b'g', b'e']);
+/// ```
+pub struct PageCursor<'arena> {
+    arena: &'arena Arena,
+    region: &'arena mut [MaybeUninit<u8>],
+    position: usize,
+}
+
+impl<'arena> PageCursor<'arena> {
+    /// Number of bytes written to the region so far, including padding.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Total size of the region in bytes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Number of bytes left in the region.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.region.len() - self.position
+    }
+
+    /// Put the value at the next position in the region aligned for `T`, and
+    /// return a reference to it, which can be used to patch the value until
+    /// something else is put through the cursor. Since `finish` returns the
+    /// region as bytes, `T` can't have any padding.
+    ///
+    /// **Panics** if the value doesn't fit in the remaining space of the region.
+    #[inline]
+    pub fn put<T: NoUninit>(&mut self, value: T) -> &mut T {
+        self.try_put(value).expect("value doesn't fit in the remaining space of the region")
+    }
+
+    /// Same as `put`, but returns `None` if the value doesn't fit in the
+    /// remaining space of the region.
+    #[inline]
+    pub fn try_put<T: NoUninit>(&mut self, value: T) -> Option<&mut T> {
+        let ptr = self.advance(size_of::<T>(), align_of::<T>())? as *mut T;
 
         unsafe {
-            use std::str::from_utf8_unchecked;
-            use std::slice::from_raw_parts;
+            ptr.write(value);
+
+            Some(&mut *ptr)
+        }
+    }
+
+    /// Put a copy of the slice at the next position in the region aligned
+    /// for `T`, and return a reference to it.
+    ///
+    /// **Panics** if the slice doesn't fit in the remaining space of the region.
+    #[inline]
+    pub fn put_slice<T: NoUninit>(&mut self, values: &[T]) -> &mut [T] {
+        self.try_put_slice(values).expect("slice doesn't fit in the remaining space of the region")
+    }
+
+    /// Same as `put_slice`, but returns `None` if the slice doesn't fit in the
+    /// remaining space of the region.
+    pub fn try_put_slice<T: NoUninit>(&mut self, values: &[T]) -> Option<&mut [T]> {
+        let size = values.len().checked_mul(size_of::<T>())?;
+        let ptr = self.advance(size, align_of::<T>())? as *mut T;
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(values.as_ptr(), ptr, values.len());
+
+            Some(std::slice::from_raw_parts_mut(ptr, values.len()))
+        }
+    }
+
+    /// Finish writing and return all bytes written to the region, with padding
+    /// between values set to zero. The unused remainder of the region is
+    /// returned to the arena, if nothing else has been allocated since.
+    pub fn finish(self) -> &'arena [u8] {
+        let ptr = self.region.as_mut_ptr() as *mut u8;
+
+        self.arena.resize_in_place(ptr, padded_size(self.region.len()), padded_size(self.position));
+
+        unsafe { std::slice::from_raw_parts(ptr, self.position) }
+    }
+
+    /// Move the position past `size` bytes aligned to `align`, zeroing the
+    /// padding, and return a pointer to the start of those bytes.
+    #[inline]
+    fn advance(&mut self, size: usize, align: usize) -> Option<*mut u8> {
+        let base = self.region.as_ptr() as usize;
+        let start = ((base + self.position + (align - 1)) & !(align - 1)) - base;
+        let end = start.checked_add(size)?;
+
+        if end > self.region.len() {
+            return None;
+        }
+
+        for byte in &mut self.region[self.position..start] {
+            *byte = MaybeUninit::new(0);
+        }
+
+        self.position = end;
+
+        Some(self.region[start..].as_mut_ptr() as *mut u8)
+    }
+}
+
+impl<'arena> fmt::Debug for PageCursor<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PageCursor")
+            .field("position", &self.position)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// A wrapper around a `str` slice that has an extra `0` byte allocated following
+/// its contents.
+#[derive(Clone, Copy, PartialEq)]
+pub struct NulTermStr<'arena>(&'arena str);
+
+impl<'arena> fmt::Debug for NulTermStr<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
+
+impl<'arena> fmt::Display for NulTermStr<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.0, f)
+    }
+}
+
+impl<'arena> NulTermStr<'arena> {
+    /// Read byte at a given `index`. This does not check for length boundaries,
+    /// but is guaranteed to return `0` for `index` equal to the length.
+    ///
+    /// This can be a very useful optimization when reading a long string one
+    /// byte at a time until termination, if checking for `0` can replace what
+    /// would otherwise have to be length checks.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// # fn main() {
+    /// let arena = Arena::new();
+    /// let str = arena.alloc_nul_term_str("foo");
+    ///
+    /// // We can safely get the underlying `&str` at any time.
+    /// assert_eq!(&str[..], "foo");
+    ///
+    /// unsafe {
+    ///     // First 3 bytes are known to us
+    ///     assert_eq!(str.byte_unchecked(0), b'f');
+    ///     assert_eq!(str.byte_unchecked(1), b'o');
+    ///     assert_eq!(str.byte_unchecked(2), b'o');
+    ///
+    ///     // Following is safe and guaranteed to be '0'
+    ///     assert_eq!(str.byte_unchecked(3), 0);
+    ///
+    ///     // Reading index 4 would be undefined behavior!
+    /// }
+    /// # }
+    /// ```
+    pub unsafe fn byte_unchecked(&self, index: usize) -> u8 {
+        *self.0.as_ptr().add(index)
+    }
+
+    /// Read byte at a given `index`, returning `0` for any `index` past the
+    /// end of the string.
+    #[inline]
+    pub fn byte(&self, index: usize) -> u8 {
+        self.0.as_bytes().get(index).copied().unwrap_or(0)
+    }
+
+    /// Get the underlying `&str`, without the terminating `0` byte.
+    #[inline]
+    pub fn as_str(&self) -> &'arena str {
+        self.0
+    }
+
+    /// Get the string as a `&CStr` for FFI use. If the source contained
+    /// any nul bytes, the `&CStr` ends at the first one of them.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    /// let str = arena.alloc_nul_term_str("foo");
+    ///
+    /// assert_eq!(str.as_c_str().to_bytes_with_nul(), b"foo\0");
+    /// ```
+    #[inline]
+    pub fn as_c_str(&self) -> &'arena CStr {
+        let bytes = unsafe { std::slice::from_raw_parts(self.0.as_ptr(), self.0.len() + 1) };
+
+        CStr::from_bytes_until_nul(bytes).expect("NulTermStr is always terminated with a 0 byte")
+    }
+}
+
+impl<'arena> AsRef<CStr> for NulTermStr<'arena> {
+    fn as_ref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl<'arena> From<NulTermStr<'arena>> for &'arena CStr {
+    fn from(nts: NulTermStr<'arena>) -> &'arena CStr {
+        nts.as_c_str()
+    }
+}
+
+impl<'arena> AsRef<str> for NulTermStr<'arena> {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl<'arena> Deref for NulTermStr<'arena> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl<'arena> From<NulTermStr<'arena>> for &'arena str {
+    fn from(nts: NulTermStr<'arena>) -> &'arena str {
+        nts.0
+    }
+}
+
+impl Arena {
+    /// Create a new arena with a single preallocated 64KiB page.
+    pub fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// Create a new arena that will never hold more than `limit` bytes of
+    /// heap memory. Allocations that would go over the limit fail: the
+    /// `try_alloc*` methods return an `AllocError`, while all other
+    /// allocation methods call the hook set with `set_alloc_error_hook`, if
+    /// any, and panic.
+    ///
+    /// The limit covers all pages, as well as any `Vec`s and `String`s
+    /// adopted by `alloc_vec` and `alloc_string`. The first 64KiB page is
+    /// always preallocated and counted towards the limit, even if the limit
+    /// is lower than that, in which case nothing else can be allocated.
+    pub fn with_limit(limit: usize) -> Self {
+        let store = vec![Page::from_vec(Vec::<usize>::with_capacity(ARENA_BLOCK / size_of::<usize>()))];
+        let ptr = store[0].ptr.as_ptr();
+
+        Arena {
+            store: Cell::new(store),
+            adopted: Cell::new(Vec::new()),
+            page: Cell::new(0),
+            ptr: Cell::new(ptr),
+            offset: Cell::new(0),
+            allocated: Cell::new(ARENA_BLOCK),
+            wasted: Cell::new(0),
+            limit,
+            recycle_pages: false,
+            free: Default::default(),
+            recycled: Cell::new(0),
+        }
+    }
+
+    /// Enable or disable page recycling. With page recycling enabled, `clear`
+    /// rewinds the arena to its first page, so that the following allocations
+    /// reuse the pages that have already been allocated instead of allocating
+    /// new ones. Adopted `Vec`s and `String`s, as well as allocations larger
+    /// than a page, are freed by `clear` in this mode.
+    ///
+    /// Without page recycling, `clear` only rewinds the current page.
+    #[inline]
+    pub fn set_page_recycling(&mut self, recycle: bool) {
+        self.recycle_pages = recycle;
+    }
+
+    /// Total number of bytes of heap memory held by the arena.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.get()
+    }
+
+    /// Number of bytes of unused capacity in `Vec`s and `String`s adopted
+    /// by `alloc_vec` and `alloc_string`. This memory is counted towards
+    /// `allocated_bytes`, but can never be used by the arena.
+    #[inline]
+    pub fn wasted_bytes(&self) -> usize {
+        self.wasted.get()
+    }
+
+    /// Number of bytes of heap memory the arena would have to allocate to
+    /// hold another `size` bytes, or `0` if they fit on the current page or
+    /// on a page kept by `reset`.
+    ///
+    /// When running on WebAssembly, this can be used to `memory.grow` the
+    /// linear memory ahead of time, by the number of 64KiB pages needed.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    ///
+    /// assert_eq!(arena.grow_hint(8), 0);
+    /// assert_eq!(arena.grow_hint(1024 * 1024), 1024 * 1024);
+    /// ```
+    pub fn grow_hint(&self, size: usize) -> usize {
+        if size > ARENA_BLOCK {
+            return size;
+        }
+
+        if self.offset.get() + padded_size(size) <= ARENA_BLOCK {
+            return 0;
+        }
+
+        let store = self.store.replace(Vec::new());
+        let reusable = store.len() > self.page.get() + 1;
+
+        self.store.replace(store);
+
+        if reusable { 0 } else { ARENA_BLOCK }
+    }
+
+    /// Maximum number of bytes of heap memory the arena is allowed to hold,
+    /// as set by `with_limit`.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns `true` if `ptr` points to memory allocated on this arena.
+    ///
+    /// This is meant for debug assertions when multiple arenas are in use,
+    /// to catch references to values on the wrong arena early. It has to
+    /// check every page of the arena, so it shouldn't be used in hot code.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    ///
+    /// let a = Arena::new();
+    /// let b = Arena::new();
+    ///
+    /// let value = a.alloc(42u64);
+    ///
+    /// assert!(a.contains(value));
+    /// assert!(!b.contains(value));
+    /// ```
+    pub fn contains<T: ?Sized>(&self, ptr: *const T) -> bool {
+        self.page_pointer(ptr as *const u8 as usize).is_some()
+    }
+
+    /// Find the page or adopted buffer the `address` is in, and return a
+    /// pointer to it derived from the pointer to that page, so that it can
+    /// be written through regardless of how the `address` was obtained.
+    fn page_pointer(&self, address: usize) -> Option<*mut u8> {
+        let current = self.page.get();
+
+        let store = self.store.replace(Vec::new());
+        let adopted = self.adopted.replace(Vec::new());
+
+        // Only count the used part of the current page, and none of the
+        // pages after it, which are left over for recycling. Recent pages
+        // are checked first.
+        let found = store[..=current].iter().enumerate().rev().find_map(|(index, page)| {
+            let used = if index == current { self.offset.get() } else { page.layout.size() };
+
+            page.pointer_to(address, used)
+        }).or_else(|| adopted.iter().find_map(|page| page.pointer_to(address, page.layout.size())));
+
+        self.store.replace(store);
+        self.adopted.replace(adopted);
+
+        found
+    }
+
+    /// Put the value onto the page of the arena and return a reference to it.
+    #[inline]
+    pub fn alloc<'arena, T: Sized + Copy>(&'arena self, value: T) -> &'arena mut T {
+        self.alloc_uninitialized().init(value)
+    }
+
+    /// Put the value produced by the closure `f` onto the page of the arena and
+    /// return a reference to it.
+    ///
+    /// Memory is reserved before `f` is called, which allows the compiler to
+    /// construct the value directly in place, instead of building it on the
+    /// stack and copying it over as `alloc` would for large types.
+    #[inline(always)]
+    pub fn alloc_with<T, F>(&self, f: F) -> &mut T
+    where
+        T: Sized + Copy,
+        F: FnOnce() -> T,
+    {
+        self.alloc_uninitialized().init(f())
+    }
+
+    /// Put a value that refers to itself onto the arena, similarly to
+    /// `Rc::new_cyclic`. This is handy for building structures with parent
+    /// pointers or doubly-linked nodes.
+    ///
+    /// The closure `f` receives a `CyclicRef` to the value, which it can store
+    /// in the value it produces, or in other values it allocates. The
+    /// `CyclicRef` resolves to the value only after `f` has returned, so
+    /// there is never a reference to the value before it's initialized.
+    ///
+    /// ```rust
+    /// use toolshed::{Arena, CyclicRef};
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct Node<'arena> {
+    ///     name: &'arena str,
+    ///     parent: Option<CyclicRef<'arena, Node<'arena>>>,
+    ///     child: Option<&'arena Node<'arena>>,
+    /// }
+    ///
+    /// let arena = Arena::new();
+    ///
+    /// let root = arena.alloc_cyclic(|root| Node {
+    ///     name: "root",
+    ///     parent: None,
+    ///     child: Some(arena.alloc(Node { name: "child", parent: Some(root), child: None })),
+    /// });
+    ///
+    /// let parent = root.child.unwrap().parent.unwrap().get().unwrap();
+    ///
+    /// assert_eq!(parent.name, "root");
+    /// assert!(std::ptr::eq(parent, root));
+    /// ```
+    pub fn alloc_cyclic<'arena, T, F>(&'arena self, f: F) -> &'arena T
+    where
+        T: Sized + Copy,
+        F: FnOnce(CyclicRef<'arena, T>) -> T,
+    {
+        let slot = self.alloc_node(CyclicSlot {
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            ready: CopyCell::new(false),
+        });
+
+        let value = f(CyclicRef { slot });
+
+        // `CyclicRef::get` doesn't hand out references until `ready` is set,
+        // so nothing can be borrowing the value while it's written.
+        unsafe {
+            (*slot.value.get()).write(value);
+        }
+
+        slot.ready.set(true);
+
+        unsafe { &*(slot.value.get() as *const T) }
+    }
+
+    /// Put a value onto the arena, same as `alloc`, for the nodes of the data
+    /// structures in this crate, which hold `CopyCell`s and so can't be `Copy`.
+    #[inline]
+    pub(crate) fn alloc_node<T>(&self, value: T) -> &T {
+        // Nothing on the arena is ever dropped
+        debug_assert!(!needs_drop::<T>());
+
+        let ptr = match self.take_recycled(size_of::<T>(), align_of::<T>()) {
+            Some(ptr) => ptr,
+            None      => self.require_aligned(size_of::<T>(), align_of::<T>()),
+        } as *mut T;
+
+        unsafe {
+            ptr.write(value);
+
+            &*ptr
+        }
+    }
+
+    /// Put all nodes yielded by an iterator of known length onto the arena,
+    /// next to each other, same as `alloc_slice_from_iter`. See `alloc_node`.
+    pub(crate) fn alloc_node_slice<T, I>(&self, iter: I) -> &[T]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        debug_assert!(!needs_drop::<T>());
+
+        let mut iter = iter.into_iter();
+        let len = iter.len();
+        let size = len.checked_mul(size_of::<T>()).expect("capacity overflow");
+        let ptr = self.require_aligned(size, align_of::<T>()) as *mut T;
+
+        for index in 0..len {
+            let node = iter.next().expect("Iterator yielded fewer elements than it reported");
+
+            unsafe { ptr.add(index).write(node) };
+        }
+
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+
+    /// Allocate enough bytes for the type `T`, then return an `Uninitialized` pointer to the memory.
+    #[inline]
+    pub fn alloc_uninitialized<'arena, T: Sized + Copy>(&'arena self) -> Uninitialized<'arena, T> {
+        let ptr = match self.take_recycled(size_of::<T>(), align_of::<T>()) {
+            Some(ptr) => ptr,
+            None      => self.require_aligned(size_of::<T>(), align_of::<T>()),
+        };
+
+        Uninitialized {
+            pointer: unsafe { &mut *(ptr as *mut MaybeUninit<T>) },
+        }
+    }
+
+    /// Allocate enough bytes for `len` elements of type `T`, then return an
+    /// `UninitializedSlice` pointer to the memory.
+    #[inline]
+    pub fn alloc_uninitialized_slice<'arena, T: Sized + Copy>(&'arena self, len: usize) -> UninitializedSlice<'arena, T> {
+        let size = len.checked_mul(size_of::<T>()).expect("capacity overflow");
+        let ptr = self.require_aligned(size, align_of::<T>()) as *mut MaybeUninit<T>;
+
+        UninitializedSlice {
+            pointer: unsafe { std::slice::from_raw_parts_mut(ptr, len) },
+        }
+    }
+
+    /// Put the default value of `T` onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc_default<T: Copy + Default>(&self) -> &mut T {
+        self.alloc_uninitialized().init(T::default())
+    }
+
+    /// Allocate a slice of `len` elements onto the arena, all set to the
+    /// default value of `T`, and return a reference to it.
+    #[inline]
+    pub fn alloc_slice_default<T: Copy + Default>(&self, len: usize) -> &mut [T] {
+        self.alloc_uninitialized_slice(len).fill(T::default())
+    }
+
+    /// Allocate enough bytes for the type `T`, all set to zero, then return an
+    /// `Uninitialized` pointer to the memory.
+    ///
+    /// Calling `assume_init` on it is only sound if all-zero bytes are a valid
+    /// value of `T`, which is not the case for references or `NonZero*`
+    /// integers, among others.
+    #[inline]
+    pub fn alloc_zeroed<'arena, T: Copy>(&'arena self) -> Uninitialized<'arena, T> {
+        unsafe { Uninitialized::from_raw(self.require_zeroed(size_of::<T>(), align_of::<T>()) as *mut T) }
+    }
+
+    /// Allocate enough bytes for `len` elements of type `T`, all set to zero,
+    /// then return an `UninitializedSlice` pointer to the memory. Slices larger
+    /// than a page are allocated directly as zeroed memory, without being
+    /// written over.
+    ///
+    /// Calling `as_mut_slice` on it is only sound if all-zero bytes are a valid
+    /// value of `T`, which is not the case for references or `NonZero*`
+    /// integers, among others.
+    #[inline]
+    pub fn alloc_slice_zeroed<'arena, T: Copy>(&'arena self, len: usize) -> UninitializedSlice<'arena, T> {
+        let size = len.checked_mul(size_of::<T>()).expect("capacity overflow");
+
+        unsafe { UninitializedSlice::from_raw_parts(self.require_zeroed(size, align_of::<T>()) as *mut T, len) }
+    }
+
+    /// Allocate a buffer of `len` zeroed bytes onto the arena and return a
+    /// mutable reference to it. This is useful for scratch buffers that need
+    /// to live as long as the arena, such as ones for unescaping strings.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    /// let buffer = arena.alloc_bytes(4);
+    ///
+    /// buffer.copy_from_slice(b"doge");
+    ///
+    /// assert_eq!(buffer, b"doge");
+    /// ```
+    #[inline]
+    pub fn alloc_bytes(&self, len: usize) -> &mut [u8] {
+        self.alloc_slice_default(len)
+    }
+
+    /// Reserve a contiguous region of `size` bytes on the arena, aligned to at
+    /// least the size of `usize`, and return a `PageCursor` for laying out
+    /// values within it. Values put through the cursor are guaranteed to be
+    /// adjacent, even if other allocations are made on the arena meanwhile.
+    #[inline]
+    pub fn reserve_region<'arena>(&'arena self, size: usize) -> PageCursor<'arena> {
+        let ptr = self.require_aligned(size, size_of::<usize>()) as *mut MaybeUninit<u8>;
+
+        PageCursor {
+            arena: self,
+            region: unsafe { std::slice::from_raw_parts_mut(ptr, size) },
+            position: 0,
+        }
+    }
+
+    /// Fallible variant of `alloc`. Returns an `AllocError` instead of aborting
+    /// if the memory for the value could not be obtained.
+    #[inline]
+    pub fn try_alloc<T: Sized + Copy>(&self, value: T) -> Result<&mut T, AllocError> {
+        Ok(self.try_alloc_uninitialized()?.init(value))
+    }
+
+    /// Fallible variant of `alloc_uninitialized`.
+    #[inline]
+    pub fn try_alloc_uninitialized<'arena, T: Sized + Copy>(&'arena self) -> Result<Uninitialized<'arena, T>, AllocError> {
+        let ptr = self.try_require_aligned(size_of::<T>(), align_of::<T>())?;
+
+        Ok(Uninitialized {
+            pointer: unsafe { &mut *(ptr as *mut MaybeUninit<T>) },
+        })
+    }
+
+    /// Allocate a slice of `T` slice onto the arena and return a reference to it.
+    /// This is useful when the original slice has an undefined lifetime.
+    ///
+    /// Note: static slices (`&'static [T]`) can be safely used in place of arena-bound
+    ///       slices without having to go through this method.
+    pub fn alloc_slice<'arena, T: Copy>(&'arena self, val: &[T]) -> &'arena [T] {
+        self.alloc_slice_mut(val)
+    }
+
+    /// Same as `alloc_slice`, but returns a mutable reference, so that the
+    /// copy can be patched in place afterwards.
+    #[inline]
+    pub fn alloc_slice_mut<'arena, T: Copy>(&'arena self, val: &[T]) -> &'arena mut [T] {
+        self.alloc_uninitialized_slice(val.len()).copy_from_slice(val)
+    }
+
+    /// Fallible variant of `alloc_slice`.
+    pub fn try_alloc_slice<'arena, T: Copy>(&'arena self, val: &[T]) -> Result<&'arena [T], AllocError> {
+        let size = val.len().checked_mul(size_of::<T>()).ok_or(AllocError { size: usize::MAX, limit_exceeded: false })?;
+        let ptr = self.try_require_aligned(size, align_of::<T>())? as *mut T;
+
+        unsafe {
+            use std::ptr::copy_nonoverlapping;
+            use std::slice::from_raw_parts;
+
+            copy_nonoverlapping(val.as_ptr(), ptr, val.len());
+            Ok(from_raw_parts(ptr, val.len()))
+        }
+    }
+
+    /// Allocate a slice of tuples onto the arena as a tuple of parallel slices
+    /// (struct-of-arrays layout) and return references to them. All slices
+    /// share a single contiguous block of memory, each of them aligned for
+    /// its own element type.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// # fn main() {
+    /// let arena = Arena::new();
+    /// let (ids, names) = arena.alloc_struct_of_slices(&[(1u32, "foo"), (2, "bar")]);
+    ///
+    /// assert_eq!(ids, &[1, 2]);
+    /// assert_eq!(names, &["foo", "bar"]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn alloc_struct_of_slices<'arena, S>(&'arena self, items: &[S]) -> S::Slices
+    where
+        S: StructOfSlices<'arena>,
+    {
+        S::alloc_in(self, items)
+    }
+
+    /// Allocate a statically-sized but lazily-generated slice `[T]` out of an iterator
+    /// This is useful if you're going to make a slice of something and put it on the arena,
+    /// but you don't want to make an allocation first just to have something to copy in.
+    ///
+    /// The slice will be at maximum length `n`, further elements of the iterator ignored and not evaluated.
+    /// If the iterator yields less than `n` elements, a shorter slice will simply be returned.
+    pub fn alloc_lazy_slice<'arena, T, I: Iterator<Item=T>>(&'arena self, vals: I, n: usize) -> &'arena [T] {
+      // Grab space for `n` elements even if it may turn out we have to walk it back
+      let size = n * size_of::<T>();
+      let ptr = self.require_aligned(size, align_of::<T>()) as *mut T;
+      let mut i: usize = 0;
+
+      unsafe {
+        use std::slice::from_raw_parts;
+
+        for val in vals.take(n) {
+          // Memory is uninitialized, it must not be dropped by assignment
+          ptr.add(i).write(val);
+          i += 1;
+        }
+        // Now fix the slice length and arena offset, keeping the offset aligned
+        self.resize_in_place(ptr as *mut u8, padded_size(size), padded_size(i * size_of::<T>()));
+        from_raw_parts(ptr, i)
+      }
+    }
+
+    /// Allocate a slice with all elements of an iterator of known length onto
+    /// the arena and return a reference to it. Exactly `len()` elements are
+    /// reserved up front, so nothing has to be walked back afterwards.
+    ///
+    /// Elements yielded past the reported length are ignored, and the method
+    /// panics if the iterator yields fewer elements than it reported.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    /// let squares = arena.alloc_slice_from_iter((1..5u32).map(|n| n * n));
+    ///
+    /// assert_eq!(squares, &[1, 4, 9, 16]);
+    /// ```
+    #[inline]
+    pub fn alloc_slice_from_iter<T, I>(&self, iter: I) -> &mut [T]
+    where
+        T: Copy,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+
+        self.alloc_uninitialized_slice(iter.len()).init_with(iter)
+    }
+
+    /// Allocate a slice with the results of applying `f` to all elements of
+    /// an iterator of known length. Every result is written straight into
+    /// its place on the arena, without collecting them anywhere first.
+    ///
+    /// Same as with `alloc_slice_from_iter`, the method panics if the
+    /// iterator yields fewer elements than it reported.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    /// let lengths = arena.alloc_slice_map(&["doge", "to", "the", "moon"], |word| word.len());
+    ///
+    /// assert_eq!(lengths, &[4, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn alloc_slice_map<I, T, U, F>(&self, iter: I, f: F) -> &mut [U]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+        U: Copy,
+        F: FnMut(T) -> U,
+    {
+        let iter = iter.into_iter();
+
+        self.alloc_uninitialized_slice(iter.len()).init_with(iter.map(f))
+    }
+
+    /// Put a `Vec<T>` on the arena without reallocating.
+    ///
+    /// Any unused capacity of the `Vec` is kept around for as long as the
+    /// arena lives, and is reported by `wasted_bytes`. The whole capacity
+    /// counts towards the limit set by `with_limit`.
+    pub fn alloc_vec<T: Copy>(&self, val: Vec<T>) -> &[T] {
+        let len = val.len();
+        let ptr = self.adopt_vec(val);
+
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+
+    /// Put a `Vec<T>` on the arena, first shrinking its capacity to fit its
+    /// length. Shrinking might reallocate the `Vec`, but no memory is wasted.
+    #[inline]
+    pub fn alloc_vec_shrunk<T: Copy>(&self, mut val: Vec<T>) -> &[T] {
+        val.shrink_to_fit();
+        self.alloc_vec(val)
+    }
+
+    /// Allocate many items at once, avoid allocation for owned values.
+    #[inline]
+    pub fn alloc_cow<'input, 'arena, T>(&'arena self, vals: Cow<'input, [T]>) -> &'arena [T]
+    where
+        T: Sized + Copy + 'input,
+    {
+        match vals {
+            Cow::Owned(vec)      => self.alloc_vec(vec),
+            Cow::Borrowed(slice) => self.alloc_slice(slice),
+        }
+    }
+
+    /// Allocate an `&str` slice onto the arena and return a reference to it. This is
+    /// useful when the original slice has an undefined lifetime.
+    ///
+    /// Note: static slices (`&'static str`) can be safely used in place of arena-bound
+    ///       slices without having to go through this method.
+    pub fn alloc_str<'arena>(&'arena self, val: &str) -> &'arena str {
+        self.alloc_str_mut(val)
+    }
+
+    /// Same as `alloc_str`, but returns a mutable reference, so that the copy
+    /// can be patched in place afterwards, e.g. with `make_ascii_lowercase`.
+    #[inline]
+    pub fn alloc_str_mut<'arena>(&'arena self, val: &str) -> &'arena mut str {
+        self.alloc_uninitialized_slice(val.len()).copy_from_str(val)
+    }
+
+    /// Allocate a region of `max_len` bytes for a string whose final length
+    /// is only known after writing it, such as one with escape sequences
+    /// decoded. `UninitializedStr::finish` returns the unused tail of the
+    /// region back to the arena.
+    #[inline]
+    pub fn alloc_str_uninit<'arena>(&'arena self, max_len: usize) -> UninitializedStr<'arena> {
+        let ptr = self.require(max_len) as *mut MaybeUninit<u8>;
+
+        UninitializedStr {
+            arena: self,
+            bytes: unsafe { std::slice::from_raw_parts_mut(ptr, max_len) },
+        }
+    }
+
+    /// Fallible variant of `alloc_str`.
+    pub fn try_alloc_str<'arena>(&'arena self, val: &str) -> Result<&'arena str, AllocError> {
+        unsafe {
+            use std::str::from_utf8_unchecked;
+
+            Ok(from_utf8_unchecked(self.try_alloc_slice(val.as_bytes())?))
+        }
+    }
+
+    /// Concatenate all string slices in `parts` into a single allocation on the
+    /// arena and return a reference to it. The total length is computed up
+    /// front by walking a clone of the iterator.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    ///
+    /// assert_eq!(arena.alloc_concat(["foo", "_", "bar"].iter().copied()), "foo_bar");
+    /// ```
+    #[inline]
+    pub fn alloc_concat<'arena, 'p, I>(&'arena self, parts: I) -> &'arena str
+    where
+        I: IntoIterator<Item = &'p str>,
+        I::IntoIter: Clone,
+    {
+        let bytes = self.alloc_concat_slices(parts.into_iter().map(str::as_bytes));
+
+        // Concatenation of valid UTF-8 strings is always valid UTF-8
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    /// Concatenate all slices in `parts` into a single allocation on the arena
+    /// and return a reference to it. The total length is computed up front
+    /// by walking a clone of the iterator.
+    ///
+    /// **Panics** if the cloned iterator yields parts of a different total length.
+    pub fn alloc_concat_slices<'arena, 'p, T, I>(&'arena self, parts: I) -> &'arena [T]
+    where
+        T: Copy + 'p,
+        I: IntoIterator<Item = &'p [T]>,
+        I::IntoIter: Clone,
+    {
+        let parts = parts.into_iter();
+        let len = parts.clone().map(<[T]>::len).sum();
+        let slice = self.alloc_uninitialized_slice::<T>(len);
+        let mut offset = 0;
+
+        for part in parts {
+            let end = offset + part.len();
+
+            assert!(end <= len, "iterator yielded more elements than its clone");
+
+            slice.pointer[offset..end].copy_from_slice(unsafe {
+                std::slice::from_raw_parts(part.as_ptr() as *const MaybeUninit<T>, part.len())
+            });
+
+            offset = end;
+        }
+
+        assert_eq!(offset, len, "iterator yielded fewer elements than its clone");
+
+        unsafe { slice.as_slice() }
+    }
+
+    /// Format the `args` directly onto the arena and return a reference to the
+    /// resulting `&str`, without going through a heap allocated `String`.
+    /// See also the `arena_format!` macro.
+    pub fn alloc_fmt<'arena>(&'arena self, args: fmt::Arguments) -> &'arena str {
+        use std::fmt::Write;
+        use crate::string::ArenaString;
+
+        if let Some(val) = args.as_str() {
+            return self.alloc_str(val);
+        }
+
+        let mut string = ArenaString::new(self);
+
+        string.write_fmt(args).expect("a formatting trait implementation returned an error");
+        string.into_str()
+    }
+
+    /// Create an `ArenaWriter` implementing `std::io::Write` and `std::fmt::Write`,
+    /// which appends bytes directly to the arena.
+    #[inline]
+    pub fn writer<'arena>(&'arena self) -> crate::string::ArenaWriter<'arena> {
+        crate::string::ArenaWriter::new(self)
+    }
+
+    /// Allocate an `&str` slice onto the arena as null terminated C-style string.
+    /// No checks are performed on the source and whether or not it already contains
+    /// any nul bytes. While this does not create any memory issues, it assumes that
+    /// the reader of the source can deal with malformed source.
+    pub fn alloc_nul_term_str<'arena>(&'arena self, val: &str) -> NulTermStr<'arena> {
+        let len_with_zero = val.len() + 1;
+        let ptr = self.require(len_with_zero);
+
+        unsafe {
+            use std::ptr::copy_nonoverlapping;
+            use std::slice::from_raw_parts;
+            use std::str::from_utf8_unchecked;
+
+            copy_nonoverlapping(val.as_ptr(), ptr, val.len());
+            *ptr.add(val.len()) = 0;
+
+            NulTermStr(from_utf8_unchecked(from_raw_parts(ptr, val.len())))
+        }
+    }
+
+    /// Pushes the `String` as it's own page onto the arena and returns a reference to it.
+    /// This does not copy or reallocate the original `String`.
+    ///
+    /// Any unused capacity of the `String` is reported by `wasted_bytes`. The
+    /// whole capacity counts towards the limit set by `with_limit`.
+    pub fn alloc_string<'arena>(&'arena self, val: String) -> &'arena str {
+        let len = val.len();
+        let ptr = self.adopt_vec(val.into_bytes());
+
+        unsafe {
+            use std::str::from_utf8_unchecked;
+            use std::slice::from_raw_parts;
+
+            from_utf8_unchecked(from_raw_parts(ptr, len))
+        }
+    }
+
+    /// Allocate `size` bytes of uninitialized memory aligned to `align`,
+    /// which is useful for buffers with an alignment requirement not tied
+    /// to any Rust type, such as SIMD buffers.
+    ///
+    /// **Panics** if `align` is not a power of two.
+    #[inline]
+    pub fn alloc_aligned(&self, size: usize, align: usize) -> NonNull<u8> {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+
+        unsafe { NonNull::new_unchecked(self.require_aligned(size, align)) }
+    }
+
+    /// Fallible variant of `alloc_aligned`.
+    ///
+    /// **Panics** if `align` is not a power of two.
+    #[inline]
+    pub fn try_alloc_aligned(&self, size: usize, align: usize) -> Result<NonNull<u8>, AllocError> {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+
+        Ok(unsafe { NonNull::new_unchecked(self.try_require_aligned(size, align)?) })
+    }
+
+    /// Take ownership of the heap allocation of a `Vec`, which will live
+    /// as long as the arena does.
+    #[inline]
+    fn adopt_vec<T>(&self, val: Vec<T>) -> *mut T {
+        let size = val.capacity() * size_of::<T>();
+
+        if size > self.limit.saturating_sub(self.allocated.get()) {
+            AllocError::over_limit(size).handle()
+        }
+
+        let unused = (val.capacity() - val.len()) * size_of::<T>();
+
+        self.wasted.set(self.wasted.get() + unused);
+        self.push_page(&self.adopted, Page::from_vec(val)) as *mut T
+    }
+
+    /// Take ownership of all pages of the `other` arena, so that everything
+    /// allocated on it lives as long as this arena does. The pages are
+    /// adopted, rather than used for further allocations.
+    pub(crate) fn adopt_pages(&self, other: Arena) {
+        let Arena { store, adopted, wasted, .. } = other;
+
+        for page in store.into_inner().into_iter().chain(adopted.into_inner()) {
+            self.push_page(&self.adopted, page);
+        }
+
+        self.wasted.set(self.wasted.get() + wasted.get());
+    }
+
+    fn push_page(&self, store: &Cell<Vec<Page>>, page: Page) -> *mut u8 {
+        let ptr = page.ptr.as_ptr();
+
+        self.allocated.set(self.allocated.get().saturating_add(page.layout.size()));
+
+        let mut temp = store.replace(Vec::new());
+        temp.push(page);
+        store.replace(temp);
+
+        ptr
+    }
+
+    fn new_page(&self, size: usize, align: usize) -> Result<Page, AllocError> {
+        if size > self.limit.saturating_sub(self.allocated.get()) {
+            return Err(AllocError::over_limit(size));
+        }
+
+        Page::alloc(size, align)
+    }
+
+    /// Same as `require_aligned`, but the memory is filled with zeroes.
+    /// Allocations larger than a page get a dedicated zeroed heap block,
+    /// rather than being written over.
+    fn require_zeroed(&self, size: usize, align: usize) -> *mut u8 {
+        if size > ARENA_BLOCK || align > ARENA_BLOCK {
+            if size > self.limit.saturating_sub(self.allocated.get()) {
+                AllocError::over_limit(size).handle()
+            }
+
+            let page = Page::alloc_zeroed(size, align).unwrap_or_else(|err| err.handle());
+
+            return self.push_page(&self.adopted, page);
+        }
+
+        let ptr = self.require_aligned(size, align);
+
+        unsafe { std::ptr::write_bytes(ptr, 0, size) };
+
+        ptr
+    }
+
+    /// Allocate a dedicated heap block, outside of the pages.
+    fn alloc_dedicated(&self, size: usize, align: usize) -> Result<*mut u8, AllocError> {
+        let page = self.new_page(size, align)?;
+
+        Ok(self.push_page(&self.adopted, page))
+    }
+
+    #[inline]
+    fn require(&self, size: usize) -> *mut u8 {
+        self.require_aligned(size, 1)
+    }
+
+    /// Same as `require`, but guarantees the returned pointer to be aligned
+    /// to `align`, which must be a power of two.
+    #[inline]
+    pub(crate) fn require_aligned(&self, size: usize, align: usize) -> *mut u8 {
+        match self.try_require_aligned(size, align) {
+            Ok(ptr) => ptr,
+            Err(err) => err.handle(),
+        }
+    }
+
+    #[inline]
+    fn try_require_aligned(&self, size: usize, align: usize) -> Result<*mut u8, AllocError> {
+        if cfg!(feature = "debug-poison") {
+            assert!(align.is_power_of_two(), "alignment must be a power of two, got {}", align);
+        }
+
+        // This should be optimized away for size and align known at compile time.
+        if size > ARENA_BLOCK || align > ARENA_BLOCK {
+            return self.alloc_dedicated(size, align);
+        }
+
+        let size = padded_size(size);
+
+        if let Some(ptr) = self.bump(size, align) {
+            return Ok(ptr);
+        }
+
+        self.grow()?;
+
+        match self.bump(size, align) {
+            Some(ptr) => Ok(ptr),
+
+            // Padding required for the alignment doesn't fit on a fresh page
+            None => self.alloc_dedicated(size, align),
+        }
+    }
+
+    /// Take `size` bytes aligned to `align` from the current page, if they fit.
+    ///
+    /// Pages are aligned to `usize`, and all sizes are padded to a multiple of
+    /// `usize`, so the offset only needs adjusting for larger alignments.
+    #[inline]
+    fn bump(&self, size: usize, align: usize) -> Option<*mut u8> {
+        let offset = self.offset.get();
+        let start = if align <= size_of::<usize>() {
+            offset
+        } else {
+            let page = self.ptr.get() as usize;
+            let aligned = (page + offset + (align - 1)) & !(align - 1);
+
+            aligned - page
+        };
+
+        if start + size > ARENA_BLOCK {
+            return None;
+        }
+
+        if cfg!(feature = "debug-poison") {
+            assert!(start >= offset, "arena offset moved backwards");
+            assert_eq!((self.ptr.get() as usize + start) % align, 0, "misaligned arena allocation");
+        }
+
+        self.offset.set(start + size);
+
+        Some(unsafe { self.ptr.get().add(start) })
+    }
+
+    /// Fill `len` bytes at `ptr` with the `POISON_BYTE`, if the `debug-poison`
+    /// feature is enabled.
+    #[inline]
+    fn poison(ptr: *mut u8, len: usize) {
+        if cfg!(feature = "debug-poison") {
+            unsafe { std::ptr::write_bytes(ptr, POISON_BYTE, len) }
+        }
+    }
+
+    /// Move on to the next page, reusing it if the arena has been rewound
+    /// by `clear`, or allocating a new one otherwise.
+    fn grow(&self) -> Result<(), AllocError> {
+        let next = self.page.get() + 1;
+        let store = self.store.replace(Vec::new());
+        let reused = store.get(next).map(|page| page.ptr.as_ptr());
+
+        self.store.replace(store);
+
+        let ptr = match reused {
+            Some(ptr) => ptr,
+            None => {
+                let page = self.new_page(ARENA_BLOCK, size_of::<usize>())?;
+
+                self.push_page(&self.store, page)
+            }
+        };
+
+        self.page.set(next);
+        self.ptr.set(ptr);
+        self.offset.set(0);
+
+        Ok(())
+    }
+
+    /// Rewind to the first page, freeing all memory that isn't a page.
+    fn rewind(&self) {
+        self.forget_recycled();
+
+        let store = self.store.replace(Vec::new());
+        let adopted = self.adopted.replace(Vec::new());
+
+        for (index, page) in store[..=self.page.get()].iter().enumerate() {
+            let used = if index == self.page.get() { self.offset.get() } else { ARENA_BLOCK };
+
+            Self::poison(page.ptr.as_ptr(), used);
+        }
+        let freed: usize = adopted.iter().map(|page| page.layout.size()).sum();
+
+        self.ptr.set(store[0].ptr.as_ptr());
+        self.store.replace(store);
+        self.page.set(0);
+        self.offset.set(0);
+        self.allocated.set(self.allocated.get() - freed);
+        self.wasted.set(0);
+
+        drop(adopted);
+    }
+
+    /// Attempt to grow or shrink an allocation of `size` bytes at `ptr` to
+    /// `new_size` bytes without moving it. This is only possible if it was
+    /// the last allocation made on the current page, and the new size still
+    /// fits on that page. Both sizes need to be padded with `padded_size`.
+    #[inline]
+    pub(crate) fn resize_in_place(&self, ptr: *mut u8, size: usize, new_size: usize) -> bool {
+        let page = self.ptr.get() as usize;
+        let offset = self.offset.get();
+        let start = ptr as usize;
+
+        if start < page || start + size != page + offset {
+            return false;
+        }
+
+        let new_offset = match (offset - size).checked_add(new_size) {
+            Some(new_offset) if new_offset <= ARENA_BLOCK => new_offset,
+            _                                             => return false,
+        };
+
+        self.offset.set(new_offset);
+
+        true
+    }
+
+    /// Give the memory of a single `value` back to the arena, so that it can
+    /// be reused by a following `alloc` of a value of the same size, such as
+    /// the next node inserted into a `List` or `Map`. Blocks are kept in free
+    /// lists by size, rounded up to the size of `usize`.
+    ///
+    /// Values larger than 16 `usize`s or with a larger alignment than `usize`
+    /// are not recycled, and their memory is kept as it was. The value itself
+    /// is never dropped.
+    ///
+    /// ```rust
+    /// # use std::ptr::NonNull;
+    /// # use toolshed::Arena;
+    /// let arena = Arena::new();
+    /// let first = NonNull::from(arena.alloc(1u64));
+    ///
+    /// unsafe { arena.recycle(first) };
+    ///
+    /// assert_eq!(arena.recycled_bytes(), 8);
+    /// assert_eq!(arena.alloc(2u64) as *const u64, first.as_ptr());
+    /// assert_eq!(arena.recycled_bytes(), 0);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The `value` must point to a single value allocated on this arena with
+    /// `alloc`, `alloc_with` or `alloc_uninitialized`, and must be valid for
+    /// writes, such as a pointer obtained from the `&mut T` they return. The
+    /// whole block the value was allocated in is overwritten, so elements of
+    /// slices, `Vec`s or `String`s may not be recycled, as that would clobber
+    /// their neighbours. Neither the `value` nor any reference to it may be
+    /// used after it is recycled.
+    pub unsafe fn recycle<T>(&self, value: NonNull<T>) {
+        let ptr = value.as_ptr() as *mut u8;
+
+        let class = match recycle_class(size_of::<T>(), align_of::<T>()) {
+            Some(class) if (ptr as usize).is_multiple_of(align_of::<FreeBlock>()) => class,
+            _ => return,
+        };
+
+        if cfg!(feature = "debug-poison") {
+            assert!(self.contains(ptr), "recycled value wasn't allocated on this arena");
+        }
+
+        let size = padded_size(size_of::<T>());
+        let block = ptr as *mut FreeBlock;
+
+        Self::poison(ptr, size);
+
+        block.write(FreeBlock {
+            next: self.free[class].get(),
+        });
+
+        self.free[class].set(NonNull::new(block));
+        self.recycled.set(self.recycled.get() + size);
+    }
+
+    /// Same as `recycle`, for the nodes of the data structures in this crate,
+    /// allocated with `alloc_node` or `alloc_node_slice`. Nodes are only ever
+    /// reachable through shared references, which can't be written through,
+    /// so the pointer to the node is derived from the page it is on instead.
+    ///
+    /// # Safety
+    ///
+    /// The `node` must have been allocated on this arena, and neither it nor
+    /// any reference to it may be used after it is recycled.
+    pub(crate) unsafe fn recycle_node<T>(&self, node: &T) {
+        // Nodes hold references, so they are padded to the size of `usize`
+        // already and recycling one node of a slice leaves the others intact.
+        debug_assert_eq!(size_of::<T>(), padded_size(size_of::<T>()));
+
+        let ptr = self
+            .page_pointer(node as *const T as usize)
+            .expect("recycled node wasn't allocated on this arena");
+
+        self.recycle(NonNull::new_unchecked(ptr as *mut T));
+    }
+
+    /// Number of bytes given back with `recycle` that haven't been reused yet.
+    #[inline]
+    pub fn recycled_bytes(&self) -> usize {
+        self.recycled.get()
+    }
+
+    /// Take a recycled block for an allocation of `size` bytes, if there is one.
+    #[inline]
+    fn take_recycled(&self, size: usize, align: usize) -> Option<*mut u8> {
+        if self.recycled.get() == 0 {
+            return None;
+        }
+
+        let class = recycle_class(size, align)?;
+        let block = self.free[class].get()?;
+
+        self.free[class].set(unsafe { block.as_ref().next });
+        self.recycled.set(self.recycled.get() - padded_size(size));
+
+        Some(block.as_ptr() as *mut u8)
+    }
+
+    /// Drop all recycled blocks, since the memory they are in is being
+    /// reclaimed anyway.
+    #[inline]
+    fn forget_recycled(&self) {
+        for list in self.free.iter() {
+            list.set(None);
+        }
+
+        self.recycled.set(0);
+    }
+
+    /// Free all values on the arena, so that its memory can be reused.
+    ///
+    /// The arena is rewound to its first page, and the following allocations
+    /// reuse all pages already allocated before allocating new ones. Adopted
+    /// `Vec`s and `String`s, as well as allocations larger than a page, are
+    /// freed.
+    ///
+    /// ```rust
+    /// # use toolshed::Arena;
+    /// let mut arena = Arena::new();
+    ///
+    /// for _ in 0..10 {
+    ///     for n in 0..10_000u64 {
+    ///         arena.alloc(n);
+    ///     }
+    ///
+    ///     arena.reset();
+    /// }
+    ///
+    /// // Only the first two pages are kept
+    /// assert_eq!(arena.allocated_bytes(), 2 * Arena::new().allocated_bytes());
+    /// ```
+    #[inline]
+    pub fn reset(&mut self) {
+        self.rewind();
+    }
+
+    /// Resets the pointer to the current page of the arena, or to the first
+    /// page with page recycling enabled (see `set_page_recycling`).
+    ///
+    /// Outside of benchmarks, use the safe `reset` method instead.
+    ///
+    /// **Using this method is an extremely bad idea!**
+    ///
+    /// The only case where the use of this method would be justified is
+    /// in benchmarks where creation of a structure on the arena is to be
+    /// tested without the cost of re-creating the arena itself on every iteration.
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn clear(&self) {
+        if self.recycle_pages {
+            self.rewind();
+        } else {
+            self.reset_to(0);
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn offset(&self) -> usize {
+        self.offset.get()
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn reset_to(&self, offset: usize) {
+        self.forget_recycled();
+
+        if offset < self.offset.get() {
+            Self::poison(self.ptr.get().add(offset), self.offset.get() - offset);
+        }
+
+        self.offset.set(offset)
+    }
+}
+
+/// Size class of recycled blocks for an allocation of `size` bytes aligned
+/// to `align`, if such an allocation can be recycled at all.
+#[inline]
+const fn recycle_class(size: usize, align: usize) -> Option<usize> {
+    if size == 0 || align > align_of::<FreeBlock>() {
+        return None;
+    }
+
+    let class = padded_size(size) / size_of::<usize>() - 1;
+
+    if class < RECYCLE_CLASSES {
+        Some(class)
+    } else {
+        None
+    }
+}
+
+/// Size of the memory actually taken by an allocation of `size` bytes
+/// on the page, since all allocations are padded to the size of `usize`.
+#[inline]
+pub(crate) const fn padded_size(size: usize) -> usize {
+    match size % size_of::<usize>() {
+        0 => size,
+        n => size + (size_of::<usize>() - n),
+    }
+}
+
+/// Tuples of `Copy` types that can be split into parallel slices by
+/// `Arena::alloc_struct_of_slices`. Implemented for tuples of up to 4 elements.
+pub trait StructOfSlices<'arena>: Copy {
+    /// Tuple of slices, one for each element of the tuple.
+    type Slices;
+
+    /// Allocate `items` onto the arena as parallel slices.
+    fn alloc_in(arena: &'arena Arena, items: &[Self]) -> Self::Slices;
+}
+
+macro_rules! impl_struct_of_slices {
+    ($($ty:ident $idx:tt $slice:ident),*) => {
+        impl<'arena, $($ty: Copy + 'arena),*> StructOfSlices<'arena> for ($($ty,)*) {
+            type Slices = ($(&'arena [$ty],)*);
+
+            fn alloc_in(arena: &'arena Arena, items: &[Self]) -> Self::Slices {
+                use std::alloc::Layout;
+                use std::slice::from_raw_parts;
+
+                let len = items.len();
+                let layout = Layout::new::<()>();
+
+                $(
+                    let (layout, $slice) = Layout::array::<$ty>(len)
+                        .and_then(|array| layout.extend(array))
+                        .expect("capacity overflow");
+                )*
+
+                let base = arena.require_aligned(layout.size(), layout.align());
+
+                unsafe {
+                    $(
+                        let $slice = base.add($slice) as *mut $ty;
+                    )*
+
+                    for (index, item) in items.iter().enumerate() {
+                        $(
+                            $slice.add(index).write(item.$idx);
+                        )*
+                    }
+
+                    ($(from_raw_parts($slice, len),)*)
+                }
+            }
+        }
+    };
+}
+
+impl_struct_of_slices!(A 0 a);
+impl_struct_of_slices!(A 0 a, B 1 b);
+impl_struct_of_slices!(A 0 a, B 1 b, C 2 c);
+impl_struct_of_slices!(A 0 a, B 1 b, C 2 c, D 3 d);
+
+/// Akin to `CopyCell`: `Sync` is unsafe but `Send` is totally fine!
+unsafe impl Send for Arena {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn allocate_some_stuff() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc(0u64), &0);
+        assert_eq!(arena.alloc(42u64), &42);
+        assert_eq!(arena.alloc(0x8000000u64), &0x8000000u64);
+
+        assert_eq!(arena.offset.get(), 8 * 3);
+
+        // For inspecting internals
+        let mut arena = arena;
+
+        assert_eq!(arena.store.get_mut().len(), 1);
+    }
+
+    #[test]
+    fn clear_recycles_pages() {
+        let mut arena = Arena::new();
+
+        arena.set_page_recycling(true);
+
+        for _ in 0..3 {
+            for _ in 0..3 {
+                arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+            }
+
+            arena.alloc_vec(vec![0u8; 100]);
+            arena.alloc_uninitialized::<[u8; ARENA_BLOCK * 2]>();
+
+            assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 5 + 100);
+
+            unsafe { arena.clear() };
+
+            assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 3);
+        }
+
+        assert_eq!(arena.store.get_mut().len(), 3);
+        assert_eq!(arena.adopted.get_mut().len(), 0);
+    }
+
+    #[test]
+    fn reset() {
+        let mut arena = Arena::new();
+
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+        arena.alloc_string(String::from("doge"));
+
+        arena.reset();
+
+        assert_eq!(arena.offset.get(), 0);
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 2);
+        assert_eq!(arena.alloc(42u64), &42);
+    }
+
+    #[test]
+    fn clear_without_recycling() {
+        let arena = Arena::new();
+
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+
+        unsafe { arena.clear() };
+
+        // Only the current page is rewound
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 3);
+    }
+
+    #[test]
+    fn alloc_with() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc_with(|| 42u64), &42);
+        assert_eq!(arena.alloc_with(|| [7u64; 128]), &[7u64; 128]);
+        assert_eq!(arena.offset.get(), 8 * 129);
+    }
+
+    #[test]
+    fn allocate_some_vecs() {
+        let arena = Arena::new();
+
+        let vecs = vec![vec![1u64, 2, 3, 4], vec![7; ARENA_BLOCK * 2], vec![]];
+
+        for vec in vecs {
+            assert_eq!(arena.alloc_vec(vec.clone()), &vec[..]);
+        }
+    }
+
+    #[test]
+    fn allocate_some_cows() {
+        let arena = Arena::new();
+
+        let vecs = vec![vec![1u64, 2, 3, 4], vec![7; ARENA_BLOCK * 2], vec![]];
+
+        for vec in vecs {
+            assert_eq!(arena.alloc_cow(vec.clone().into()), &vec[..]);
+        }
+    }
+
+    #[test]
+    fn allocate_huge_heap() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc(0u64), &0);
+        assert_eq!(arena.alloc(42u64), &42);
+
+        arena.alloc_uninitialized::<[usize; 1024 * 1024]>();
+
+        // Still writes to the first page
+        assert_eq!(arena.offset.get(), 8 * 2);
+        assert_eq!(arena.alloc(0x8000000u64), &0x8000000u64);
+        assert_eq!(arena.offset.get(), 8 * 3);
+
+        // For inspecting internals
+        let mut arena = arena;
+
+        // However a dedicated allocation has been added
+        assert_eq!(arena.store.get_mut().len(), 1);
+        assert_eq!(arena.adopted.get_mut().len(), 1);
+
+        // Dedicated allocation is appropriately large
+        assert_eq!(
+            arena.adopted.get_mut()[0].layout.size(),
+            size_of::<usize>() * 1024 * 1024
+        );
+    }
+
+    #[test]
+    fn alloc_slice() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc_slice(&[10u16, 20u16]), &[10u16, 20u16][..]);
+        assert_eq!(arena.offset.get(), 8);
+    }
+
+    #[test]
+    fn alloc_slice_mut() {
+        let arena = Arena::new();
+        let source = [1u32, 2, 3];
+
+        let slice = arena.alloc_slice_mut(&source);
+
+        slice[1] = 20;
+
+        assert_eq!(slice, &[1, 20, 3]);
+        assert_eq!(source, [1, 2, 3]);
+
+        let string = arena.alloc_str_mut("Doge");
+
+        string.make_ascii_uppercase();
+
+        assert_eq!(string, "DOGE");
+    }
+
+    #[test]
+    fn alloc_concat() {
+        let arena = Arena::new();
+        let parts = [String::from("doge"), String::new(), String::from("_moon")];
+
+        assert_eq!(arena.alloc_concat(parts.iter().map(String::as_str)), "doge_moon");
+        assert_eq!(arena.alloc_concat(std::iter::empty()), "");
+        assert_eq!(arena.offset.get(), 16);
+
+        assert_eq!(arena.alloc_concat_slices(vec![&[1u16, 2][..], &[3]]), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn alloc_struct_of_slices() {
+        let arena = Arena::new();
+
+        arena.alloc(1u8);
+
+        let items = [(1u8, 10u64, 100u16), (2, 20, 200), (3, 30, 300)];
+        let (a, b, c) = arena.alloc_struct_of_slices(&items);
+
+        assert_eq!(a, &[1, 2, 3]);
+        assert_eq!(b, &[10, 20, 30]);
+        assert_eq!(c, &[100, 200, 300]);
+
+        assert_eq!(b.as_ptr() as usize % std::mem::align_of::<u64>(), 0);
+        assert_eq!(c.as_ptr() as usize % std::mem::align_of::<u16>(), 0);
+
+        // 8 bytes for the first `u8`, then 3 + 5 padding, 24, and 6 + 2 padding
+        assert_eq!(arena.offset.get(), 48);
+    }
+
+    #[test]
+    fn alloc_struct_of_over_aligned_slices() {
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        #[repr(align(32))]
+        struct Simd([u8; 32]);
+
+        let arena = Arena::new();
+
+        arena.alloc(1u8);
+
+        let (a, b) = arena.alloc_struct_of_slices(&[(Simd([1; 32]), 1u8), (Simd([2; 32]), 2)]);
+
+        assert_eq!(a, &[Simd([1; 32]), Simd([2; 32])]);
+        assert_eq!(b, &[1, 2]);
+        assert_eq!(a.as_ptr() as usize % 32, 0);
+    }
+
+    #[test]
+    fn alloc_uninitialized() {
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        struct Point {
+            x: u32,
+            y: u64,
+        }
+
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc_uninitialized().init_with(|| Point { x: 1, y: 2 }), &Point { x: 1, y: 2 });
+
+        let mut uninit = arena.alloc_uninitialized::<Point>();
+        let ptr = uninit.as_mut_ptr();
+
+        let point = unsafe {
+            std::ptr::addr_of_mut!((*ptr).x).write(3);
+            std::ptr::addr_of_mut!((*ptr).y).write(4);
+
+            uninit.assume_init()
+        };
+
+        assert_eq!(point, &Point { x: 3, y: 4 });
+    }
+
+    #[test]
+    fn alloc_default() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.alloc_default::<(u32, bool)>(), &(0, false));
+
+        let table = arena.alloc_slice_default::<u16>(5);
+
+        table[2] = 7;
+
+        assert_eq!(table, &[0, 0, 7, 0, 0]);
+        assert!(arena.alloc_slice_default::<u64>(0).is_empty());
+    }
+
+    #[test]
+    fn alloc_bytes() {
+        let arena = Arena::new();
+
+        arena.alloc_slice_default::<u8>(16).fill(0xFF);
+        unsafe { arena.reset_to(0) };
+
+        let buffer = arena.alloc_bytes(10);
+
+        assert_eq!(buffer, &[0; 10]);
+
+        buffer[9] = 1;
+
+        assert!(arena.alloc_bytes(0).is_empty());
+        assert_eq!(arena.offset.get(), 16);
+    }
+
+    #[test]
+    fn page_cursor() {
+        let arena = Arena::new();
+        let mut cursor = arena.reserve_region(32);
+
+        *cursor.put(7u8) += 1;
+
+        assert_eq!(cursor.put(3u32), &3);
+
+        arena.alloc(42u64);
+
+        assert_eq!(cursor.put_slice(&[1u16, 2, 3]), &[1, 2, 3]);
+        assert_eq!(cursor.position(), 14);
+        assert_eq!(cursor.remaining(), 18);
+        assert_eq!(cursor.try_put([0u8; 19]), None);
+        assert!(cursor.try_put_slice(&[0u64; 3]).is_none());
+        assert_eq!(cursor.finish(), &[8, 0, 0, 0, 3, 0, 0, 0, 1, 0, 2, 0, 3, 0]);
+    }
+
+    #[test]
+    fn page_cursor_no_uninit_struct() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[repr(C)]
+        struct Span {
+            start: u32,
+            end: u32,
+        }
+
+        unsafe impl NoUninit for Span {}
+
+        let arena = Arena::new();
+        let mut cursor = arena.reserve_region(16);
+
+        cursor.put(1u8);
+        cursor.put(Span { start: 2, end: 3 }).end += 1;
+
+        assert_eq!(cursor.finish(), &[1, 0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0]);
+    }
+
+    #[test]
+    fn page_cursor_finish_returns_memory() {
+        let arena = Arena::new();
+        let mut cursor = arena.reserve_region(1024);
+
+        cursor.put(1u64);
+
+        assert_eq!(format!("{:?}", cursor), "PageCursor { position: 8, capacity: 1024 }");
+        assert_eq!(cursor.finish().len(), 8);
+        assert_eq!(arena.offset.get(), 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn page_cursor_overflow() {
+        let arena = Arena::new();
+        let mut cursor = arena.reserve_region(4);
 
-            from_utf8_unchecked(from_raw_parts(ptr, len))
-        }
+        cursor.put(1u64);
     }
 
-    #[inline]
-    fn alloc_byte_vec(&self, mut val: Vec<u8>) -> *mut u8 {
-        let ptr = val.as_mut_ptr();
+    #[test]
+    fn alloc_zeroed() {
+        let arena = Arena::new();
 
-        let mut temp = self.store.replace(Vec::new());
-        temp.push(val);
-        self.store.replace(temp);
+        // Dirty the page, so that zeroes don't come from the allocator
+        arena.alloc_slice_default::<u8>(64).fill(0xFF);
+        unsafe { arena.reset_to(0) };
 
-        ptr
-    }
+        assert_eq!(unsafe { arena.alloc_zeroed::<(u64, u8)>().assume_init() }, &(0, 0));
+        assert_eq!(unsafe { arena.alloc_slice_zeroed::<u32>(8).as_mut_slice() }, &[0; 8]);
+
+        let huge = unsafe { arena.alloc_slice_zeroed::<u64>(ARENA_BLOCK).as_mut_slice() };
 
-    fn alloc_bytes(&self, size: usize) -> *mut u8 {
-        self.alloc_byte_vec(Vec::with_capacity(size))
+        assert!(huge.iter().all(|n| *n == 0));
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 9);
     }
 
-    #[inline]
-    fn require(&self, size: usize) -> *mut u8 {
-        // This should be optimized away for size known at compile time.
-        if size > ARENA_BLOCK {
-            return self.alloc_bytes(size);
-        }
+    #[test]
+    fn alloc_uninitialized_slice() {
+        let arena = Arena::new();
 
-        let size = match size % size_of::<usize>() {
-            0 => size,
-            n => size + (size_of::<usize>() - n),
-        };
+        let filled = arena.alloc_uninitialized_slice(3).init_with([1u32, 2, 3, 4].iter().cloned());
 
-        let offset = self.offset.get();
-        let cap = offset + size;
+        assert_eq!(filled, &[1, 2, 3]);
 
-        if cap > ARENA_BLOCK {
-            self.grow();
+        let mut slice = arena.alloc_uninitialized_slice(2);
 
-            self.offset.set(size);
-            self.ptr.get()
-        } else {
-            self.offset.set(cap);
-            unsafe { self.ptr.get().add(offset) }
-        }
-    }
+        assert_eq!(slice.len(), 2);
 
-    fn grow(&self) {
-        let ptr = self.alloc_byte_vec(Vec::with_capacity(ARENA_BLOCK));
-        self.ptr.set(ptr);
-    }
+        slice.write(1, 20u64);
+        slice.write(0, 10);
 
-    /// Resets the pointer to the current page of the arena.
-    ///
-    /// **Using this method is an extremely bad idea!**
-    ///
-    /// The only case where the use of this method would be justified is
-    /// in benchmarks where creation of a structure on the arena is to be
-    /// tested without the cost of re-creating the arena itself on every iteration.
-    #[doc(hidden)]
-    #[inline]
-    pub unsafe fn clear(&self) {
-        self.reset_to(0)
+        assert_eq!(unsafe { slice.as_mut_slice() }, &[10, 20]);
+        assert_eq!(arena.alloc_uninitialized_slice(4).fill(7u8), &[7; 4]);
+        assert_eq!(arena.offset.get(), 16 + 16 + 8);
     }
 
-    #[doc(hidden)]
-    #[inline]
-    pub unsafe fn offset(&self) -> usize {
-        self.offset.get()
-    }
+    #[test]
+    fn uninitialized_slice_from_external_memory() {
+        let mut scratch = [0u32; 4];
 
-    #[doc(hidden)]
-    #[inline]
-    pub unsafe fn reset_to(&self, offset: usize) {
-        self.offset.set(offset)
-    }
-}
+        let slice = unsafe { UninitializedSlice::from_raw_parts(scratch.as_mut_ptr(), 3) };
 
-/// Akin to `CopyCell`: `Sync` is unsafe but `Send` is totally fine!
-unsafe impl Send for Arena {}
+        assert_eq!(slice.len(), 3);
+        assert_eq!(slice.init_with(vec![1, 2, 3]), &[1, 2, 3]);
+        assert_eq!(scratch, [1, 2, 3, 0]);
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        let slice = UninitializedSlice::from(&mut scratch[..]);
+
+        assert_eq!(slice.fill(7), &[7, 7, 7, 7]);
+    }
 
     #[test]
-    fn allocate_some_stuff() {
+    fn alloc_cyclic() {
+        #[derive(Clone, Copy)]
+        struct Node<'arena> {
+            value: u32,
+            prev: Option<CyclicRef<'arena, Node<'arena>>>,
+            next: Option<&'arena Node<'arena>>,
+        }
+
         let arena = Arena::new();
 
-        assert_eq!(arena.alloc(0u64), &0);
-        assert_eq!(arena.alloc(42u64), &42);
-        assert_eq!(arena.alloc(0x8000000u64), &0x8000000u64);
+        let a = arena.alloc_cyclic(|a| {
+            let b = arena.alloc_cyclic(|b| {
+                // Neither value is initialized yet
+                assert!(a.get().is_none());
+                assert!(b.get().is_none());
 
-        assert_eq!(arena.offset.get(), 8 * 3);
+                Node { value: 2, prev: Some(a), next: None }
+            });
 
-        // For inspecting internals
-        let mut arena = arena;
+            Node { value: 1, prev: None, next: Some(b) }
+        });
 
-        assert_eq!(arena.store.get_mut().len(), 1);
+        let b = a.next.unwrap();
+
+        assert_eq!(a.value, 1);
+        assert_eq!(b.value, 2);
+        assert!(std::ptr::eq(b.prev.unwrap().get().unwrap(), a));
+
+        let lonely = arena.alloc_cyclic(|this| Node { value: 3, prev: Some(this), next: None });
+        let this = lonely.prev.unwrap();
+
+        assert_eq!(this.get().unwrap().value, 3);
+        assert!(std::ptr::eq(this.get().unwrap(), lonely));
+        assert!(this.ptr_eq(&this.clone()));
+        assert!(!this.ptr_eq(&b.prev.unwrap()));
     }
 
     #[test]
-    fn allocate_some_vecs() {
+    #[should_panic]
+    fn alloc_uninitialized_slice_short_iter() {
         let arena = Arena::new();
 
-        let vecs = vec![vec![1u64, 2, 3, 4], vec![7; ARENA_BLOCK * 2], vec![]];
-
-        for vec in vecs {
-            assert_eq!(arena.alloc_vec(vec.clone()), &vec[..]);
-        }
+        arena.alloc_uninitialized_slice(3).init_with([1u32, 2].iter().cloned());
     }
 
     #[test]
-    fn allocate_some_cows() {
+    fn alloc_slice_from_iter() {
         let arena = Arena::new();
 
-        let vecs = vec![vec![1u64, 2, 3, 4], vec![7; ARENA_BLOCK * 2], vec![]];
+        let words = arena.alloc_slice_from_iter(vec!["foo", "bar"]);
 
-        for vec in vecs {
-            assert_eq!(arena.alloc_cow(vec.clone().into()), &vec[..]);
-        }
+        assert_eq!(words, &["foo", "bar"]);
+        assert!(arena.alloc_slice_from_iter(std::iter::empty::<u64>()).is_empty());
+        assert_eq!(arena.offset.get(), 2 * size_of::<&str>());
     }
 
     #[test]
-    fn allocate_huge_heap() {
+    fn alloc_slice_map() {
         let arena = Arena::new();
+        let names = vec![String::from("foo"), String::from("barbaz")];
 
-        assert_eq!(arena.alloc(0u64), &0);
-        assert_eq!(arena.alloc(42u64), &42);
-
-        arena.alloc_uninitialized::<[usize; 1024 * 1024]>();
+        let upper = arena.alloc_slice_map(names, |name| arena.alloc_str(&name.to_uppercase()));
+        let sizes = arena.alloc_slice_map(upper.iter(), |name| name.len() as u8);
 
-        // Still writes to the first page
-        assert_eq!(arena.offset.get(), 8 * 2);
-        assert_eq!(arena.alloc(0x8000000u64), &0x8000000u64);
-        assert_eq!(arena.offset.get(), 8 * 3);
+        assert_eq!(upper, &["FOO", "BARBAZ"]);
+        assert_eq!(sizes, &[3, 6]);
+        assert!(arena.alloc_slice_map(Vec::<u32>::new(), |n| n + 1).is_empty());
+    }
 
-        // For inspecting internals
-        let mut arena = arena;
+    #[test]
+    #[should_panic]
+    fn alloc_slice_from_lying_iter() {
+        struct Liar;
 
-        // However second page has been added
-        assert_eq!(arena.store.get_mut().len(), 2);
+        impl Iterator for Liar {
+            type Item = u8;
 
-        // Second page is appropriately large
-        assert_eq!(
-            arena.store.get_mut()[1].capacity(),
-            size_of::<usize>() * 1024 * 1024
-        );
-    }
+            fn next(&mut self) -> Option<u8> {
+                None
+            }
+        }
 
-    #[test]
-    fn alloc_slice() {
-        let arena = Arena::new();
+        impl ExactSizeIterator for Liar {
+            fn len(&self) -> usize {
+                3
+            }
+        }
 
-        assert_eq!(arena.alloc_slice(&[10u16, 20u16]), &[10u16, 20u16][..]);
-        assert_eq!(arena.offset.get(), 8);
+        Arena::new().alloc_slice_from_iter(Liar);
     }
 
     #[test]
@@ -463,6 +2553,26 @@ mod test {
       assert!(big_nums[0..3].iter().eq(trunc_nums.iter()));
     }
 
+    #[test]
+    fn alloc_lazy_slice_keeps_alignment() {
+        let arena = Arena::new();
+
+        let nums = arena.alloc_lazy_slice([1u32, 2, 3].iter().cloned(), 6);
+
+        assert_eq!(nums, &[1, 2, 3]);
+        assert_eq!(arena.offset.get(), 16);
+    }
+
+    #[test]
+    fn alloc_lazy_slice_of_owned_values() {
+        let arena = Arena::new();
+        let strings = vec![String::from("foo"), String::from("bar")];
+
+        let slice = arena.alloc_lazy_slice(strings.into_iter(), 4);
+
+        assert_eq!(slice, &["foo", "bar"]);
+    }
+
     #[test]
     fn aligns_slice_allocs() {
         let arena = Arena::new();
@@ -474,6 +2584,54 @@ mod test {
         assert_eq!(arena.offset.get(), 32);
     }
 
+    #[test]
+    fn aligns_over_aligned_types() {
+        #[derive(Clone, Copy)]
+        #[repr(align(64))]
+        struct CacheLine(u8);
+
+        let arena = Arena::new();
+
+        arena.alloc(1u8);
+
+        let line = arena.alloc(CacheLine(42));
+
+        assert_eq!(line as *mut CacheLine as usize % 64, 0);
+        assert_eq!(line.0, 42);
+
+        let lines = arena.alloc_slice(&[CacheLine(1), CacheLine(2)]);
+
+        assert_eq!(lines.as_ptr() as usize % 64, 0);
+
+        // Alignment padding is never handed out twice
+        assert!(arena.offset.get() >= 8 + 64 * 3);
+    }
+
+    #[test]
+    fn alloc_aligned() {
+        let arena = Arena::new();
+
+        arena.alloc(1u8);
+
+        for &align in &[1, 8, 32, 64, 4096] {
+            let ptr = arena.alloc_aligned(100, align);
+
+            assert_eq!(ptr.as_ptr() as usize % align, 0);
+        }
+
+        // Alignment too large for a page
+        let align = (ARENA_BLOCK * 2).next_power_of_two();
+        let ptr = arena.alloc_aligned(16, align);
+
+        assert_eq!(ptr.as_ptr() as usize % align, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "alignment must be a power of two")]
+    fn alloc_aligned_checks_align() {
+        Arena::new().alloc_aligned(16, 3);
+    }
+
     #[test]
     fn aligns_str_allocs() {
         let arena = Arena::new();
@@ -485,6 +2643,17 @@ mod test {
         assert_eq!(arena.offset.get(), 32);
     }
 
+    #[test]
+    fn alloc_fmt() {
+        let arena = Arena::new();
+        let name = "doge";
+
+        assert_eq!(arena.alloc_fmt(format_args!("{} to the {}!", name, 42)), "doge to the 42!");
+        assert_eq!(arena_format!(arena, "{}_{}", name, 1), "doge_1");
+        assert_eq!(arena_format!(&arena, "static"), "static");
+        assert_eq!(arena.offset.get(), 16 + 8 + 8);
+    }
+
     #[test]
     fn alloc_nul_term_str() {
         let arena = Arena::new();
@@ -497,6 +2666,307 @@ mod test {
             "abcdefghijk\u{0}".as_bytes(),
         );
 
-        assert_eq!(&**nts, "abcdefghijk");
+        assert_eq!(&*nts, "abcdefghijk");
+        assert_eq!(nts.len(), 11);
+    }
+
+    #[test]
+    fn nul_term_str_access() {
+        let arena = Arena::new();
+        let nts = arena.alloc_nul_term_str("doge");
+
+        assert_eq!(nts.byte(0), b'd');
+        assert_eq!(nts.byte(3), b'e');
+        assert_eq!(nts.byte(4), 0);
+        assert_eq!(nts.byte(100), 0);
+        assert_eq!(nts.as_str(), "doge");
+        assert_eq!(nts.as_c_str().to_str(), Ok("doge"));
+
+        let with_nul = arena.alloc_nul_term_str("foo\0bar");
+        let c_str: &CStr = with_nul.into();
+
+        assert_eq!(c_str.to_bytes(), b"foo");
+        assert_eq!(with_nul.len(), 7);
+    }
+
+    #[test]
+    fn try_alloc() {
+        let arena = Arena::new();
+
+        assert_eq!(arena.try_alloc(42u64), Ok(&mut 42));
+        assert_eq!(arena.try_alloc_slice(&[10u16, 20u16]), Ok(&[10u16, 20u16][..]));
+        assert_eq!(arena.try_alloc_str("doge"), Ok("doge"));
+        assert_eq!(arena.offset.get(), 24);
+    }
+
+    #[test]
+    fn try_alloc_fails_gracefully() {
+        let arena = Arena::new();
+        let err = arena.try_alloc_uninitialized::<[u64; 1 << 50]>().err();
+
+        assert_eq!(err.map(|err| err.size()), Some(8 << 50));
+
+        // Arena is still usable afterwards
+        assert_eq!(arena.try_alloc(42u64), Ok(&mut 42));
+    }
+
+    #[test]
+    fn with_limit() {
+        let arena = Arena::with_limit(ARENA_BLOCK * 2);
+
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK);
+        assert_eq!(arena.try_alloc(42u64), Ok(&mut 42));
+
+        // Requires a second page
+        assert!(arena.try_alloc_uninitialized::<[u8; ARENA_BLOCK]>().is_ok());
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 2);
+
+        // Requires a third page
+        let err = arena.try_alloc_uninitialized::<[u8; ARENA_BLOCK]>().err().unwrap();
+
+        assert_eq!(err.size(), ARENA_BLOCK);
+        assert!(err.limit_exceeded());
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 2);
+
+        // Dedicated allocations are covered by the limit too
+        let err = arena.try_alloc_uninitialized::<[u8; ARENA_BLOCK + 1]>().err().unwrap();
+
+        assert!(err.limit_exceeded());
+    }
+
+    #[cfg(feature = "debug-poison")]
+    #[test]
+    fn poisons_reclaimed_memory() {
+        let mut arena = Arena::new();
+
+        let first = arena.alloc(0x1234_5678u32) as *const u32;
+
+        for n in 0..ARENA_BLOCK {
+            arena.alloc(n);
+        }
+
+        let last = arena.alloc(0xABCD_EF01u32) as *const u32;
+
+        arena.reset();
+
+        let poisoned = u32::from_ne_bytes([POISON_BYTE; 4]);
+
+        assert_eq!(unsafe { *first }, poisoned);
+        assert_eq!(unsafe { *last }, poisoned);
+
+        let kept = arena.alloc(1u64) as *const u64;
+        let offset = unsafe { arena.offset() };
+        let cleared = arena.alloc(2u64) as *const u64;
+
+        unsafe { arena.reset_to(offset) };
+
+        assert_eq!(unsafe { *kept }, 1);
+        assert_eq!(unsafe { *cleared }, u64::from_ne_bytes([POISON_BYTE; 8]));
+    }
+
+    #[test]
+    fn contains() {
+        let mut arena = Arena::new();
+        let other = Arena::new();
+
+        let small = arena.alloc(7u8) as *const u8;
+        let str = arena.alloc_str("doge") as *const str;
+        let adopted = arena.alloc_vec(vec![1u32, 2, 3]) as *const [u32];
+        let huge = arena.alloc_slice(&[0u8; ARENA_BLOCK * 2]) as *const [u8];
+        let local = 42u8;
+
+        assert!(arena.contains(small));
+        assert!(arena.contains(str));
+        assert!(arena.contains(adopted));
+        assert!(arena.contains(huge));
+        assert!(!arena.contains(&local));
+        assert!(!other.contains(small));
+
+        for n in 0..ARENA_BLOCK {
+            arena.alloc(n);
+        }
+
+        assert!(arena.contains(small));
+
+        arena.reset();
+
+        assert!(!arena.contains(small));
+        assert!(!arena.contains(adopted));
+    }
+
+    #[test]
+    fn wasted_bytes() {
+        let arena = Arena::new();
+
+        let mut vec = Vec::with_capacity(10);
+        vec.extend_from_slice(&[1u32, 2, 3]);
+
+        assert_eq!(arena.alloc_vec(vec), &[1, 2, 3]);
+        assert_eq!(arena.wasted_bytes(), 7 * 4);
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK + 10 * 4);
+
+        let mut string = String::with_capacity(8);
+        string.push_str("foo");
+
+        assert_eq!(arena.alloc_string(string), "foo");
+        assert_eq!(arena.wasted_bytes(), 7 * 4 + 5);
+    }
+
+    #[test]
+    fn recycle() {
+        let mut arena = Arena::new();
+
+        let a = NonNull::from(arena.alloc([1u64; 3]));
+        let b = NonNull::from(arena.alloc([2u64; 3]));
+        let big = NonNull::from(arena.alloc([0u8; 256]));
+
+        unsafe {
+            arena.recycle(a);
+            arena.recycle(b);
+            arena.recycle(big);
+        }
+
+        assert_eq!(arena.recycled_bytes(), 48);
+
+        let offset = arena.offset.get();
+
+        // Same size class, reused in reverse order of recycling
+        assert_eq!(arena.alloc((1u64, 2u64, 3u32)) as *const _ as usize, b.as_ptr() as usize);
+        assert_eq!(arena.alloc([3u64; 3]) as *mut _, a.as_ptr());
+        assert_eq!(arena.offset.get(), offset);
+        assert_eq!(arena.recycled_bytes(), 0);
+
+        unsafe { arena.recycle(NonNull::from(arena.alloc(1u8))) };
+
+        assert_eq!(arena.recycled_bytes(), 8);
+
+        arena.reset();
+
+        assert_eq!(arena.recycled_bytes(), 0);
+        assert_eq!(arena.alloc(1u8) as *const u8, arena.store.get_mut()[0].ptr.as_ptr());
+    }
+
+    #[test]
+    fn alloc_vec_shrunk() {
+        let arena = Arena::new();
+
+        let mut vec = Vec::with_capacity(10);
+        vec.extend_from_slice(&[1u32, 2, 3]);
+
+        assert_eq!(arena.alloc_vec_shrunk(vec), &[1, 2, 3]);
+        assert_eq!(arena.wasted_bytes(), 0);
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK + 3 * 4);
+    }
+
+    #[test]
+    fn grow_hint() {
+        let mut arena = Arena::new();
+
+        assert_eq!(arena.grow_hint(ARENA_BLOCK), 0);
+        assert_eq!(arena.grow_hint(ARENA_BLOCK + 1), ARENA_BLOCK + 1);
+
+        arena.alloc(0u64);
+
+        assert_eq!(arena.grow_hint(ARENA_BLOCK - 8), 0);
+        assert_eq!(arena.grow_hint(ARENA_BLOCK - 7), ARENA_BLOCK);
+
+        let allocated = arena.allocated_bytes();
+
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+
+        assert_eq!(arena.allocated_bytes(), allocated + ARENA_BLOCK);
+
+        arena.reset();
+        arena.alloc(0u64);
+
+        assert_eq!(arena.grow_hint(ARENA_BLOCK), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "arena memory limit exceeded")]
+    fn with_limit_panics() {
+        let arena = Arena::with_limit(ARENA_BLOCK);
+
+        arena.alloc(42u64);
+        arena.alloc_uninitialized::<[u8; ARENA_BLOCK]>();
+    }
+
+    #[test]
+    fn with_limit_adopted_vecs() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let arena = Arena::with_limit(ARENA_BLOCK + 64);
+
+        assert!(arena.alloc_vec(Vec::<u8>::with_capacity(64)).is_empty());
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK + 64);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            arena.alloc_string(String::from("doge"));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK + 64);
+    }
+
+    #[test]
+    fn alloc_error_hook() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Other tests may fail allocations while the hook is set, so only
+        // errors for this odd size are counted
+        const SIZE: usize = ARENA_BLOCK + 7;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        set_alloc_error_hook(move |err| {
+            if err.size() == SIZE && err.limit_exceeded() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let arena = Arena::with_limit(ARENA_BLOCK);
+
+        arena.alloc(42u64);
+
+        assert!(arena.try_alloc_uninitialized::<[u8; SIZE]>().is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            arena.alloc_uninitialized::<[u8; SIZE]>();
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(take_alloc_error_hook().is_some());
+        assert!(take_alloc_error_hook().is_none());
+    }
+
+    #[test]
+    fn alloc_str_uninit() {
+        let arena = Arena::new();
+        let before = arena.offset.get();
+        let mut region = arena.alloc_str_uninit(100);
+
+        assert_eq!(region.capacity(), 100);
+
+        let len = region.write_str(0, "doge");
+        region.write(len, b'!');
+
+        let doge = unsafe { region.finish(len + 1) };
+
+        assert_eq!(doge, "doge!");
+        assert_eq!(arena.offset.get() - before, 8);
+
+        // Slack can't be returned with another allocation in the way
+        let mut region = arena.alloc_str_uninit(32);
+        let moon = arena.alloc_str("moon");
+        let len = region.write_str(0, "to");
+
+        assert_eq!(unsafe { region.finish(len) }, "to");
+        assert_eq!(moon, "moon");
+        assert_eq!(arena.offset.get() - before, 48);
     }
 }
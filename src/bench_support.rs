@@ -0,0 +1,157 @@
+//! Helpers for benchmarking data structures on the `Arena`, available with
+//! the `bench-support` feature flag.
+//!
+//! These are the same word lists and arena reset idioms the benchmarks of
+//! this crate use, so that downstream crates can compare their own arena
+//! data structures on equal footing.
+//!
+//! ```rust
+//! use toolshed::Arena;
+//! use toolshed::list::List;
+//! use toolshed::bench_support::{black_box, measure_in_arena, WORDS};
+//!
+//! let mut arena = Arena::new();
+//!
+//! let measurement = measure_in_arena(&mut arena, 1000, |arena| {
+//!     black_box(List::from_iter(arena, WORDS.iter().cloned()));
+//! });
+//!
+//! assert_eq!(measurement.iterations, 1000);
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::arena::Arena;
+
+pub use std::hint::black_box;
+
+/// A list of 65 unique words, taken from the source of this crate.
+pub static WORDS: &[&str] = &[
+    "ARENA_BLOCK", "Arena", "Cell", "Self", "String", "T", "Vec", "_unchecked", "a",
+    "alignment", "alloc", "alloc_bytes", "alloc_str", "alloc_str_zero_end", "alloc_string",
+    "as", "as_bytes", "as_mut_ptr", "as_ptr", "block", "cap", "cell", "const",
+    "copy_nonoverlapping", "else", "extend_from_slice", "fn", "from_raw_parts", "from_utf",
+    "get", "grow", "if", "impl", "inline", "into", "into_bytes", "isize", "len",
+    "len_with_zero", "let", "mem", "mut", "new", "offset", "ptr", "pub", "push",
+    "replace", "return", "self", "set", "size_of", "slice", "std", "store", "str",
+    "struct", "temp", "u", "unsafe", "use", "usize", "val", "vec", "with_capacity"
+];
+
+/// A short list of words to put in sets and maps, all but the last of
+/// which are also in `WORDS`.
+pub static SET_WORDS: &[&str] = &["alloc_bytes", "alloc", "Cell", "String", "yetAnother"];
+
+/// Wrap the closure `f` so that the `arena` is reset before every call.
+///
+/// The resulting closure can be passed to `test::Bencher::iter`, or any
+/// other harness, to benchmark creation of structures on the arena without
+/// the arena growing across iterations. Since results can't outlive the
+/// reset, they should be passed to `black_box` inside of `f`.
+pub fn reset_each<'a, F>(arena: &'a mut Arena, mut f: F) -> impl FnMut() + 'a
+where
+    F: FnMut(&Arena) + 'a,
+{
+    move || {
+        arena.reset();
+        f(arena)
+    }
+}
+
+/// Result of a `measure` run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// Number of times the measured closure was called.
+    pub iterations: u32,
+
+    /// Total time taken by all iterations.
+    pub total: Duration,
+}
+
+impl Measurement {
+    /// Average time taken by a single iteration.
+    #[inline]
+    pub fn per_iteration(&self) -> Duration {
+        self.total / self.iterations.max(1)
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}/iter ({} iterations)", self.per_iteration(), self.iterations)
+    }
+}
+
+/// Call `f` the given number of `iterations` times and measure the total
+/// time it took. Values returned from `f` are passed to `black_box`.
+pub fn measure<R, F>(iterations: u32, mut f: F) -> Measurement
+where
+    F: FnMut() -> R,
+{
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        black_box(f());
+    }
+
+    Measurement {
+        iterations,
+        total: start.elapsed(),
+    }
+}
+
+/// Like `measure`, but resetting the `arena` before every call to `f`,
+/// see `reset_each`.
+#[inline]
+pub fn measure_in_arena<F>(arena: &mut Arena, iterations: u32, f: F) -> Measurement
+where
+    F: FnMut(&Arena),
+{
+    measure(iterations, reset_each(arena, f))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::set::Set;
+
+    #[test]
+    fn words() {
+        assert_eq!(WORDS.len(), 65);
+        assert_eq!(SET_WORDS.iter().filter(|word| WORDS.contains(word)).count(), 4);
+    }
+
+    #[test]
+    fn reset_each_resets() {
+        let mut arena = Arena::new();
+
+        {
+            let mut run = reset_each(&mut arena, |arena| {
+                let mut set = Set::new();
+
+                for word in WORDS.iter() {
+                    set.insert(arena, *word);
+                }
+
+                black_box(set);
+            });
+
+            for _ in 0..1000 {
+                run();
+            }
+        }
+
+        assert_eq!(arena.allocated_bytes(), 64 * 1024);
+    }
+
+    #[test]
+    fn measure_calls() {
+        let mut calls = 0;
+
+        let measurement = measure(10, || calls += 1);
+
+        assert_eq!(calls, 10);
+        assert_eq!(measurement.iterations, 10);
+        assert!(measurement.per_iteration() <= measurement.total);
+    }
+}
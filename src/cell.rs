@@ -1,66 +1,248 @@
 //! A mutable memory location for `Copy` types.
+//!
+//! ## Aliasing model
+//!
+//! All the data structures in this crate are graphs of nodes allocated on the
+//! `Arena` and linked together with shared `&'arena` references. Nodes are never
+//! moved or freed while the arena is alive, and are never handed out as `&mut`
+//! once linked, so any number of shared references to them can coexist. All
+//! mutation of a node after allocation goes through a `CopyCell` inside of it,
+//! which wraps its value in an `UnsafeCell`.
+//!
+//! Since `UnsafeCell` can't implement `Copy`, neither can `CopyCell`. The
+//! collection handles (`List`, `Map`, `Set` and friends) hold their own state,
+//! such as the root node or the length, in plain fields so that they remain
+//! `Copy`, and take `&mut self` in the methods that change it. A copy of a handle
+//! is a snapshot of that state: it keeps pointing at the same nodes, but won't
+//! see a new root or length set through another copy.
+//!
+//! The values stored in a cell are only ever copied in and out, references to
+//! them are never handed out. Values that can be borrowed for `'arena`, such as
+//! the elements of a `List`, are written once when their node is allocated and
+//! are never modified afterwards.
+//!
+//! ## Memory ordering
+//!
+//! `CopyCell` is never `Sync`, and neither are the nodes that contain it, so
+//! the collections can't be shared or sent across threads. All reads and writes
+//! of a given cell therefore happen on a single thread, and program order is the
+//! only ordering there is.
+//!
+//! Structures shared across threads, such as ones built on the `SyncArena`,
+//! should use `AtomicCell` instead, which is `Sync` and gives reads and writes
+//! acquire / release semantics.
 
+use std::cell::UnsafeCell;
 use std::fmt::{self, Debug};
-use std::marker::PhantomData;
+use std::mem::{size_of, transmute_copy};
+use std::sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+use crate::arena::NoUninit;
 
 /// This should be identical to the `Cell` implementation in the standard
-/// library, but always require that the internal type implements `Copy`
-/// and implements `Copy` itself.
-#[derive(PartialEq, Eq, Copy, Clone)]
+/// library, but always require that the internal type implements `Copy`.
+///
+/// Unlike `Cell`, it's `Clone` for any `T: Copy`, cloning the contained value
+/// into a new, independent cell.
 #[repr(transparent)]
 pub struct CopyCell<T> {
     /// Internal value
-    value: T,
-
-    /// We trick the compiler to think that `CopyCell` contains a raw pointer,
-    /// this way we make sure the `Sync` marker is not implemented and `CopyCell`
-    /// cannot be shared across threads!
-    _no_sync: PhantomData<*mut T>
+    value: UnsafeCell<T>,
 }
 
-/// `Sync` is unsafe due to mutability, however `Send` is totally fine!
-unsafe impl<T> Send for CopyCell<T> {}
-
 impl<T> CopyCell<T> {
     /// Creates a new `CopyCell` containing the given value.
     pub const fn new(value: T) -> Self {
         CopyCell {
-            value,
-            _no_sync: PhantomData
+            value: UnsafeCell::new(value),
         }
     }
+
+    /// Consumes the cell, returning the contained value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Returns a raw pointer to the contained value.
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
 }
 
 impl<T: Copy> CopyCell<T> {
     /// Returns a copy of the contained value.
     #[inline]
     pub fn get(&self) -> T {
-        self.value
+        // `CopyCell` is not `Sync` and never hands out references to its
+        // value, so nothing can be writing to it while we read.
+        unsafe { *self.value.get() }
     }
 
     /// Sets the contained value.
     #[inline]
     pub fn set(&self, value: T) {
-        use std::ptr::write_volatile;
-
-        // Regular write produces abnormal behavior when running tests in
-        // `--release` mode. Reordering writes when the compiler assumes
-        // things are immutable is dangerous.
-        //
-        // We can just cast the pointer from `CopyCell<T>` to `T` because of
-        // #[repr(transparent)]
-        //
-        // This behavior is copied over from the std implementation of
-        // the `UnsafeCell`, and it's the best we can do right now in terms
-        // of soundness till we get a stable `UnsafeCell` that implements `Copy`.
-        unsafe { write_volatile(self as *const CopyCell<T> as *const T as *mut T, value) };
+        // Same as in `get`, nothing else can be reading or writing the value
+        // while we write to it.
+        unsafe { *self.value.get() = value };
+    }
+
+    /// Sets the contained value, returning the previous one.
+    #[inline]
+    pub fn replace(&self, value: T) -> T {
+        let previous = self.get();
+
+        self.set(value);
+
+        previous
+    }
+}
+
+impl<T: Copy> Clone for CopyCell<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        CopyCell::new(self.get())
     }
 }
 
-impl<T: Debug> Debug for CopyCell<T> {
+impl<T: Copy + PartialEq> PartialEq for CopyCell<T> {
+    #[inline]
+    fn eq(&self, other: &CopyCell<T>) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Copy + Eq> Eq for CopyCell<T> {}
+
+impl<T: Copy + Debug> Debug for CopyCell<T> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        Debug::fmt(&self.value, f)
+        Debug::fmt(&self.get(), f)
+    }
+}
+
+/// A thread-safe counterpart of the `CopyCell`, for `Copy` types that are
+/// 8, 16, 32 or 64 bits wide, such as integers, references or `Option`s of
+/// references. All operations map to a single atomic instruction.
+///
+/// Values are converted to and from integers of the same width, and compared
+/// bitwise by `compare_exchange`, so `T` must implement `NoUninit`.
+///
+/// ```rust
+/// use toolshed::AtomicCell;
+///
+/// let cell = AtomicCell::new(None::<&u64>);
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| cell.set(Some(&42)));
+/// });
+///
+/// assert_eq!(cell.get(), Some(&42));
+/// ```
+#[repr(C, align(8))]
+pub struct AtomicCell<T> {
+    value: UnsafeCell<T>,
+}
+
+/// All access to the value goes through atomic operations.
+unsafe impl<T: Send> Send for AtomicCell<T> {}
+unsafe impl<T: Send> Sync for AtomicCell<T> {}
+
+/// Dispatch an atomic operation on the value of the cell by its size.
+macro_rules! with_atomic {
+    ($cell:expr, |$atomic:ident, $bits:ident| $body:expr) => {
+        match size_of::<T>() {
+            1 => { type $bits = u8; let $atomic = unsafe { &*($cell.value.get() as *const AtomicU8) }; $body },
+            2 => { type $bits = u16; let $atomic = unsafe { &*($cell.value.get() as *const AtomicU16) }; $body },
+            4 => { type $bits = u32; let $atomic = unsafe { &*($cell.value.get() as *const AtomicU32) }; $body },
+            _ => { type $bits = u64; let $atomic = unsafe { &*($cell.value.get() as *const AtomicU64) }; $body },
+        }
+    };
+}
+
+impl<T: NoUninit> AtomicCell<T> {
+    const SIZE_CHECK: () = assert!(
+        matches!(size_of::<T>(), 1 | 2 | 4 | 8),
+        "AtomicCell only supports types that are 8, 16, 32 or 64 bits wide"
+    );
+
+    /// Creates a new `AtomicCell` containing the given value.
+    ///
+    /// Fails to compile if `T` isn't 8, 16, 32 or 64 bits wide.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SIZE_CHECK;
+
+        AtomicCell {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Returns a copy of the contained value.
+    #[inline]
+    pub fn get(&self) -> T {
+        with_atomic!(self, |atomic, Bits| unsafe {
+            transmute_copy::<Bits, T>(&atomic.load(Ordering::Acquire))
+        })
+    }
+
+    /// Sets the contained value.
+    #[inline]
+    pub fn set(&self, value: T) {
+        with_atomic!(self, |atomic, Bits| unsafe {
+            atomic.store(transmute_copy::<T, Bits>(&value), Ordering::Release)
+        })
+    }
+
+    /// Sets the contained value, returning the previous one.
+    #[inline]
+    pub fn replace(&self, value: T) -> T {
+        with_atomic!(self, |atomic, Bits| unsafe {
+            let previous = atomic.swap(transmute_copy::<T, Bits>(&value), Ordering::AcqRel);
+
+            transmute_copy::<Bits, T>(&previous)
+        })
+    }
+
+    /// Sets the contained value to `new` if it's bitwise equal to `current`.
+    ///
+    /// Returns the previous value, as `Ok` if it was replaced and as `Err`
+    /// otherwise.
+    #[inline]
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        with_atomic!(self, |atomic, Bits| unsafe {
+            atomic
+                .compare_exchange(
+                    transmute_copy::<T, Bits>(&current),
+                    transmute_copy::<T, Bits>(&new),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .map(|bits| transmute_copy::<Bits, T>(&bits))
+                .map_err(|bits| transmute_copy::<Bits, T>(&bits))
+        })
+    }
+
+    /// Consumes the cell, returning the contained value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: NoUninit + Default> Default for AtomicCell<T> {
+    #[inline]
+    fn default() -> Self {
+        AtomicCell::new(T::default())
+    }
+}
+
+impl<T: NoUninit + Debug> Debug for AtomicCell<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.get(), f)
     }
 }
 
@@ -71,14 +253,14 @@ mod test {
     #[test]
     fn cell() {
         let cell_a = CopyCell::new(42u64);
-        let cell_b = cell_a;  // copy
+        let cell_b = cell_a.clone();
         let cell_c = &cell_a; // reference
 
         assert_eq!(cell_a.get(), 42);
         assert_eq!(cell_b.get(), 42);
         assert_eq!(cell_c.get(), 42);
 
-        // Only affects the copy
+        // Only affects the clone
         cell_b.set(100);
 
         assert_eq!(cell_a.get(), 42);
@@ -92,7 +274,7 @@ mod test {
         assert_eq!(cell_b.get(), 100);
         assert_eq!(cell_c.get(), 200);
 
-        // Again, only affects the copy
+        // Again, only affects the clone
         cell_b.set(300);
 
         assert_eq!(cell_a.get(), 200);
@@ -108,4 +290,72 @@ mod test {
 
         assert_eq!(cell.get(), REF);
     }
+
+    #[test]
+    fn atomic_cell() {
+        let a = AtomicCell::new(1u8);
+        let b = AtomicCell::new(-2i16);
+        let c = AtomicCell::new('c');
+        let d = AtomicCell::new(4.0f64);
+
+        a.set(10);
+        b.set(-20);
+
+        assert_eq!(a.get(), 10);
+        assert_eq!(b.get(), -20);
+        assert_eq!(c.replace('C'), 'c');
+        assert_eq!(c.get(), 'C');
+        assert_eq!(d.compare_exchange(1.0, 2.0), Err(4.0));
+        assert_eq!(d.compare_exchange(4.0, 2.0), Ok(4.0));
+        assert_eq!(d.into_inner(), 2.0);
+    }
+
+    #[test]
+    fn atomic_cell_no_uninit_struct() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[repr(C)]
+        struct Range {
+            start: u16,
+            end: u16,
+        }
+
+        unsafe impl NoUninit for Range {}
+
+        let cell = AtomicCell::new(Range { start: 0, end: 1 });
+
+        assert_eq!(cell.compare_exchange(Range { start: 0, end: 1 }, Range { start: 1, end: 2 }), Ok(Range { start: 0, end: 1 }));
+        assert_eq!(cell.get(), Range { start: 1, end: 2 });
+    }
+
+    #[test]
+    fn atomic_cell_refs() {
+        static NUMBERS: [u64; 2] = [1, 2];
+
+        let cell = AtomicCell::new(None);
+
+        assert_eq!(cell.compare_exchange(None, Some(&NUMBERS[0])), Ok(None));
+        assert_eq!(cell.compare_exchange(None, Some(&NUMBERS[1])), Err(Some(&1)));
+        assert_eq!(format!("{:?}", cell), "Some(1)");
+    }
+
+    #[test]
+    fn atomic_cell_counter() {
+        let counter = AtomicCell::new(0usize);
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        let mut current = counter.get();
+
+                        while let Err(actual) = counter.compare_exchange(current, current + 1) {
+                            current = actual;
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(counter.get(), 4000);
+    }
 }
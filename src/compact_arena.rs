@@ -0,0 +1,230 @@
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+
+use crate::arena::{padded_size, AllocError, Page};
+
+/// An arena that keeps all of its memory in a single contiguous reservation
+/// of up to 4GiB, so that values on it can be referred to with 32-bit
+/// `ArenaRef` offsets rather than full pointers.
+///
+/// The whole capacity is requested from the allocator up front. On most
+/// platforms the allocator only reserves address space for big blocks,
+/// with physical memory being committed as the pages are first written to,
+/// so it's fine to pick a generous capacity.
+///
+/// ```rust
+/// use toolshed::{CompactArena, ArenaRef};
+///
+/// #[derive(Clone, Copy)]
+/// struct Node {
+///     value: u32,
+///     next: Option<ArenaRef<Node>>,
+/// }
+///
+/// let arena = CompactArena::with_capacity(1024 * 1024);
+///
+/// let tail = arena.alloc(Node { value: 2, next: None });
+/// let head = arena.alloc(Node { value: 1, next: Some(tail) });
+///
+/// unsafe {
+///     let head = head.get(&arena);
+///
+///     assert_eq!(head.value, 1);
+///     assert_eq!(head.next.unwrap().get(&arena).value, 2);
+/// }
+/// ```
+pub struct CompactArena {
+    page: Page,
+    offset: Cell<usize>,
+}
+
+impl CompactArena {
+    /// Create a new `CompactArena` reserving `capacity` bytes.
+    ///
+    /// **Panics** if `capacity` is larger than `u32::MAX`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity <= u32::MAX as usize, "CompactArena capacity can't exceed u32::MAX");
+
+        CompactArena {
+            page: Page::alloc(capacity.max(1), 1).unwrap_or_else(|err| err.handle()),
+            offset: Cell::new(0),
+        }
+    }
+
+    /// Total number of bytes reserved by the arena.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.page.size()
+    }
+
+    /// Number of bytes taken by allocations so far, including padding.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// Put the value onto the arena and return an `ArenaRef` to it.
+    ///
+    /// **Panics** if the arena is out of capacity.
+    #[inline]
+    pub fn alloc<T: Copy>(&self, value: T) -> ArenaRef<T> {
+        self.try_alloc(value).unwrap_or_else(|err| err.handle())
+    }
+
+    /// Put the value onto the arena and return an `ArenaRef` to it, or an
+    /// `AllocError` if the arena is out of capacity.
+    #[inline]
+    pub fn try_alloc<T: Copy>(&self, value: T) -> Result<ArenaRef<T>, AllocError> {
+        let size = size_of::<T>();
+        let base = self.page.as_ptr() as usize;
+        let align = align_of::<T>();
+        let start = ((base + self.offset.get() + (align - 1)) & !(align - 1)) - base;
+
+        if start + size > self.capacity() {
+            return Err(AllocError::over_limit(size));
+        }
+
+        self.offset.set(start + padded_size(size));
+
+        unsafe { (self.page.as_ptr().add(start) as *mut T).write(value) }
+
+        Ok(ArenaRef {
+            offset: start as u32,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl fmt::Debug for CompactArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompactArena")
+            .field("capacity", &self.capacity())
+            .field("allocated", &self.allocated_bytes())
+            .finish()
+    }
+}
+
+/// A 32-bit reference to a value on a `CompactArena`, half the size of a
+/// regular reference on 64-bit platforms.
+///
+/// `ArenaRef` doesn't know which `CompactArena` it belongs to, so it has to
+/// be dereferenced with the arena it was allocated on.
+pub struct ArenaRef<T> {
+    offset: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ArenaRef<T> {
+    /// Get the offset of the value from the start of the arena.
+    #[inline]
+    pub fn offset(self) -> u32 {
+        self.offset
+    }
+
+    /// Get a reference to the value on the `arena`.
+    ///
+    /// # Safety
+    ///
+    /// The `arena` must be the `CompactArena` this `ArenaRef` came from.
+    /// There is no way to check that, and an `ArenaRef` used with another
+    /// arena points at arbitrary memory, possibly of a different type.
+    #[inline]
+    pub unsafe fn get(self, arena: &CompactArena) -> &T {
+        debug_assert!(self.offset as usize + size_of::<T>() <= arena.allocated_bytes());
+
+        &*(arena.page.as_ptr().add(self.offset as usize) as *const T)
+    }
+}
+
+impl<T> Clone for ArenaRef<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaRef<T> {}
+
+impl<T> PartialEq for ArenaRef<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+    }
+}
+
+impl<T> Eq for ArenaRef<T> {}
+
+impl<T> fmt::Debug for ArenaRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ArenaRef({})", self.offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct Node {
+        value: u64,
+        next: Option<ArenaRef<Node>>,
+    }
+
+    #[test]
+    fn alloc_and_get() {
+        let arena = CompactArena::with_capacity(1024);
+
+        let a = arena.alloc(1u8);
+        let b = arena.alloc(2u64);
+        let c = arena.alloc((3u16, 4u32));
+
+        unsafe {
+            assert_eq!(a.get(&arena), &1);
+            assert_eq!(b.get(&arena), &2);
+            assert_eq!(c.get(&arena), &(3, 4));
+        }
+
+        assert_eq!(b.offset() % 8, 0);
+    }
+
+    #[test]
+    fn linked_nodes() {
+        let arena = CompactArena::with_capacity(1024);
+        let mut next = None;
+
+        for value in 0..10 {
+            next = Some(arena.alloc(Node { value, next }));
+        }
+
+        let mut values = Vec::new();
+
+        while let Some(node) = next {
+            let node = unsafe { node.get(&arena) };
+
+            values.push(node.value);
+            next = node.next;
+        }
+
+        assert_eq!(values, [9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn out_of_capacity() {
+        let arena = CompactArena::with_capacity(16);
+
+        assert!(arena.try_alloc(1u64).is_ok());
+        assert!(arena.try_alloc(2u64).is_ok());
+
+        let err = arena.try_alloc(3u64).unwrap_err();
+
+        assert_eq!(err.size(), 8);
+        assert_eq!(err.limit_exceeded(), true);
+    }
+
+    #[test]
+    fn compact_size() {
+        assert_eq!(size_of::<ArenaRef<Node>>(), 4);
+    }
+}
@@ -0,0 +1,437 @@
+//! Deserializing with **serde** directly into collections on the `Arena`,
+//! available with the `impl_serialize` feature flag.
+//!
+//! Arena collections can't implement `Deserialize`, since they need the
+//! `Arena` to allocate in. Instead, an `ArenaSeed` carrying the `Arena` is
+//! passed to `DeserializeSeed::deserialize`, and types that can be created
+//! on the `Arena` implement `DeserializeIn`. Strings are copied into the
+//! `Arena` with `alloc_str`, so they don't borrow from the input.
+//!
+//! ```rust
+//! use serde::de::DeserializeSeed;
+//! use toolshed::Arena;
+//! use toolshed::de::MapSeed;
+//! use toolshed::list::List;
+//!
+//! let arena = Arena::new();
+//! let mut json = serde_json::Deserializer::from_str(r#"{"doge": [1, 2], "moon": [3]}"#);
+//!
+//! let map = MapSeed::<&str, List<u32>>::new(&arena).deserialize(&mut json).unwrap();
+//!
+//! assert_eq!(map.get("doge").unwrap().iter().copied().collect::<Vec<_>>(), [1, 2]);
+//! assert_eq!(map.get("moon").unwrap().iter().copied().collect::<Vec<_>>(), [3]);
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::arena::Arena;
+use crate::list::{GrowableList, List};
+use crate::map::Map;
+use crate::set::Set;
+use crate::value::Value;
+
+/// Types that can be deserialized into the `Arena`.
+pub trait DeserializeIn<'arena>: Sized {
+    /// Deserialize a value, allocating any of its data on the `arena`.
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// A `DeserializeSeed` for any `T` implementing `DeserializeIn`, carrying
+/// the `Arena` to deserialize it into.
+pub struct ArenaSeed<'arena, T> {
+    arena: &'arena Arena,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// An `ArenaSeed` for a `List`.
+pub type ListSeed<'arena, T> = ArenaSeed<'arena, List<'arena, T>>;
+
+/// An `ArenaSeed` for a `Map`.
+pub type MapSeed<'arena, K, V> = ArenaSeed<'arena, Map<'arena, K, V>>;
+
+/// An `ArenaSeed` for a `Set`.
+pub type SetSeed<'arena, I> = ArenaSeed<'arena, Set<'arena, I>>;
+
+impl<'arena, T> ArenaSeed<'arena, T> {
+    /// Create a new seed deserializing into the `arena`.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        ArenaSeed {
+            arena,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T> Clone for ArenaSeed<'arena, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'arena, T> Copy for ArenaSeed<'arena, T> {}
+
+impl<'arena, T> fmt::Debug for ArenaSeed<'arena, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ArenaSeed")
+    }
+}
+
+impl<'de, 'arena, T> DeserializeSeed<'de> for ArenaSeed<'arena, T>
+where
+    T: DeserializeIn<'arena>,
+{
+    type Value = T;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_in(self.arena, deserializer)
+    }
+}
+
+macro_rules! impl_deserialize_in {
+    ($( $ty:ty ),*) => {
+        $(
+            impl<'arena> DeserializeIn<'arena> for $ty {
+                #[inline]
+                fn deserialize_in<'de, D>(_: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    <$ty>::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_deserialize_in!(
+    (), bool, char, f32, f64,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize
+);
+
+struct StrVisitor<'arena>(&'arena Arena);
+
+impl<'de, 'arena> Visitor<'de> for StrVisitor<'arena> {
+    type Value = &'arena str;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<&'arena str, E> {
+        Ok(self.0.alloc_str(value))
+    }
+}
+
+impl<'arena> DeserializeIn<'arena> for &'arena str {
+    #[inline]
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrVisitor(arena))
+    }
+}
+
+impl<'arena, T> DeserializeIn<'arena> for Option<T>
+where
+    T: DeserializeIn<'arena>,
+{
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OptionVisitor<'arena, T>(ArenaSeed<'arena, T>);
+
+        impl<'de, 'arena, T: DeserializeIn<'arena>> Visitor<'de> for OptionVisitor<'arena, T> {
+            type Value = Option<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an option")
+            }
+
+            #[inline]
+            fn visit_none<E: de::Error>(self) -> Result<Option<T>, E> {
+                Ok(None)
+            }
+
+            #[inline]
+            fn visit_unit<E: de::Error>(self) -> Result<Option<T>, E> {
+                Ok(None)
+            }
+
+            #[inline]
+            fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<T>, D::Error> {
+                self.0.deserialize(deserializer).map(Some)
+            }
+        }
+
+        deserializer.deserialize_option(OptionVisitor(ArenaSeed::new(arena)))
+    }
+}
+
+struct ListVisitor<'arena, T>(ArenaSeed<'arena, T>);
+
+impl<'de, 'arena, T> Visitor<'de> for ListVisitor<'arena, T>
+where
+    T: DeserializeIn<'arena> + Copy + 'arena,
+{
+    type Value = List<'arena, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<List<'arena, T>, A::Error> {
+        let mut list = GrowableList::new();
+
+        while let Some(item) = seq.next_element_seed(self.0)? {
+            list.push(self.0.arena, item);
+        }
+
+        Ok(list.as_list())
+    }
+}
+
+impl<'arena, T> DeserializeIn<'arena> for List<'arena, T>
+where
+    T: DeserializeIn<'arena> + Copy + 'arena,
+{
+    #[inline]
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ListVisitor(ArenaSeed::new(arena)))
+    }
+}
+
+struct MapVisitor<'arena, K, V> {
+    arena: &'arena Arena,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'de, 'arena, K, V> Visitor<'de> for MapVisitor<'arena, K, V>
+where
+    K: DeserializeIn<'arena> + Eq + Hash + Copy + 'arena,
+    V: DeserializeIn<'arena> + Copy + 'arena,
+{
+    type Value = Map<'arena, K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Map<'arena, K, V>, A::Error> {
+        let mut map = Map::new();
+
+        while let Some((key, value)) = access.next_entry_seed(ArenaSeed::new(self.arena), ArenaSeed::new(self.arena))? {
+            map.insert(self.arena, key, value);
+        }
+
+        Ok(map)
+    }
+}
+
+/// If a key repeats, the last value for it is kept.
+impl<'arena, K, V> DeserializeIn<'arena> for Map<'arena, K, V>
+where
+    K: DeserializeIn<'arena> + Eq + Hash + Copy + 'arena,
+    V: DeserializeIn<'arena> + Copy + 'arena,
+{
+    #[inline]
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor {
+            arena,
+            marker: PhantomData,
+        })
+    }
+}
+
+struct SetVisitor<'arena, I>(ArenaSeed<'arena, I>);
+
+impl<'de, 'arena, I> Visitor<'de> for SetVisitor<'arena, I>
+where
+    I: DeserializeIn<'arena> + Eq + Hash + Copy + 'arena,
+{
+    type Value = Set<'arena, I>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Set<'arena, I>, A::Error> {
+        let mut set = Set::new();
+
+        while let Some(item) = seq.next_element_seed(self.0)? {
+            set.insert(self.0.arena, item);
+        }
+
+        Ok(set)
+    }
+}
+
+impl<'arena, I> DeserializeIn<'arena> for Set<'arena, I>
+where
+    I: DeserializeIn<'arena> + Eq + Hash + Copy + 'arena,
+{
+    #[inline]
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SetVisitor(ArenaSeed::new(arena)))
+    }
+}
+
+struct ValueVisitor<'arena>(&'arena Arena);
+
+impl<'de, 'arena> Visitor<'de> for ValueVisitor<'arena> {
+    type Value = Value<'arena>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    #[inline]
+    fn visit_unit<E: de::Error>(self) -> Result<Value<'arena>, E> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn visit_none<E: de::Error>(self) -> Result<Value<'arena>, E> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value<'arena>, D::Error> {
+        Value::deserialize_in(self.0, deserializer)
+    }
+
+    #[inline]
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value<'arena>, E> {
+        Ok(Value::Bool(value))
+    }
+
+    #[inline]
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value<'arena>, E> {
+        Ok(Value::Int(value))
+    }
+
+    /// Integers that don't fit an `i64` become a `Float`, same as in the
+    /// JSON parser of this crate.
+    #[inline]
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value<'arena>, E> {
+        match i64::try_from(value) {
+            Ok(value) => Ok(Value::Int(value)),
+            Err(_)    => Ok(Value::Float(value as f64)),
+        }
+    }
+
+    #[inline]
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value<'arena>, E> {
+        Ok(Value::Float(value))
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value<'arena>, E> {
+        Ok(Value::Str(self.0.alloc_str(value)))
+    }
+
+    #[inline]
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Value<'arena>, A::Error> {
+        ListVisitor(ArenaSeed::new(self.0)).visit_seq(seq).map(Value::List)
+    }
+
+    #[inline]
+    fn visit_map<A: MapAccess<'de>>(self, access: A) -> Result<Value<'arena>, A::Error> {
+        MapVisitor {
+            arena: self.0,
+            marker: PhantomData,
+        }.visit_map(access).map(Value::Map)
+    }
+}
+
+impl<'arena> DeserializeIn<'arena> for Value<'arena> {
+    #[inline]
+    fn deserialize_in<'de, D>(arena: &'arena Arena, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor(arena))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json;
+
+    fn from_str<'arena, T: DeserializeIn<'arena>>(arena: &'arena Arena, json: &str) -> serde_json::Result<T> {
+        ArenaSeed::new(arena).deserialize(&mut serde_json::Deserializer::from_str(json))
+    }
+
+    #[test]
+    fn list_can_be_deserialized() {
+        let arena = Arena::new();
+        let json = String::from(r#"["doge", "to", "the", "moon!"]"#);
+
+        let list: List<&str> = from_str(&arena, &json).unwrap();
+
+        drop(json);
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), ["doge", "to", "the", "moon!"]);
+    }
+
+    #[test]
+    fn map_and_set_can_be_deserialized() {
+        let arena = Arena::new();
+
+        let map: Map<&str, Option<u64>> = from_str(&arena, r#"{"foo": 10, "bar": null, "foo": 30}"#).unwrap();
+        let set: Set<&str> = SetSeed::new(&arena)
+            .deserialize(&mut serde_json::Deserializer::from_str(r#"["foo", "bar", "foo"]"#))
+            .unwrap();
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"foo", Some(30)), (&"bar", None)]);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn value_can_be_deserialized() {
+        let arena = Arena::new();
+        let json = r#"{"name":"doge","age":12,"weight":7.5,"tags":[true,null]}"#;
+
+        let value: Value = from_str(&arena, json).unwrap();
+
+        assert_eq!(value.get("name"), Some(Value::Str("doge")));
+        assert_eq!(value.get("age"), Some(Value::Int(12)));
+        assert_eq!(value.get("weight"), Some(Value::Float(7.5)));
+        assert_eq!(value.get("tags").unwrap().index(1), Some(Value::Null));
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+    }
+
+    #[test]
+    fn errors() {
+        let arena = Arena::new();
+
+        assert!(from_str::<List<u8>>(&arena, r#"[1, 256]"#).is_err());
+        assert!(from_str::<Map<&str, u8>>(&arena, r#"[1]"#).is_err());
+    }
+}
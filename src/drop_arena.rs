@@ -0,0 +1,211 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem::{align_of, needs_drop, size_of, MaybeUninit};
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+
+use crate::arena::Arena;
+
+/// Type-erased drop glue for a single value allocated on a `DropArena`.
+#[derive(Clone, Copy)]
+struct DropNode {
+    ptr: *mut u8,
+    drop: unsafe fn(*mut u8),
+    next: Option<NonNull<DropNode>>,
+}
+
+unsafe fn drop_erased<T>(ptr: *mut u8) {
+    ptr::drop_in_place(ptr as *mut T)
+}
+
+/// A variant of the `Arena` that can hold values that aren't `Copy`, such as
+/// `String`s or file handles. Destructors of all values allocated with
+/// `DropArena::alloc` are run, in reverse order of allocation, when the
+/// `DropArena` is dropped.
+///
+/// `DropArena` dereferences to the underlying `Arena`, so it can be used for
+/// `Copy` allocations and with all the data structures in this crate. It does
+/// not allow mutable access to the `Arena`, as resetting it would free memory
+/// of values that are yet to be dropped.
+///
+/// ```rust
+/// use toolshed::DropArena;
+///
+/// let arena = DropArena::new();
+///
+/// let name: &mut String = arena.alloc(String::from("doge"));
+///
+/// name.push_str(" to the moon");
+///
+/// assert_eq!(name, "doge to the moon");
+/// ```
+///
+/// Values can borrow data that lives for `'a`, which has to outlive the
+/// `DropArena`, since their destructors run when the arena is dropped:
+///
+/// ```rust,compile_fail
+/// use toolshed::DropArena;
+///
+/// struct Noisy<'a>(&'a String);
+///
+/// impl Drop for Noisy<'_> {
+///     fn drop(&mut self) {
+///         println!("{}", self.0);
+///     }
+/// }
+///
+/// let arena = DropArena::new();
+/// let name = String::from("doge");
+///
+/// arena.alloc(Noisy(&name));
+/// ```
+pub struct DropArena<'a> {
+    arena: Arena,
+    drops: Cell<Option<NonNull<DropNode>>>,
+    // Invariant, so that a `DropArena` can't be coerced to accept values
+    // living shorter than the ones it will be dropped with
+    values: PhantomData<fn(&'a ()) -> &'a ()>,
+}
+
+impl Default for DropArena<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DropArena<'a> {
+    /// Create a new `DropArena` with a single preallocated 64KiB page.
+    pub fn new() -> Self {
+        DropArena {
+            arena: Arena::new(),
+            drops: Cell::new(None),
+            values: PhantomData,
+        }
+    }
+
+    /// Put the value onto the arena and return a reference to it. If the type
+    /// needs to be dropped, its destructor will run when the `DropArena` is
+    /// dropped.
+    #[inline]
+    pub fn alloc<T: 'a>(&self, value: T) -> &mut T {
+        let slot = self.slot();
+
+        // Drop glue is only registered once the value is written, so that
+        // failing to allocate the node leaks the value instead of dropping
+        // uninitialized memory.
+        let value = slot.write(value);
+
+        if needs_drop::<T>() {
+            let node = self.arena.alloc(DropNode {
+                ptr: value as *mut T as *mut u8,
+                drop: drop_erased::<T>,
+                next: self.drops.get(),
+            });
+
+            self.drops.set(Some(NonNull::from(node)));
+        }
+
+        value
+    }
+
+    #[inline]
+    fn slot<T>(&self) -> Slot<'_, T> {
+        let ptr = self.arena.require_aligned(size_of::<T>(), align_of::<T>()) as *mut MaybeUninit<T>;
+
+        Slot(unsafe { &mut *ptr })
+    }
+}
+
+/// Uninitialized memory for a `T` on the `DropArena`.
+struct Slot<'arena, T>(&'arena mut MaybeUninit<T>);
+
+impl<'arena, T> Slot<'arena, T> {
+    #[inline]
+    fn write(self, value: T) -> &'arena mut T {
+        self.0.write(value)
+    }
+}
+
+impl Deref for DropArena<'_> {
+    type Target = Arena;
+
+    #[inline]
+    fn deref(&self) -> &Arena {
+        &self.arena
+    }
+}
+
+impl Drop for DropArena<'_> {
+    fn drop(&mut self) {
+        let mut next = self.drops.get();
+
+        while let Some(node) = next {
+            unsafe {
+                let node = *node.as_ptr();
+
+                (node.drop)(node.ptr);
+                next = node.next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    struct Noisy<'a> {
+        id: u32,
+        log: &'a RefCell<Vec<u32>>,
+    }
+
+    impl Drop for Noisy<'_> {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(self.id);
+        }
+    }
+
+    #[test]
+    fn runs_destructors_in_reverse_order() {
+        let log = RefCell::new(Vec::new());
+
+        {
+            let arena = DropArena::new();
+
+            for id in 0..3 {
+                arena.alloc(Noisy { id, log: &log });
+            }
+
+            assert!(log.borrow().is_empty());
+        }
+
+        assert_eq!(*log.borrow(), [2, 1, 0]);
+    }
+
+    #[test]
+    fn alloc_owned_values() {
+        let counter = Rc::new(());
+
+        {
+            let arena = DropArena::new();
+            let name = arena.alloc(String::from("doge"));
+            let numbers = arena.alloc(vec![1, 2, 3]);
+
+            arena.alloc(counter.clone());
+
+            name.push('!');
+            numbers.push(4);
+
+            assert_eq!(name, "doge!");
+            assert_eq!(numbers, &[1, 2, 3, 4]);
+            assert_eq!(Rc::strong_count(&counter), 2);
+
+            // Copy values are allocated on the same arena
+            assert_eq!(arena.alloc_str("moon"), "moon");
+        }
+
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,383 @@
+//! A handle-based arena with generation checked removal.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// A small `Copy` handle to a value stored in a `GenerationalArena`.
+///
+/// Each `Id` remembers the generation of the slot it was created for, so
+/// once the value is removed, the `Id` will no longer resolve, even if the
+/// slot has since been reused for another value.
+pub struct Id<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    /// Get the index of the slot this `Id` points to.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+
+    /// Get the generation of the slot this `Id` was created for.
+    #[inline]
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+impl<T> Clone for Id<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Id({}v{})", self.index, self.generation)
+    }
+}
+
+enum Entry<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    Free {
+        generation: u32,
+        next_free: Option<u32>,
+    },
+}
+
+/// An arena that hands out `Id<T>` handles instead of references, allowing
+/// values to be removed. This is useful for graph-like structures where nodes
+/// get deleted, which can't be expressed safely with references into the
+/// `Arena`.
+///
+/// Slots of removed values are reused, with the generation of the slot
+/// incremented, so that stale `Id`s never resolve to the new value.
+///
+/// ```rust
+/// use toolshed::generational::GenerationalArena;
+///
+/// let mut arena = GenerationalArena::new();
+///
+/// let foo = arena.insert("foo");
+/// let bar = arena.insert("bar");
+///
+/// assert_eq!(arena[foo], "foo");
+/// assert_eq!(arena.remove(foo), Some("foo"));
+/// assert_eq!(arena.get(foo), None);
+///
+/// let baz = arena.insert("baz");
+///
+/// // The slot of `foo` is reused, but `foo` doesn't resolve to `baz`
+/// assert_eq!(baz.index(), foo.index());
+/// assert_eq!(arena.get(foo), None);
+/// assert_eq!(arena.get(bar), Some(&"bar"));
+/// ```
+pub struct GenerationalArena<T> {
+    entries: Vec<Entry<T>>,
+    free: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for GenerationalArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> GenerationalArena<T> {
+    /// Create a new, empty `GenerationalArena`.
+    #[inline]
+    pub const fn new() -> Self {
+        GenerationalArena {
+            entries: Vec::new(),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Create a new, empty `GenerationalArena` with room for `capacity` values.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        GenerationalArena {
+            entries: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Put the value in the arena and return an `Id` for it.
+    ///
+    /// **Panics** if the arena would hold more than `u32::MAX` slots.
+    pub fn insert(&mut self, value: T) -> Id<T> {
+        self.len += 1;
+
+        if let Some(index) = self.free {
+            let entry = &mut self.entries[index as usize];
+
+            let generation = match *entry {
+                Entry::Free { generation, next_free } => {
+                    self.free = next_free;
+
+                    generation
+                },
+                Entry::Occupied { .. } => unreachable!("free list points to an occupied entry"),
+            };
+
+            *entry = Entry::Occupied { generation, value };
+
+            return Id { index, generation, marker: PhantomData };
+        }
+
+        assert!(self.entries.len() < u32::MAX as usize, "too many slots in GenerationalArena");
+
+        let index = self.entries.len() as u32;
+
+        self.entries.push(Entry::Occupied { generation: 0, value });
+
+        Id { index, generation: 0, marker: PhantomData }
+    }
+
+    /// Remove the value from the arena and return it, or `None` if the `Id`
+    /// doesn't point to a live value.
+    pub fn remove(&mut self, id: Id<T>) -> Option<T> {
+        match self.entries.get(id.index()) {
+            Some(&Entry::Occupied { generation, .. }) if generation == id.generation => {},
+            _ => return None,
+        }
+
+        // Once the generation is exhausted, the slot is retired rather than
+        // put back on the free list, so that the generation never wraps.
+        let (generation, next_free) = match id.generation.checked_add(1) {
+            Some(generation) => {
+                let next_free = self.free;
+
+                self.free = Some(id.index);
+
+                (generation, next_free)
+            },
+            None => (id.generation, None),
+        };
+
+        let entry = std::mem::replace(&mut self.entries[id.index()], Entry::Free { generation, next_free });
+
+        self.len -= 1;
+
+        match entry {
+            Entry::Occupied { value, .. } => Some(value),
+            Entry::Free { .. }            => None,
+        }
+    }
+
+    /// Returns `true` if the `Id` points to a live value.
+    #[inline]
+    pub fn contains(&self, id: Id<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Get a reference to the value, or `None` if the `Id` doesn't point to
+    /// a live value.
+    #[inline]
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        match self.entries.get(id.index())? {
+            Entry::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the value, or `None` if the `Id` doesn't
+    /// point to a live value.
+    #[inline]
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        match self.entries.get_mut(id.index())? {
+            Entry::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the number of live values in the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no live values in the arena.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over all live values and their `Id`s, in order
+    /// of their slots.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> {
+        self.entries.iter().enumerate().filter_map(|(index, entry)| match entry {
+            Entry::Occupied { generation, value } => Some((Id {
+                index: index as u32,
+                generation: *generation,
+                marker: PhantomData,
+            }, value)),
+            Entry::Free { .. } => None,
+        })
+    }
+}
+
+impl<T> Index<Id<T>> for GenerationalArena<T> {
+    type Output = T;
+
+    /// **Panics** if the `Id` doesn't point to a live value.
+    #[inline]
+    fn index(&self, id: Id<T>) -> &T {
+        self.get(id).expect("Id doesn't point to a live value")
+    }
+}
+
+impl<T> IndexMut<Id<T>> for GenerationalArena<T> {
+    /// **Panics** if the `Id` doesn't point to a live value.
+    #[inline]
+    fn index_mut(&mut self, id: Id<T>) -> &mut T {
+        self.get_mut(id).expect("Id doesn't point to a live value")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for GenerationalArena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_and_get() {
+        let mut arena = GenerationalArena::new();
+
+        let foo = arena.insert(10);
+        let bar = arena.insert(20);
+
+        assert_eq!(arena.get(foo), Some(&10));
+        assert_eq!(arena[bar], 20);
+        assert_eq!(arena.len(), 2);
+
+        arena[bar] += 1;
+
+        assert_eq!(arena.get(bar), Some(&21));
+    }
+
+    #[test]
+    fn remove() {
+        let mut arena = GenerationalArena::new();
+
+        let foo = arena.insert("foo");
+        let bar = arena.insert("bar");
+
+        assert_eq!(arena.remove(foo), Some("foo"));
+        assert_eq!(arena.remove(foo), None);
+        assert_eq!(arena.contains(foo), false);
+        assert_eq!(arena.contains(bar), true);
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut arena = GenerationalArena::new();
+
+        let foo = arena.insert("foo");
+
+        arena.remove(foo);
+
+        let bar = arena.insert("bar");
+
+        assert_eq!(bar.index(), foo.index());
+        assert_eq!(bar.generation(), 1);
+        assert_ne!(foo, bar);
+        assert_eq!(arena.get(foo), None);
+        assert_eq!(arena.get_mut(foo), None);
+        assert_eq!(arena.remove(foo), None);
+        assert_eq!(arena[bar], "bar");
+    }
+
+    #[test]
+    fn retires_exhausted_slots() {
+        let mut arena = GenerationalArena::new();
+
+        let foo = arena.insert("foo");
+
+        if let Entry::Occupied { ref mut generation, .. } = arena.entries[0] {
+            *generation = u32::MAX;
+        }
+
+        let foo = Id { generation: u32::MAX, ..foo };
+
+        assert_eq!(arena.remove(foo), Some("foo"));
+
+        let bar = arena.insert("bar");
+
+        assert_eq!(bar.index(), 1);
+        assert_eq!(arena.get(foo), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut arena = GenerationalArena::new();
+
+        let ids: Vec<_> = (0..5).map(|n| arena.insert(n)).collect();
+
+        arena.remove(ids[1]);
+        arena.remove(ids[3]);
+
+        let items: Vec<_> = arena.iter().collect();
+
+        assert_eq!(items, [(ids[0], &0), (ids[2], &2), (ids[4], &4)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_removed() {
+        let mut arena = GenerationalArena::new();
+
+        let foo = arena.insert(1u8);
+
+        arena.remove(foo);
+
+        let _ = arena[foo];
+    }
+
+    #[test]
+    fn debug() {
+        let mut arena = GenerationalArena::new();
+
+        arena.insert("foo");
+
+        assert_eq!(format!("{:?}", arena), r#"{Id(0v0): "foo"}"#);
+    }
+}
@@ -0,0 +1,47 @@
+//! The default hasher used by the `Map`, `Set` and their bloom variants.
+//!
+//! The hasher is chosen crate-wide with feature flags:
+//!
+//! + `hash-fx` _(default)_: the very fast `FxHasher`.
+//! + `hash-ahash`: `AHasher` from the `ahash` crate.
+//! + `hash-siphash`: `SipHasher` from the standard library. This is also
+//!   used if none of the hasher features are enabled.
+//!
+//! Since features are additive, if more than one is enabled SipHash takes
+//! precedence over aHash, which takes precedence over FxHash.
+//!
+//! **None of these options is DoS resistant.** Hashes are stored in the nodes
+//! of the maps and in snapshots, so they have to be the same in every process,
+//! and all hashers are created with fixed keys rather than random ones. SipHash
+//! and aHash only give a better distribution of hashes than FxHash, an attacker
+//! choosing the keys can still make them collide. Don't use the maps of this
+//! crate for keys coming from untrusted input.
+
+#[cfg(all(feature = "hash-fx", not(feature = "hash-ahash"), not(feature = "hash-siphash")))]
+pub use rustc_hash::FxHasher as DefaultHasher;
+
+#[cfg(all(feature = "hash-ahash", not(feature = "hash-siphash")))]
+pub use ahash::AHasher as DefaultHasher;
+
+#[cfg(any(feature = "hash-siphash", not(any(feature = "hash-fx", feature = "hash-ahash"))))]
+pub use std::collections::hash_map::DefaultHasher;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::hash::{Hash, Hasher};
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        let mut hasher = DefaultHasher::default();
+
+        value.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    #[test]
+    fn hashes_are_deterministic() {
+        assert_eq!(hash("doge"), hash("doge"));
+        assert_ne!(hash("doge"), hash("moon"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FusedIterator;
+
+use crate::arena::Arena;
+use crate::cell::CopyCell;
+use crate::set::{Set, SetIter};
+
+/// A wrapper around the `Arena` that allocates structurally identical values
+/// only once. Allocating a value equal to one allocated before returns a
+/// reference to the existing value, so values can also be compared cheaply
+/// with `std::ptr::eq`.
+///
+/// ```rust
+/// use toolshed::{Arena, HashConsArena};
+///
+/// #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// enum Type<'arena> {
+///     Int,
+///     Array(&'arena Type<'arena>),
+/// }
+///
+/// let arena = Arena::new();
+/// let types = HashConsArena::new(&arena);
+///
+/// let int = types.alloc(Type::Int);
+/// let a = types.alloc(Type::Array(int));
+/// let b = types.alloc(Type::Array(types.alloc(Type::Int)));
+///
+/// assert!(std::ptr::eq(a, b));
+/// assert_eq!(types.len(), 2);
+/// ```
+pub struct HashConsArena<'arena, T> {
+    arena: &'arena Arena,
+    values: CopyCell<Set<'arena, &'arena T>>,
+    len: CopyCell<usize>,
+}
+
+impl<'arena, T> HashConsArena<'arena, T> {
+    /// Create a new, empty `HashConsArena` allocating on the `arena`.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        HashConsArena {
+            arena,
+            values: CopyCell::new(Set::new()),
+            len: CopyCell::new(0),
+        }
+    }
+
+    /// Get the `Arena` the values are allocated on.
+    #[inline]
+    pub fn arena(&self) -> &'arena Arena {
+        self.arena
+    }
+
+    /// Get the number of unique values allocated.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns `true` if nothing has been allocated yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Get an iterator over all unique values, in the order they were
+    /// first allocated.
+    #[inline]
+    pub fn iter(&self) -> HashConsIter<'arena, T> {
+        HashConsIter {
+            inner: self.values.get().iter(),
+        }
+    }
+}
+
+impl<'arena, T> HashConsArena<'arena, T>
+where
+    T: Copy + Eq + Hash,
+{
+    /// Get a reference to a value equal to `value` on the arena, allocating
+    /// it only if no such value has been allocated before.
+    pub fn alloc(&self, value: T) -> &'arena T {
+        if let Some(existing) = self.get(&value) {
+            return existing;
+        }
+
+        let value = &*self.arena.alloc(value);
+
+        let mut values = self.values.get();
+
+        values.insert(self.arena, value);
+        self.values.set(values);
+        self.len.set(self.len.get() + 1);
+
+        value
+    }
+
+    /// Get a reference to a value equal to `value`, if it has been
+    /// allocated already.
+    #[inline]
+    pub fn get(&self, value: &T) -> Option<&'arena T> {
+        self.values.get().get_by(value).copied()
+    }
+}
+
+impl<'arena, T> fmt::Debug for HashConsArena<'arena, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HashConsArena")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// An iterator over the unique values of a `HashConsArena`.
+pub struct HashConsIter<'arena, T> {
+    inner: SetIter<'arena, &'arena T>,
+}
+
+impl<'arena, T> Iterator for HashConsIter<'arena, T> {
+    type Item = &'arena T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().copied()
+    }
+}
+
+impl<'arena, T> FusedIterator for HashConsIter<'arena, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deduplicates() {
+        let arena = Arena::new();
+        let strings = HashConsArena::new(&arena);
+
+        assert!(strings.is_empty());
+        assert_eq!(strings.get(&"foo"), None);
+
+        let foo = strings.alloc("foo");
+        let bar = strings.alloc("bar");
+
+        assert!(std::ptr::eq(strings.alloc("foo"), foo));
+        assert!(std::ptr::eq(strings.get(&"bar").unwrap(), bar));
+        assert!(arena.contains(foo));
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings.iter().copied().collect::<Vec<_>>(), ["foo", "bar"]);
+        assert_eq!(format!("{:?}", strings), "HashConsArena { len: 2 }");
+    }
+
+    #[test]
+    fn shares_subtrees() {
+        #[derive(PartialEq, Eq, Hash, Clone, Copy)]
+        struct Pair<'arena>(u32, Option<&'arena Pair<'arena>>);
+
+        let arena = Arena::new();
+        let pairs = HashConsArena::new(&arena);
+
+        let mut left = pairs.alloc(Pair(0, None));
+        let mut right = pairs.alloc(Pair(0, None));
+
+        for n in 1..100 {
+            left = pairs.alloc(Pair(n, Some(left)));
+            right = pairs.alloc(Pair(n, Some(right)));
+        }
+
+        assert!(std::ptr::eq(left, right));
+        assert_eq!(pairs.len(), 100);
+    }
+}
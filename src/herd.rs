@@ -0,0 +1,425 @@
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::sync::Mutex;
+
+use crate::alloc_in::CloneIn;
+use crate::arena::Arena;
+
+/// A pool of `Arena`s that allows multiple threads to allocate independently,
+/// with all allocations sharing the lifetime of the `Herd`.
+///
+/// Each thread takes a `Member` from the herd, which holds an `Arena` for
+/// exclusive use. When the `Member` is dropped, its `Arena` is returned to
+/// the herd rather than freed, so the references it handed out stay valid
+/// and can be stitched together into a single structure.
+///
+/// ```rust
+/// use toolshed::Herd;
+///
+/// let herd = Herd::new();
+///
+/// let words: Vec<&str> = std::thread::scope(|scope| {
+///     let handles: Vec<_> = ["foo", "bar"]
+///         .iter()
+///         .map(|word| {
+///             let herd = &herd;
+///
+///             scope.spawn(move || herd.get().alloc_str(word))
+///         })
+///         .collect();
+///
+///     handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+/// });
+///
+/// assert_eq!(words, ["foo", "bar"]);
+/// ```
+pub struct Herd {
+    arenas: Mutex<Vec<Arena>>,
+}
+
+impl Default for Herd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Herd {
+    /// Create a new, empty `Herd`. Arenas are only created once needed.
+    pub fn new() -> Self {
+        Herd {
+            arenas: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Take a `Member` from the herd, reusing an idle `Arena` if possible.
+    pub fn get(&self) -> Member<'_> {
+        let arena = self.lock().pop().unwrap_or_else(Arena::new);
+
+        Member {
+            arena: ManuallyDrop::new(arena),
+            herd: self,
+        }
+    }
+
+    /// Total number of bytes of heap memory held by all idle arenas.
+    pub fn allocated_bytes(&self) -> usize {
+        self.lock().iter().map(Arena::allocated_bytes).sum()
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Arena>> {
+        self.arenas.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// An `Arena` borrowed from a `Herd` for exclusive use by a single thread.
+/// All allocations made through a `Member` live as long as the `Herd`.
+pub struct Member<'herd> {
+    arena: ManuallyDrop<Arena>,
+    herd: &'herd Herd,
+}
+
+impl<'herd> Member<'herd> {
+    /// Put the value onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc<T: Sized + Copy>(&self, value: T) -> &'herd mut T {
+        self.arena().alloc(value)
+    }
+
+    /// Allocate a slice of `T` onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc_slice<T: Copy>(&self, val: &[T]) -> &'herd [T] {
+        self.arena().alloc_slice(val)
+    }
+
+    /// Allocate an `&str` slice onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc_str(&self, val: &str) -> &'herd str {
+        self.arena().alloc_str(val)
+    }
+
+    /// Get the `Arena` of this member. References it hands out are bound to
+    /// the `Member`, since the `Arena` can be taken by another thread once
+    /// the `Member` is dropped.
+    #[inline]
+    pub fn as_arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// The `Arena` lives until the `Herd` is dropped, and so does its memory.
+    /// It must never be exposed with the `'herd` lifetime though.
+    #[inline]
+    fn arena(&self) -> &'herd Arena {
+        unsafe { &*(&*self.arena as *const Arena) }
+    }
+}
+
+impl Drop for Member<'_> {
+    fn drop(&mut self) {
+        let arena = unsafe { ManuallyDrop::take(&mut self.arena) };
+
+        self.herd.lock().push(arena);
+    }
+}
+
+/// Arenas are boxed so that they don't move while `LocalArena::as_main`
+/// references to them are around.
+#[allow(clippy::vec_box)]
+type LocalArenas = Vec<Box<Arena>>;
+
+/// A pool of `Arena`s for building a structure from many threads, merged
+/// into a single `main` arena once the threads are done.
+///
+/// Same as with the `Herd`, each thread takes a `LocalArena` for exclusive
+/// use, which is returned to the pool when dropped and can then be picked up
+/// by any other thread. All allocations are bound to the lifetime of the
+/// `main` arena though, and `merge_into` hands the pages of all arenas in
+/// the pool over to it, so that no references need to be rewritten.
+///
+/// If the pool is dropped without merging, the memory of all its arenas is
+/// leaked, since references to it can still be around.
+///
+/// Data structures such as `List` can't be sent between threads, so they
+/// have to be passed back to the main thread as an `UnsafeList` or
+/// `UnsafeMap`.
+///
+/// ```rust
+/// use toolshed::{Arena, ThreadLocalArenas};
+/// use toolshed::list::{List, UnsafeList};
+///
+/// let main = Arena::new();
+/// let pool = ThreadLocalArenas::new();
+///
+/// let lists: Vec<UnsafeList> = std::thread::scope(|scope| {
+///     let handles: Vec<_> = (0..4u64)
+///         .map(|n| {
+///             let pool = &pool;
+///
+///             scope.spawn(move || {
+///                 let local = pool.get();
+///                 let arena = unsafe { local.as_main() };
+///
+///                 List::from_iter(arena, (0..10).map(|i| n * 10 + i)).into_unsafe()
+///             })
+///         })
+///         .collect();
+///
+///     handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+/// });
+///
+/// pool.merge_into(&main);
+///
+/// let all = List::from_iter(&main, lists.into_iter().map(|list| unsafe { list.into_list::<u64>() }));
+///
+/// assert_eq!(all.iter().map(|list| list.iter().sum::<u64>()).sum::<u64>(), (0..40).sum::<u64>());
+/// ```
+pub struct ThreadLocalArenas<'main> {
+    arenas: Mutex<LocalArenas>,
+    // `'main` must be invariant, otherwise a pool handing out references
+    // for one lifetime could be merged into an arena living shorter
+    main: PhantomData<fn(&'main ()) -> &'main ()>,
+}
+
+impl Default for ThreadLocalArenas<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'main> ThreadLocalArenas<'main> {
+    /// Create a new, empty pool. Arenas are only created once needed.
+    pub fn new() -> Self {
+        ThreadLocalArenas {
+            arenas: Mutex::new(Vec::new()),
+            main: PhantomData,
+        }
+    }
+
+    /// Take a `LocalArena` from the pool, reusing an idle `Arena` if possible.
+    pub fn get(&self) -> LocalArena<'_, 'main> {
+        let arena = self.lock().pop().unwrap_or_else(|| Box::new(Arena::new()));
+
+        LocalArena {
+            arena: ManuallyDrop::new(arena),
+            pool: self,
+        }
+    }
+
+    /// Number of idle arenas in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if there are no idle arenas in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Total number of bytes of heap memory held by all idle arenas.
+    pub fn allocated_bytes(&self) -> usize {
+        self.lock().iter().map(|arena| arena.allocated_bytes()).sum()
+    }
+
+    /// Hand the pages of all arenas over to the `main` arena, which then
+    /// owns everything allocated through the pool.
+    pub fn merge_into(self, main: &'main Arena) {
+        for arena in self.lock().drain(..) {
+            main.adopt_pages(*arena);
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, LocalArenas> {
+        self.arenas.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for ThreadLocalArenas<'_> {
+    fn drop(&mut self) {
+        for arena in self.lock().drain(..) {
+            std::mem::forget(arena);
+        }
+    }
+}
+
+/// An `Arena` borrowed from a `ThreadLocalArenas` pool for exclusive use by
+/// a single thread. All allocations made through a `LocalArena` live as long
+/// as the `main` arena the pool is merged into.
+pub struct LocalArena<'pool, 'main> {
+    arena: ManuallyDrop<Box<Arena>>,
+    pool: &'pool ThreadLocalArenas<'main>,
+}
+
+impl<'pool, 'main> LocalArena<'pool, 'main> {
+    /// Put the value onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc<T: Sized + Copy>(&self, value: T) -> &'main mut T {
+        unsafe { self.as_main() }.alloc(value)
+    }
+
+    /// Allocate a slice of `T` onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc_slice<T: Copy>(&self, val: &[T]) -> &'main [T] {
+        unsafe { self.as_main() }.alloc_slice(val)
+    }
+
+    /// Allocate an `&str` slice onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc_str(&self, val: &str) -> &'main str {
+        unsafe { self.as_main() }.alloc_str(val)
+    }
+
+    /// Clone `value` onto this arena with `CloneIn`, rewriting all references
+    /// within it, such as ones to the arena of another thread.
+    #[inline]
+    pub fn clone_in<R>(&self, value: &R) -> R::Cloned
+    where
+        R: CloneIn<'main> + ?Sized,
+    {
+        value.clone_in(unsafe { self.as_main() })
+    }
+
+    /// Get the `Arena` of this local arena. References it hands out are bound
+    /// to the `LocalArena`, use `as_main` to build data structures living as
+    /// long as the `main` arena.
+    #[inline]
+    pub fn as_arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// Get the `Arena` of this local arena with the lifetime of the `main`
+    /// arena, so that data structures can be built on it directly.
+    ///
+    /// # Safety
+    ///
+    /// The returned reference must not be used after the `LocalArena` is
+    /// dropped, since the `Arena` can be taken by another thread after that.
+    /// Everything allocated through it stays valid for `'main`.
+    #[inline]
+    pub unsafe fn as_main(&self) -> &'main Arena {
+        &*(&**self.arena as *const Arena)
+    }
+}
+
+impl Drop for LocalArena<'_, '_> {
+    fn drop(&mut self) {
+        let arena = unsafe { ManuallyDrop::take(&mut self.arena) };
+
+        self.pool.lock().push(arena);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+    use crate::arena::ARENA_BLOCK;
+    use crate::list::List;
+
+    #[test]
+    fn reuses_arenas() {
+        let herd = Herd::new();
+
+        let foo = herd.get().alloc(42u64);
+
+        assert_eq!(herd.allocated_bytes(), ARENA_BLOCK);
+
+        let bar = herd.get().alloc(100u64);
+
+        assert_eq!(herd.allocated_bytes(), ARENA_BLOCK);
+        assert_eq!((*foo, *bar), (42, 100));
+    }
+
+    #[test]
+    fn members_are_exclusive() {
+        let herd = Herd::new();
+        let a = herd.get();
+        let b = herd.get();
+
+        assert!(!std::ptr::eq(a.as_arena(), b.as_arena()));
+
+        drop(a);
+        drop(b);
+
+        assert_eq!(herd.allocated_bytes(), 2 * ARENA_BLOCK);
+    }
+
+    #[test]
+    fn allocate_from_many_threads() {
+        let herd = Herd::new();
+
+        let slices: Vec<&[u64]> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4u64)
+                .map(|n| {
+                    let herd = &herd;
+
+                    scope.spawn(move || {
+                        let member = herd.get();
+                        let values: Vec<u64> = (0..1000).map(|i| n * 1000 + i).collect();
+
+                        member.alloc_slice(&values)
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        for (n, slice) in slices.iter().enumerate() {
+            assert!(slice.iter().copied().eq((0..1000).map(|i| n as u64 * 1000 + i)));
+        }
+    }
+
+    #[test]
+    fn thread_local_arenas_merge() {
+        let main = Arena::new();
+        let pool = ThreadLocalArenas::new();
+
+        let words: Vec<&str> = thread::scope(|scope| {
+            let handles: Vec<_> = ["doge", "to", "the", "moon"]
+                .iter()
+                .map(|word| {
+                    let pool = &pool;
+
+                    scope.spawn(move || pool.get().alloc_str(word))
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let arenas = pool.len();
+        let bytes = pool.allocated_bytes();
+
+        assert!(arenas >= 1);
+        assert_eq!(bytes, arenas * ARENA_BLOCK);
+
+        pool.merge_into(&main);
+
+        assert_eq!(main.allocated_bytes(), ARENA_BLOCK + bytes);
+        assert_eq!(words, ["doge", "to", "the", "moon"]);
+    }
+
+    #[test]
+    fn thread_local_arenas_clone_in() {
+        let main = Arena::new();
+        let pool = ThreadLocalArenas::new();
+        let a = pool.get();
+        let b = pool.get();
+
+        let list = List::from_iter(unsafe { a.as_main() }, vec![1u32, 2, 3]);
+        let cloned = b.clone_in(&list);
+
+        assert!(b.as_arena().contains(cloned.first_element().unwrap()));
+        assert!(!a.as_arena().contains(cloned.first_element().unwrap()));
+
+        drop((a, b));
+
+        assert!(!pool.is_empty());
+
+        pool.merge_into(&main);
+
+        assert!(main.contains(list.first_element().unwrap()));
+        assert_eq!(cloned.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,334 @@
+//! `rkyv` zero-copy archiving of the data structures in this crate.
+//!
+//! `List`s are archived as an `ArchivedVec`, while `Map`s and `Set`s are
+//! archived as an `ArchivedIndexMap` and `ArchivedIndexSet`, which keep the
+//! insertion order and allow lookups directly in the archived bytes.
+//!
+//! Since `rkyv` can't archive plain references, collections of arena
+//! references, such as `List<'arena, &'arena str>`, are archived through the
+//! `rkyv::with::Map` and `rkyv::with::MapKV` wrappers, which makes it
+//! possible to archive whole trees of arena data with `#[derive(Archive)]`:
+//!
+//! ```rust,ignore
+//! #[derive(Archive, Serialize)]
+//! struct Module<'arena> {
+//!     #[rkyv(with = MapKV<InlineAsBox, Identity>)]
+//!     exports: Map<'arena, &'arena str, u32>,
+//!     #[rkyv(with = Map<Inline>)]
+//!     functions: List<'arena, &'arena Function<'arena>>,
+//! }
+//! ```
+
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use rkyv::collections::swiss_table::{ArchivedIndexMap, ArchivedIndexSet, IndexMapResolver, IndexSetResolver};
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::with::{ArchiveWith, SerializeWith};
+use rkyv::{Archive, Place, Serialize};
+
+use crate::list::List;
+use crate::map::Map;
+use crate::set::Set;
+
+/// Load factor of archived maps and sets, same as `rkyv` uses for `HashMap`.
+const LOAD_FACTOR: (usize, usize) = (7, 8);
+
+impl<'arena, T: Archive> Archive for List<'arena, T> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: VecResolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_len(self.len(), resolver, out);
+    }
+}
+
+impl<'arena, T, S> Serialize<S> for List<'arena, T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<VecResolver, S::Error> {
+        let items: Vec<&T> = self.iter().collect();
+
+        ArchivedVec::<T::Archived>::serialize_from_iter::<T, _, _>(items.iter().copied(), serializer)
+    }
+}
+
+impl<'arena, K: Archive, V: Archive + Copy> Archive for Map<'arena, K, V> {
+    type Archived = ArchivedIndexMap<K::Archived, V::Archived>;
+    type Resolver = IndexMapResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: IndexMapResolver, out: Place<Self::Archived>) {
+        ArchivedIndexMap::resolve_from_len(self.iter().count(), LOAD_FACTOR, resolver, out);
+    }
+}
+
+impl<'arena, K, V, S> Serialize<S> for Map<'arena, K, V>
+where
+    K: Serialize<S> + Hash + Eq,
+    V: Serialize<S> + Copy,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<IndexMapResolver, S::Error> {
+        let entries: Vec<(&K, V)> = self.iter().collect();
+
+        ArchivedIndexMap::<K::Archived, V::Archived>::serialize_from_iter::<_, _, _, K, V, _>(
+            entries.iter().map(|(key, value)| (*key, value)),
+            LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+impl<'arena, I: Archive> Archive for Set<'arena, I> {
+    type Archived = ArchivedIndexSet<I::Archived>;
+    type Resolver = IndexSetResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: IndexSetResolver, out: Place<Self::Archived>) {
+        ArchivedIndexSet::resolve_from_len(self.iter().count(), LOAD_FACTOR, resolver, out);
+    }
+}
+
+impl<'arena, I, S> Serialize<S> for Set<'arena, I>
+where
+    I: Serialize<S> + Hash + Eq,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<IndexSetResolver, S::Error> {
+        let items: Vec<&I> = self.iter().collect();
+
+        ArchivedIndexSet::<I::Archived>::serialize_from_iter::<_, I, _>(items.iter().copied(), LOAD_FACTOR, serializer)
+    }
+}
+
+/// Adapter archiving a value through the `A` wrapper. Hashing and equality
+/// are those of the value itself, so that archived keys can be looked up.
+struct With<'a, A, T>(&'a T, PhantomData<A>);
+
+impl<'a, A, T> With<'a, A, T> {
+    #[inline]
+    fn new(value: &'a T) -> Self {
+        With(value, PhantomData)
+    }
+}
+
+impl<A: ArchiveWith<T>, T> Archive for With<'_, A, T> {
+    type Archived = A::Archived;
+    type Resolver = A::Resolver;
+
+    #[inline]
+    fn resolve(&self, resolver: A::Resolver, out: Place<A::Archived>) {
+        A::resolve_with(self.0, resolver, out)
+    }
+}
+
+impl<A, T, S> Serialize<S> for With<'_, A, T>
+where
+    A: SerializeWith<T, S>,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize(&self, serializer: &mut S) -> Result<A::Resolver, S::Error> {
+        A::serialize_with(self.0, serializer)
+    }
+}
+
+impl<A, T: Hash> Hash for With<'_, A, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<A, T: PartialEq> PartialEq for With<'_, A, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<A, T: Eq> Eq for With<'_, A, T> {}
+
+impl<'arena, A, T> ArchiveWith<List<'arena, T>> for rkyv::with::Map<A>
+where
+    A: ArchiveWith<T>,
+{
+    type Archived = ArchivedVec<A::Archived>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve_with(list: &List<'arena, T>, resolver: VecResolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_len(list.len(), resolver, out);
+    }
+}
+
+impl<'arena, A, T, S> SerializeWith<List<'arena, T>, S> for rkyv::with::Map<A>
+where
+    A: ArchiveWith<T> + SerializeWith<T, S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(list: &List<'arena, T>, serializer: &mut S) -> Result<VecResolver, S::Error> {
+        let items: Vec<With<A, T>> = list.iter().map(With::new).collect();
+
+        ArchivedVec::serialize_from_slice(&items, serializer)
+    }
+}
+
+impl<'arena, A, I> ArchiveWith<Set<'arena, I>> for rkyv::with::Map<A>
+where
+    A: ArchiveWith<I>,
+{
+    type Archived = ArchivedIndexSet<A::Archived>;
+    type Resolver = IndexSetResolver;
+
+    #[inline]
+    fn resolve_with(set: &Set<'arena, I>, resolver: IndexSetResolver, out: Place<Self::Archived>) {
+        ArchivedIndexSet::resolve_from_len(set.iter().count(), LOAD_FACTOR, resolver, out);
+    }
+}
+
+impl<'arena, A, I, S> SerializeWith<Set<'arena, I>, S> for rkyv::with::Map<A>
+where
+    A: ArchiveWith<I> + SerializeWith<I, S>,
+    I: Hash + Eq,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(set: &Set<'arena, I>, serializer: &mut S) -> Result<IndexSetResolver, S::Error> {
+        let items: Vec<With<A, I>> = set.iter().map(With::new).collect();
+
+        ArchivedIndexSet::<A::Archived>::serialize_from_iter::<_, With<A, I>, _>(items.iter(), LOAD_FACTOR, serializer)
+    }
+}
+
+impl<'arena, A, B, K, V> ArchiveWith<Map<'arena, K, V>> for rkyv::with::MapKV<A, B>
+where
+    A: ArchiveWith<K>,
+    B: ArchiveWith<V>,
+    V: Copy,
+{
+    type Archived = ArchivedIndexMap<A::Archived, B::Archived>;
+    type Resolver = IndexMapResolver;
+
+    #[inline]
+    fn resolve_with(map: &Map<'arena, K, V>, resolver: IndexMapResolver, out: Place<Self::Archived>) {
+        ArchivedIndexMap::resolve_from_len(map.iter().count(), LOAD_FACTOR, resolver, out);
+    }
+}
+
+impl<'arena, A, B, K, V, S> SerializeWith<Map<'arena, K, V>, S> for rkyv::with::MapKV<A, B>
+where
+    A: ArchiveWith<K> + SerializeWith<K, S>,
+    B: ArchiveWith<V> + SerializeWith<V, S>,
+    K: Hash + Eq,
+    V: Copy,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(map: &Map<'arena, K, V>, serializer: &mut S) -> Result<IndexMapResolver, S::Error> {
+        let entries: Vec<(&K, V)> = map.iter().collect();
+
+        ArchivedIndexMap::<A::Archived, B::Archived>::serialize_from_iter::<_, _, _, With<A, K>, With<B, V>, _>(
+            entries.iter().map(|(key, value)| (With::new(*key), With::new(value))),
+            LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rkyv::rancor::Error;
+    use rkyv::with::{Identity, Inline, InlineAsBox, MapKV};
+    use crate::Arena;
+
+    #[test]
+    fn list_can_be_archived() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u32, 2, 3]);
+
+        let bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        let archived = rkyv::access::<ArchivedVec<rkyv::Archived<u32>>, Error>(&bytes).unwrap();
+
+        assert_eq!(archived.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn map_and_set_can_be_archived() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+        let mut set = Set::new();
+
+        map.insert(&arena, 10u64, 1u8);
+        map.insert(&arena, 20, 2);
+        set.insert(&arena, 30u64);
+
+        let map_bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+        let set_bytes = rkyv::to_bytes::<Error>(&set).unwrap();
+
+        let map = rkyv::access::<ArchivedIndexMap<rkyv::Archived<u64>, u8>, Error>(&map_bytes).unwrap();
+        let set = rkyv::access::<ArchivedIndexSet<rkyv::Archived<u64>>, Error>(&set_bytes).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&rkyv::Archived::<u64>::from_native(20)), Some(&2));
+        assert_eq!(map.get_index(0).map(|(_, value)| *value), Some(1));
+        assert!(set.contains(&rkyv::Archived::<u64>::from_native(30)));
+    }
+
+    #[derive(Clone, Copy, Archive, Serialize)]
+    #[rkyv(crate = rkyv)]
+    struct Function<'arena> {
+        #[rkyv(with = InlineAsBox)]
+        name: &'arena str,
+        arity: u8,
+    }
+
+    #[derive(Clone, Copy, Archive, Serialize)]
+    #[rkyv(crate = rkyv)]
+    struct Module<'arena> {
+        #[rkyv(with = MapKV<InlineAsBox, Identity>)]
+        exports: Map<'arena, &'arena str, u32>,
+        #[rkyv(with = rkyv::with::Map<InlineAsBox>)]
+        imports: List<'arena, &'arena str>,
+        #[rkyv(with = rkyv::with::Map<InlineAsBox>)]
+        keywords: Set<'arena, &'arena str>,
+        #[rkyv(with = rkyv::with::Map<Inline>)]
+        functions: List<'arena, &'arena Function<'arena>>,
+    }
+
+    #[test]
+    fn tree_can_be_archived() {
+        let arena = Arena::new();
+        let mut module = Module {
+            exports: Map::new(),
+            imports: List::from_iter(&arena, vec!["std", "core"]),
+            keywords: Set::from_iter(&arena, vec!["let", "fn"]),
+            functions: List::from_iter(&arena, vec![
+                &*arena.alloc(Function { name: "main", arity: 0 }),
+                &*arena.alloc(Function { name: "add", arity: 2 }),
+            ]),
+        };
+
+        module.exports.insert(&arena, "main", 0);
+        module.exports.insert(&arena, "add", 1);
+
+        let bytes = rkyv::to_bytes::<Error>(&module).unwrap();
+        let archived = rkyv::access::<ArchivedModule, Error>(&bytes).unwrap();
+
+        assert_eq!(archived.exports.get("add").map(|index| index.to_native()), Some(1));
+        assert_eq!(archived.exports.get("sub"), None);
+        assert_eq!(&*archived.imports[1], "core");
+        assert!(archived.keywords.contains("fn"));
+        assert_eq!(&*archived.functions[1].name, "add");
+        assert_eq!(archived.functions[1].arity, 2);
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt::{self, Debug};
-use crate::list::{List, GrowableList, ListBuilder};
-use crate::map::{Map, BloomMap};
-use crate::set::{Set, BloomSet};
+use std::hash::Hash;
+use crate::list::{List, GrowableList, ListBuilder, DoublyList, UnrolledList};
+use crate::map::{Map, BloomMap, BigBloomMap, CompactMap, PolicyMap, Counter, MapCursor};
+use crate::set::{Set, BloomSet, PolicySet};
+use crate::weak::WeakRef;
 
 impl<'arena, T> Debug for List<'arena, T>
 where
@@ -33,6 +35,26 @@ where
     }
 }
 
+impl<'arena, T> Debug for DoublyList<'arena, T>
+where
+    T: Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'arena, T> Debug for UnrolledList<'arena, T>
+where
+    T: Debug + Copy,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 impl<'arena, K, V> Debug for Map<'arena, K, V>
 where
     K: Debug,
@@ -44,6 +66,30 @@ where
     }
 }
 
+impl<'m, 'arena, K, V> Debug for MapCursor<'m, 'arena, K, V>
+where
+    K: Debug + Eq + Hash + Copy,
+    V: Debug + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapCursor")
+            .field("key", &self.key())
+            .field("value", &self.value())
+            .field("depth", &self.depth())
+            .finish()
+    }
+}
+
+impl<'arena, K> Debug for Counter<'arena, K>
+where
+    K: Debug + Copy,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
 impl<'arena, K, V> Debug for BloomMap<'arena, K, V>
 where
     K: Debug,
@@ -55,6 +101,39 @@ where
     }
 }
 
+impl<'arena, K, V> Debug for BigBloomMap<'arena, K, V>
+where
+    K: Debug,
+    V: Debug + Copy,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'arena, K, V> Debug for CompactMap<'arena, K, V>
+where
+    K: Debug,
+    V: Debug + Copy,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'arena, K, V, P> Debug for PolicyMap<'arena, K, V, P>
+where
+    K: Debug,
+    V: Debug + Copy,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
 impl<'arena, I> Debug for Set<'arena, I>
 where
     I: Debug,
@@ -75,6 +154,29 @@ where
     }
 }
 
+impl<'arena, I, P> Debug for PolicySet<'arena, I, P>
+where
+    I: Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'arena, T> Debug for WeakRef<'arena, T>
+where
+    T: Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("WeakRef").field(value).finish(),
+            None        => f.write_str("WeakRef(<invalidated>)"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -93,7 +195,7 @@ mod test {
     #[test]
     fn map_debug() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -107,7 +209,7 @@ mod test {
     #[test]
     fn bloom_map_debug() {
         let arena = Arena::new();
-        let map = BloomMap::new();
+        let mut map = BloomMap::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -121,7 +223,7 @@ mod test {
     #[test]
     fn set_debug() {
         let arena = Arena::new();
-        let set = Set::new();
+        let mut set = Set::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -135,7 +237,7 @@ mod test {
     #[test]
     fn bloom_set_debug() {
         let arena = Arena::new();
-        let set = BloomSet::new();
+        let mut set = BloomSet::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -145,4 +247,16 @@ mod test {
 
         assert_eq!(debug, r#"{"foo", "bar", "doge"}"#);
     }
+
+    #[test]
+    fn weak_ref_debug() {
+        let arena = Arena::new();
+        let weak = WeakRef::new(&arena, "doge");
+
+        assert_eq!(format!("{:?}", weak), r#"WeakRef("doge")"#);
+
+        weak.invalidate();
+
+        assert_eq!(format!("{:?}", weak), "WeakRef(<invalidated>)");
+    }
 }
@@ -1,5 +1,5 @@
-use crate::list::List;
-use crate::map::{Map, BloomMap};
+use crate::list::{List, DoublyList, UnrolledList};
+use crate::map::{Map, BloomMap, BigBloomMap};
 use crate::set::{Set, BloomSet};
 
 impl<'a, 'b, A, B> PartialEq<List<'b, B>> for List<'a, A>
@@ -12,6 +12,27 @@ where
     }
 }
 
+impl<'a, 'b, A, B> PartialEq<DoublyList<'b, B>> for DoublyList<'a, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &DoublyList<'b, B>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, 'b, A, B> PartialEq<UnrolledList<'b, B>> for UnrolledList<'a, A>
+where
+    A: PartialEq<B> + Copy,
+    B: Copy,
+{
+    #[inline]
+    fn eq(&self, other: &UnrolledList<'b, B>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
 impl<'a, 'b, KA, VA, KB, VB> PartialEq<Map<'b, KB, VB>> for Map<'a, KA, VA>
 where
     (&'a KA, VA): PartialEq<(&'b KB, VB)>,
@@ -36,6 +57,18 @@ where
     }
 }
 
+impl<'a, 'b, KA, VA, KB, VB> PartialEq<BigBloomMap<'b, KB, VB>> for BigBloomMap<'a, KA, VA>
+where
+    (&'a KA, VA): PartialEq<(&'b KB, VB)>,
+    VA: Copy,
+    VB: Copy,
+{
+    #[inline]
+    fn eq(&self, other: &BigBloomMap<'b, KB, VB>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
 impl<'a, 'b, A, B> PartialEq<Set<'b, B>> for Set<'a, A>
 where
     A: PartialEq<B>,
@@ -55,3 +88,50 @@ where
         self.iter().eq(other.iter())
     }
 }
+
+macro_rules! impl_partial_eq_maps {
+    ($( $a:ident == $b:ident ),*) => {
+        $(
+            impl<'a, 'b, KA, VA, KB, VB> PartialEq<$b<'b, KB, VB>> for $a<'a, KA, VA>
+            where
+                (&'a KA, VA): PartialEq<(&'b KB, VB)>,
+                VA: Copy,
+                VB: Copy,
+            {
+                #[inline]
+                fn eq(&self, other: &$b<'b, KB, VB>) -> bool {
+                    self.iter().eq(other.iter())
+                }
+            }
+        )*
+    }
+}
+
+impl_partial_eq_maps!(
+    Map == BloomMap,
+    BloomMap == Map,
+    Map == BigBloomMap,
+    BigBloomMap == Map,
+    BloomMap == BigBloomMap,
+    BigBloomMap == BloomMap
+);
+
+impl<'a, 'b, A, B> PartialEq<BloomSet<'b, B>> for Set<'a, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &BloomSet<'b, B>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, 'b, A, B> PartialEq<Set<'b, B>> for BloomSet<'a, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &Set<'b, B>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
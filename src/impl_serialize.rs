@@ -1,7 +1,8 @@
 use serde::ser::{Serialize, Serializer};
 use crate::list::List;
-use crate::map::{Map, BloomMap};
+use crate::map::{Map, BloomMap, BigBloomMap};
 use crate::set::{Set, BloomSet};
+use crate::value::Value;
 
 impl<'arena, T> Serialize for List<'arena, T>
 where
@@ -44,6 +45,20 @@ where
     }
 }
 
+impl<'arena, K, V> Serialize for BigBloomMap<'arena, K, V>
+where
+    K: Serialize,
+    V: Serialize + Copy,
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
 impl<'arena, I> Serialize for Set<'arena, I>
 where
     I: Serialize,
@@ -70,6 +85,24 @@ where
     }
 }
 
+impl<'arena> Serialize for Value<'arena> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        match *self {
+            Value::Null         => serializer.serialize_unit(),
+            Value::Bool(value)  => serializer.serialize_bool(value),
+            Value::Int(value)   => serializer.serialize_i64(value),
+            Value::Float(value) => serializer.serialize_f64(value),
+            Value::Str(value)   => serializer.serialize_str(value),
+            Value::List(list)   => list.serialize(serializer),
+            Value::Map(map)     => map.serialize(serializer),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -88,7 +121,7 @@ mod test {
     #[test]
     fn map_can_be_serialized() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -102,7 +135,7 @@ mod test {
     #[test]
     fn bloom_map_can_be_serialized() {
         let arena = Arena::new();
-        let map = BloomMap::new();
+        let mut map = BloomMap::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -116,7 +149,7 @@ mod test {
     #[test]
     fn set_can_be_serialized() {
         let arena = Arena::new();
-        let set = Set::new();
+        let mut set = Set::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -130,7 +163,7 @@ mod test {
     #[test]
     fn bloom_set_can_be_serialized() {
         let arena = Arena::new();
-        let set = BloomSet::new();
+        let mut set = BloomSet::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -140,4 +173,22 @@ mod test {
 
         assert_eq!(json, r#"["foo","bar","doge"]"#);
     }
+
+    #[test]
+    fn value_can_be_serialized() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, "name", Value::Str("doge"));
+        map.insert(&arena, "age", Value::Int(12));
+        map.insert(&arena, "weight", Value::Float(7.5));
+        map.insert(&arena, "tags", Value::List(List::from_iter(&arena, vec![
+            Value::Bool(true),
+            Value::Null,
+        ])));
+
+        let json = serde_json::to_string(&Value::Map(map)).unwrap();
+
+        assert_eq!(json, r#"{"name":"doge","age":12,"weight":7.5,"tags":[true,null]}"#);
+    }
 }
@@ -0,0 +1,169 @@
+//! A string interner built on the `Arena`.
+
+use std::fmt;
+
+use crate::arena::Arena;
+use crate::map::Map;
+use crate::vec::ArenaVec;
+
+/// A small `Copy` handle to a string stored in a `StringInterner`.
+///
+/// Two `Symbol`s produced by the same interner are equal if, and only if,
+/// the strings they were created from are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Get the index of this `Symbol`. Symbols are numbered in the order
+    /// in which new strings were interned, starting at `0`.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Deduplicates strings on the `Arena`, handing out `Symbol`s for them.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::interner::StringInterner;
+///
+/// let arena = Arena::new();
+/// let mut interner = StringInterner::new(&arena);
+///
+/// let foo = interner.intern("foo");
+/// let bar = interner.intern("bar");
+///
+/// assert_eq!(interner.intern("foo"), foo);
+/// assert_ne!(foo, bar);
+/// assert_eq!(interner.resolve(bar), "bar");
+/// ```
+pub struct StringInterner<'arena> {
+    arena: &'arena Arena,
+    map: Map<'arena, &'arena str, Symbol>,
+    strings: ArenaVec<'arena, &'arena str>,
+}
+
+impl<'arena> StringInterner<'arena> {
+    /// Create a new, empty `StringInterner`.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        StringInterner {
+            arena,
+            map: Map::new(),
+            strings: ArenaVec::new(arena),
+        }
+    }
+
+    /// Get the `Symbol` for the string, copying it to the `Arena` if it
+    /// hasn't been interned before.
+    pub fn intern(&mut self, value: &str) -> Symbol {
+        if let Some(symbol) = self.get(value) {
+            return symbol;
+        }
+
+        assert!(self.strings.len() < u32::MAX as usize, "too many interned strings");
+
+        let symbol = Symbol(self.strings.len() as u32);
+        let value = self.arena.alloc_str(value);
+
+        self.map.insert(self.arena, value, symbol);
+        self.strings.push(value);
+
+        symbol
+    }
+
+    /// Get the `Symbol` for the string, if it has been interned already.
+    #[inline]
+    pub fn get(&self, value: &str) -> Option<Symbol> {
+        self.map.get_by(value)
+    }
+
+    /// Get the string the `Symbol` was created from.
+    ///
+    /// **Panics** if the `Symbol` wasn't produced by this interner.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &'arena str {
+        self.strings[symbol.index()]
+    }
+
+    /// Get the string the `Symbol` was created from, or `None` if the `Symbol`
+    /// is out of range for this interner.
+    #[inline]
+    pub fn try_resolve(&self, symbol: Symbol) -> Option<&'arena str> {
+        self.strings.get(symbol.index()).cloned()
+    }
+
+    /// Get the number of unique strings in the interner.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if nothing has been interned yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Get an iterator over all `Symbol`s and their strings, in the order
+    /// they were interned.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Symbol, &'arena str)> + 'a {
+        self.strings.iter().enumerate().map(|(index, value)| (Symbol(index as u32), *value))
+    }
+}
+
+impl<'arena> fmt::Debug for StringInterner<'arena> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let arena = Arena::new();
+        let mut interner = StringInterner::new(&arena);
+
+        let foo = interner.intern("foo");
+        let bar = interner.intern("bar");
+
+        assert_eq!(foo.index(), 0);
+        assert_eq!(bar.index(), 1);
+        assert_eq!(interner.intern("foo"), foo);
+        assert_eq!(interner.intern(&String::from("bar")), bar);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn get_and_resolve() {
+        let arena = Arena::new();
+        let mut interner = StringInterner::new(&arena);
+
+        let doge = interner.intern("doge");
+
+        assert_eq!(interner.get("doge"), Some(doge));
+        assert_eq!(interner.get("moon"), None);
+        assert_eq!(interner.resolve(doge), "doge");
+        assert_eq!(interner.try_resolve(Symbol(1)), None);
+    }
+
+    #[test]
+    fn iter() {
+        let arena = Arena::new();
+        let mut interner = StringInterner::new(&arena);
+
+        for word in "to be or not to be".split(' ') {
+            interner.intern(word);
+        }
+
+        let words: Vec<_> = interner.iter().map(|(_, word)| word).collect();
+
+        assert_eq!(words, ["to", "be", "or", "not"]);
+    }
+}
@@ -0,0 +1,568 @@
+//! A minimal JSON parser and writer for the `Value` document model.
+//!
+//! The parser allocates all strings, lists and maps directly on the `Arena`,
+//! without any intermediate representation.
+//!
+//! ```rust
+//! use toolshed::Arena;
+//! use toolshed::json;
+//! use toolshed::value::Value;
+//!
+//! let arena = Arena::new();
+//! let doc = json::parse(&arena, r#"{ "name": "doge", "tags": ["to", "the", "moon"] }"#).unwrap();
+//!
+//! assert_eq!(doc.get("name"), Some(Value::Str("doge")));
+//! assert_eq!(json::to_string(&doc), r#"{"name":"doge","tags":["to","the","moon"]}"#);
+//! ```
+
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::arena::Arena;
+use crate::list::GrowableList;
+use crate::map::Map;
+use crate::string::ArenaString;
+use crate::value::Value;
+
+/// Maximum depth of nested lists and maps the parser accepts.
+pub const MAX_DEPTH: usize = 256;
+
+/// Error returned when parsing JSON fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    message: &'static str,
+    position: usize,
+}
+
+impl ParseError {
+    /// Byte offset in the source at which the error occurred.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse the JSON `source` into a `Value` on the `arena`.
+///
+/// Integers that fit in an `i64` are parsed as `Value::Int`, all other
+/// numbers as `Value::Float`. If a map contains the same key more than
+/// once, the last value is kept.
+pub fn parse<'arena>(arena: &'arena Arena, source: &str) -> Result<Value<'arena>, ParseError> {
+    let mut parser = Parser {
+        arena,
+        source,
+        bytes: source.as_bytes(),
+        index: 0,
+        depth: 0,
+    };
+
+    let value = parser.value()?;
+
+    parser.skip_whitespace();
+
+    if parser.index != parser.bytes.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+
+    Ok(value)
+}
+
+struct Parser<'arena, 'src> {
+    arena: &'arena Arena,
+    source: &'src str,
+    bytes: &'src [u8],
+    index: usize,
+    depth: usize,
+}
+
+impl<'arena, 'src> Parser<'arena, 'src> {
+    #[inline]
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            message,
+            position: self.index,
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).cloned()
+    }
+
+    #[inline]
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.index += 1;
+        }
+    }
+
+    #[inline]
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+
+        self.index += 1;
+
+        Ok(())
+    }
+
+    fn literal(&mut self, literal: &'static str, value: Value<'arena>) -> Result<Value<'arena>, ParseError> {
+        if !self.bytes[self.index..].starts_with(literal.as_bytes()) {
+            return Err(self.error("invalid literal"));
+        }
+
+        self.index += literal.len();
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value<'arena>, ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => Ok(Value::Str(self.string()?)),
+            Some(b'[') => self.nested(Self::list),
+            Some(b'{') => self.nested(Self::map),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    #[inline]
+    fn nested<F>(&mut self, f: F) -> Result<Value<'arena>, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<Value<'arena>, ParseError>,
+    {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+
+        self.depth += 1;
+
+        let value = f(self);
+
+        self.depth -= 1;
+
+        value
+    }
+
+    fn list(&mut self) -> Result<Value<'arena>, ParseError> {
+        let mut list = GrowableList::new();
+
+        self.index += 1;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b']') {
+            self.index += 1;
+
+            return Ok(Value::List(list.as_list()));
+        }
+
+        loop {
+            list.push(self.arena, self.value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.index += 1,
+                Some(b']') => {
+                    self.index += 1;
+
+                    return Ok(Value::List(list.as_list()));
+                },
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Value<'arena>, ParseError> {
+        let mut map = Map::new();
+
+        self.index += 1;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b'}') {
+            self.index += 1;
+
+            return Ok(Value::Map(map));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"));
+            }
+
+            let key = self.string()?;
+
+            self.expect(b':', "expected `:`")?;
+
+            map.insert(self.arena, key, self.value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.index += 1,
+                Some(b'}') => {
+                    self.index += 1;
+
+                    return Ok(Value::Map(map));
+                },
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value<'arena>, ParseError> {
+        let start = self.index;
+        let mut integer = true;
+
+        if self.peek() == Some(b'-') {
+            self.index += 1;
+        }
+
+        match self.peek() {
+            Some(b'0') => self.index += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(self.error("invalid number")),
+        }
+
+        if self.peek() == Some(b'.') {
+            integer = false;
+            self.index += 1;
+
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+
+            self.digits();
+        }
+
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            integer = false;
+            self.index += 1;
+
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.index += 1;
+            }
+
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+
+            self.digits();
+        }
+
+        let number = &self.source[start..self.index];
+
+        if integer {
+            if let Ok(value) = number.parse() {
+                return Ok(Value::Int(value));
+            }
+        }
+
+        number.parse().map(Value::Float).map_err(|_| self.error("invalid number"))
+    }
+
+    #[inline]
+    fn digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.index += 1;
+        }
+    }
+
+    fn string(&mut self) -> Result<&'arena str, ParseError> {
+        self.index += 1;
+
+        let start = self.index;
+
+        // Fast path for strings without escapes, which can be copied as is
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    let value = self.arena.alloc_str(&self.source[start..self.index]);
+
+                    self.index += 1;
+
+                    return Ok(value);
+                },
+                Some(b'\\') => break,
+                Some(0..=0x1F) => return Err(self.error("control character in string")),
+                Some(_) => self.index += 1,
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        let mut string = ArenaString::with_capacity(self.arena, self.index - start + 16);
+
+        string.push_str(&self.source[start..self.index]);
+
+        loop {
+            let chunk = self.index;
+
+            while let Some(byte) = self.peek() {
+                match byte {
+                    b'"' | b'\\' | 0..=0x1F => break,
+                    _ => self.index += 1,
+                }
+            }
+
+            string.push_str(&self.source[chunk..self.index]);
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.index += 1;
+
+                    return Ok(string.into_str());
+                },
+                Some(b'\\') => {
+                    self.index += 1;
+                    string.push(self.escape()?);
+                },
+                Some(_) => return Err(self.error("control character in string")),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let byte = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+
+        self.index += 1;
+
+        Ok(match byte {
+            b'"'  => '"',
+            b'\\' => '\\',
+            b'/'  => '/',
+            b'b'  => '\u{8}',
+            b'f'  => '\u{c}',
+            b'n'  => '\n',
+            b'r'  => '\r',
+            b't'  => '\t',
+            b'u'  => {
+                let high = self.hex()?;
+
+                if !(0xD800..0xDC00).contains(&high) {
+                    return std::char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+                }
+
+                if !self.bytes[self.index..].starts_with(b"\\u") {
+                    return Err(self.error("unpaired surrogate"));
+                }
+
+                self.index += 2;
+
+                let low = self.hex()?;
+
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("unpaired surrogate"));
+                }
+
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+
+                return std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"));
+            },
+            _ => {
+                self.index -= 1;
+
+                return Err(self.error("invalid escape"));
+            }
+        })
+    }
+
+    fn hex(&mut self) -> Result<u32, ParseError> {
+        let digits = self.source.get(self.index..self.index + 4).ok_or_else(|| self.error("invalid unicode escape"))?;
+
+        if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(self.error("invalid unicode escape"));
+        }
+
+        self.index += 4;
+
+        Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+    }
+}
+
+/// Write the `value` as compact JSON.
+///
+/// Floats that are not finite are written as `null`.
+pub fn write<W: Write>(out: &mut W, value: &Value) -> fmt::Result {
+    match *value {
+        Value::Null        => out.write_str("null"),
+        Value::Bool(value) => out.write_str(if value { "true" } else { "false" }),
+        Value::Int(value)  => write!(out, "{}", value),
+        Value::Float(value) if value.is_finite() => write!(out, "{:?}", value),
+        Value::Float(_)    => out.write_str("null"),
+        Value::Str(value)  => write_str(out, value),
+        Value::List(list)  => {
+            out.write_char('[')?;
+
+            for (index, item) in list.iter().enumerate() {
+                if index != 0 {
+                    out.write_char(',')?;
+                }
+
+                write(out, item)?;
+            }
+
+            out.write_char(']')
+        },
+        Value::Map(map) => {
+            out.write_char('{')?;
+
+            for (index, (key, item)) in map.iter().enumerate() {
+                if index != 0 {
+                    out.write_char(',')?;
+                }
+
+                write_str(out, key)?;
+                out.write_char(':')?;
+                write(out, &item)?;
+            }
+
+            out.write_char('}')
+        },
+    }
+}
+
+/// Write the `value` as compact JSON to a new `String`.
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+
+    write(&mut out, value).expect("writing to a String can't fail");
+
+    out
+}
+
+fn write_str<W: Write>(out: &mut W, value: &str) -> fmt::Result {
+    out.write_char('"')?;
+
+    let mut start = 0;
+
+    for (index, byte) in value.bytes().enumerate() {
+        let escape = match byte {
+            b'"'  => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0..=0x1F => "",
+            _ => continue,
+        };
+
+        out.write_str(&value[start..index])?;
+
+        if escape.is_empty() {
+            write!(out, "\\u{:04x}", byte)?;
+        } else {
+            out.write_str(escape)?;
+        }
+
+        start = index + 1;
+    }
+
+    out.write_str(&value[start..])?;
+    out.write_char('"')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::list::List;
+
+    #[test]
+    fn parse_scalars() {
+        let arena = Arena::new();
+
+        assert_eq!(parse(&arena, "null"), Ok(Value::Null));
+        assert_eq!(parse(&arena, " true "), Ok(Value::Bool(true)));
+        assert_eq!(parse(&arena, "false"), Ok(Value::Bool(false)));
+        assert_eq!(parse(&arena, "42"), Ok(Value::Int(42)));
+        assert_eq!(parse(&arena, "-7"), Ok(Value::Int(-7)));
+        assert_eq!(parse(&arena, "0.5"), Ok(Value::Float(0.5)));
+        assert_eq!(parse(&arena, "1e3"), Ok(Value::Float(1000.0)));
+        assert_eq!(parse(&arena, "-2.5E-1"), Ok(Value::Float(-0.25)));
+        assert_eq!(parse(&arena, "18446744073709551616"), Ok(Value::Float(18446744073709551616.0)));
+        assert_eq!(parse(&arena, r#""doge""#), Ok(Value::Str("doge")));
+    }
+
+    #[test]
+    fn parse_strings() {
+        let arena = Arena::new();
+
+        assert_eq!(parse(&arena, r#""a\"b\\c\/d\ne""#), Ok(Value::Str("a\"b\\c/d\ne")));
+        assert_eq!(parse(&arena, r#""é中""#), Ok(Value::Str("é中")));
+        assert_eq!(parse(&arena, r#""😀!""#), Ok(Value::Str("😀!")));
+        assert_eq!(parse(&arena, r#""zażółć""#), Ok(Value::Str("zażółć")));
+    }
+
+    #[test]
+    fn parse_nested() {
+        let arena = Arena::new();
+        let doc = parse(&arena, r#"{"a": [1, 2, {"b": null}], "c": {}, "d": [], "a": true}"#).unwrap();
+
+        assert_eq!(doc.get("a"), Some(Value::Bool(true)));
+        assert_eq!(doc.get("c").and_then(|c| c.as_map()).map(|c| c.is_empty()), Some(true));
+        assert_eq!(doc.get("d"), Some(Value::List(List::empty())));
+
+        let doc = parse(&arena, r#"[1, 2, {"b": null}]"#).unwrap();
+
+        assert_eq!(doc.index(1), Some(Value::Int(2)));
+        assert_eq!(doc.index(2).and_then(|map| map.get("b")), Some(Value::Null));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let arena = Arena::new();
+
+        let error = |source| parse(&arena, source).unwrap_err().position();
+
+        assert_eq!(error(""), 0);
+        assert_eq!(error("nul"), 0);
+        assert_eq!(error("[1, 2"), 5);
+        assert_eq!(error("[1 2]"), 3);
+        assert_eq!(error(r#"{"a" 1}"#), 5);
+        assert_eq!(error("{1: 2}"), 1);
+        assert_eq!(error(r#""abc"#), 4);
+        assert_eq!(error(r#""\x""#), 2);
+        assert_eq!(error(r#""\ud83d""#), 7);
+        assert_eq!(error("01"), 1);
+        assert_eq!(error("1."), 2);
+        assert_eq!(error("-"), 1);
+        assert_eq!(error("[] []"), 3);
+        assert_eq!(error(&"[".repeat(MAX_DEPTH + 1)), MAX_DEPTH);
+    }
+
+    #[test]
+    fn write_values() {
+        let arena = Arena::new();
+        let source = r#"{"str":"a\"b\\c\nd\u0001","int":-1,"float":0.5,"whole":2.0,"list":[true,false,null],"map":{}}"#;
+        let doc = parse(&arena, source).unwrap();
+
+        assert_eq!(to_string(&doc), source);
+        assert_eq!(to_string(&Value::Float(f64::NAN)), "null");
+    }
+
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let source = r#"[{"name":"doge","tags":["to","the","moon"],"age":12,"weight":7.25}]"#;
+        let doc = parse(&arena, source).unwrap();
+
+        assert_eq!(parse(&arena, &to_string(&doc)), Ok(doc));
+    }
+}
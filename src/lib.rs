@@ -12,22 +12,118 @@
 //! + Paginated `Arena`: internally preallocates 64KiB _pages_ on the heap and
 //!     allows `Copy` types to be put on that heap.
 //!
+//! + `SyncArena`: a thread-safe variant of the `Arena` using an atomic bump
+//!     pointer, allowing multiple threads to allocate on a shared arena.
+//!
+//! + `DropArena`: a variant of the `Arena` that can also hold values that aren't
+//!     `Copy`, running their destructors when the arena is dropped.
+//!
+//! + `TypedArena`: an arena for values of a single type, stored in contiguous
+//!     chunks that can be iterated over in order of allocation.
+//!
+//! + `HashConsArena`: a wrapper around the `Arena` that allocates structurally
+//!     identical values only once, sharing them by reference.
+//!
+//! + `CompactArena`: an arena in a single contiguous reservation, handing out
+//!     32-bit `ArenaRef` offsets that are half the size of regular references.
+//!
+//! + `Herd`: a pool of `Arena`s handed out to threads for exclusive use, with all
+//!     allocations sharing the lifetime of the `Herd`.
+//!
+//! + `ThreadLocalArenas`: a pool of `Arena`s for building a structure from many
+//!     threads, merged into a single main `Arena` once the threads are done.
+//!
 //! + `CopyCell`: virtually identical to `std::cell::Cell` but requires that
-//!     internal types implement `Copy`, and implements `Copy` itself.
+//!     internal types implement `Copy`. The cell is `Clone`, but not `Copy`.
+//!
+//! + `AtomicCell`: a thread-safe counterpart of `CopyCell` for 8 to 64 bit wide
+//!     `Copy` types, for structures shared across threads.
+//!
+//! + `WeakRef`: a handle to a value on the `Arena` with a liveness flag, allowing
+//!     values to be logically deleted while their memory persists.
 //!
 //! + `List`, `Map` and `Set`: your basic data structures that allocate on the
-//!     `Arena` and use internal mutability via `CopyCell`. Never worry about
+//!     `Arena` and link their nodes with `CopyCell`s. Never worry about
 //!     sharing pointers again!
 //!
+//! + `DoublyList`: a doubly-linked variant of `List` that can be walked backwards
+//!     and modified at both ends.
+//!
+//! + `UnrolledList`: a variant of `List` storing several elements in every node,
+//!     for faster iteration over lists of small `Copy` items.
+//!
+//! + `GenerationalArena`: hands out small `Copy` `Id` handles instead of references,
+//!     allowing values to be removed, with stale `Id`s detected by a generation check.
+//!
+//! + `ArenaVec`: a growable vector of `Copy` elements with its storage on the
+//!     `Arena`, which can be frozen into a plain `&'arena [T]` slice.
+//!
+//! + `ArenaString`: a growable string builder that writes directly into the
+//!     `Arena`, growing in place whenever possible.
+//!
+//! + `StringInterner`: deduplicates strings on the `Arena` and hands out small
+//!     `Copy` `Symbol` handles that can be resolved back to `&'arena str`.
+//!
 //! + `BloomMap` and `BloomSet`: special variants of `Map` and `Set` with a
 //!     very simple but very fast bloom filter. If a map / set is often queried
 //!     for keys / elements it doesn't contain, the bloom filter check will
 //!     reduce the need to do a full tree lookup, greatly increasing performance.
 //!     The overhead compared to a regular `Map` or `Set` is also minimal.
 //!
+//! + `PolicyMap` and `PolicySet`: variants of `Map` and `Set` that hash and compare
+//!     keys through a `KeyPolicy`, such as `AsciiCaseInsensitive`.
+//!
+//! + `Counter`: a multiset counting occurrences of keys, with a single lookup per
+//!     occurrence and a `most_common_in` query.
+//!
+//! + `ScalableBloom`: a bloom filter for deduplicating any number of items, which
+//!     grows by chaining filters on the `Arena` while bounding its false positive rate.
+//!
+//! + `Value`: a dynamically typed, JSON-like document model with all of its
+//!     strings, lists and maps allocated on the `Arena`, along with a minimal
+//!     JSON parser and writer.
+//!
+//! + `AllocIn`: deep-copies owned data such as `String`, `Vec<T>` or `Box<T>`
+//!     into the `Arena`, with a derive macro behind the `derive` feature flag.
+//!     `CloneIn` deep-clones arena data, including `List`s, `Map`s and `Set`s,
+//!     from one `Arena` into another.
+//!
+//! + `PrettyPrinter`: tracks indentation and wraps lines while writing trees of
+//!     arena data, such as ASTs, into an `ArenaString` or any `io::Write`.
+//!
+//! + `Snapshot`: copies a `List`, `Map` or custom tree into a relocatable byte
+//!     buffer that is loaded back into an `Arena` with a single `memcpy`, and can
+//!     be persisted across runs in a versioned byte format.
+//!
+//! + `patterns`: ready-made helpers for common idioms, such as chains of lexical
+//!     scopes, batch interning, builders that can roll back to a checkpoint and
+//!     memoized structural hashing of trees.
+//!
 //! + All data structures implement expected traits, such as `Debug` or `PartialEq`.
 //!
-//! + Optional **serde** `Serialize` support behind a feature flag.
+//! + Optional **serde** `Serialize` support behind a feature flag, along with
+//!     deserializing straight into the `Arena` through `DeserializeSeed`.
+//!
+//! + Optional **rkyv** zero-copy `Archive` support behind the `impl_archive`
+//!     feature flag, with `Map` and `MapKV` wrappers for collections of arena references.
+//!
+//! + `Allocator` implementation for `&Arena`, allowing std collections to keep
+//!     their memory on the `Arena`, behind the `allocator-api` feature flag
+//!     (requires nightly).
+//!
+//! + Poisoning of memory reclaimed by the `Arena` and extra checks on every
+//!     allocation, to catch use-after-clear bugs, behind the `debug-poison`
+//!     feature flag.
+//!
+//! + `Arena` pages sized to fit in pages of WebAssembly linear memory, behind the
+//!     `wasm` feature flag, along with `Arena::grow_hint` for `memory.grow` aware
+//!     embedders.
+//!
+//! + Word lists and arena reset helpers for benchmarking data structures on the
+//!     `Arena`, behind the `bench-support` feature flag.
+//!
+//! + Crate-wide choice of the default hasher between FxHash, aHash and SipHash
+//!     with the `hash-fx` (default), `hash-ahash` and `hash-siphash` feature flags.
 //!
 //! ## Example
 //!
@@ -59,23 +155,19 @@
 //!     let parent: &Foo = &*arena.alloc(Foo::Nested(child));
 //!
 //!     // Empty map does not allocate
-//!     let map = Map::new();
+//!     let mut map = Map::new();
 //!
 //!     // Inserting stuff in the map requires a reference to the `Arena`.
 //!     // The reference can be shared, since `Arena` uses interior mutability.
 //!     map.insert(&arena, "child", child);
 //!
-//!     // We can put our `map` on the arena as well. Once again we use the `&*`
-//!     // operation to change the reference to be immutable, just to demonstrate
-//!     // that our `Map` implementation is perfectly happy with internal mutability.
-//!     let map: &Map<&str, &Foo> = &*arena.alloc(map);
+//!     // We can put our `map` on the arena as well. `Map` is `Copy`, and
+//!     // copies of it share its nodes, so we keep a mutable reference to
+//!     // the one on the arena and insert through it.
+//!     let map: &mut Map<&str, &Foo> = arena.alloc(map);
 //!
 //!     // Each insert allocates a small chunk of data on the arena. Since arena is
 //!     // preallocated on the heap, these inserts are very, very fast.
-//!     //
-//!     // We only have a non-mutable reference to `map` now, however `Map` is also
-//!     // using interior mutability on references to allow exactly this kind of
-//!     // behavior in a safe manner.
 //!     map.insert(&arena, "parent", parent);
 //!
 //!     assert_eq!(map.get("child"), Some(&Foo::Integer(42)));
@@ -86,6 +178,7 @@
 //! ```
 
 #![warn(missing_docs)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
 // Pull in serde if `impl_serialize` is enabled
 #[cfg(feature = "impl_serialize")]
@@ -95,11 +188,36 @@ use serde;
 #[cfg(all(test, feature = "impl_serialize"))]
 use serde_json;
 
+// Allow the derive macros to refer to `::toolshed` within this crate
+#[cfg(feature = "derive")]
+extern crate self as toolshed;
+
+#[macro_use]
+mod macros;
 mod cell;
+mod weak;
+pub mod hash;
 pub mod map;
 pub mod set;
 pub mod list;
+pub mod interner;
+pub mod generational;
+pub mod value;
+pub mod json;
+pub mod patterns;
+pub mod pretty;
+pub mod snapshot;
+pub mod string;
+pub mod substring;
+pub mod vec;
 mod arena;
+mod sync_arena;
+mod herd;
+mod drop_arena;
+mod typed_arena;
+mod hash_cons;
+mod compact_arena;
+mod alloc_in;
 mod bloom;
 mod impl_partial_eq;
 mod impl_debug;
@@ -107,5 +225,29 @@ mod impl_debug;
 #[cfg(feature = "impl_serialize")]
 mod impl_serialize;
 
-pub use self::arena::{Arena, Uninitialized, NulTermStr};
-pub use self::cell::CopyCell;
+#[cfg(feature = "impl_serialize")]
+pub mod de;
+
+#[cfg(feature = "impl_archive")]
+mod impl_archive;
+
+#[cfg(feature = "bench-support")]
+pub mod bench_support;
+
+#[cfg(feature = "allocator-api")]
+mod allocator;
+
+pub use self::arena::{Arena, CyclicRef, Uninitialized, UninitializedSlice, UninitializedStr, NulTermStr, AllocError, StructOfSlices, PageCursor, NoUninit, POISON_BYTE};
+pub use self::arena::{set_alloc_error_hook, take_alloc_error_hook};
+pub use self::sync_arena::SyncArena;
+pub use self::herd::{Herd, Member, ThreadLocalArenas, LocalArena};
+pub use self::drop_arena::DropArena;
+pub use self::typed_arena::{TypedArena, TypedArenaIter};
+pub use self::hash_cons::{HashConsArena, HashConsIter};
+pub use self::compact_arena::{CompactArena, ArenaRef};
+pub use self::cell::{CopyCell, AtomicCell};
+pub use self::weak::WeakRef;
+pub use self::alloc_in::{AllocIn, CloneIn};
+
+#[cfg(feature = "derive")]
+pub use toolshed_derive::AllocIn;
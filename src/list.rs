@@ -1,27 +1,66 @@
 //! A linked list and auxiliary types that can be used with the `Arena`.
 
-use std::num::NonZeroUsize;
+use std::hash::Hash;
+use std::iter::FusedIterator;
+use std::ptr::NonNull;
 
+use crate::alloc_in::CloneIn;
 use crate::arena::Arena;
 use crate::cell::CopyCell;
+use crate::map::Map;
+use crate::snapshot::{Relocate, SnapshotWriter};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
 struct ListNode<'arena, T> {
-    value: T,
+    value: CopyCell<T>,
     next: CopyCell<Option<&'arena ListNode<'arena, T>>>,
 }
 
+impl<'arena, T> ListNode<'arena, T> {
+    /// Values are only written to by `List::iter_cells`, which may not be used
+    /// while any of the references returned here are alive.
+    #[inline]
+    fn value(&self) -> &T {
+        unsafe { &*self.value.as_ptr() }
+    }
+}
+
+impl<'arena, T: Copy> ListNode<'arena, T> {
+    /// Allocate nodes for all items in a single slice on the arena, linked in
+    /// order, returning the first and the last node.
+    fn alloc_chain<I>(arena: &'arena Arena, items: I) -> Option<(&'arena Self, &'arena Self)>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let nodes = arena.alloc_node_slice(items.into_iter().map(|value| ListNode {
+            value: CopyCell::new(value),
+            next: CopyCell::new(None),
+        }));
+
+        for pair in nodes.windows(2) {
+            pair[0].next.set(Some(&pair[1]));
+        }
+
+        Some((nodes.first()?, nodes.last()?))
+    }
+}
+
 /// A single-ended linked list.
+///
+/// The list keeps track of its length, so `len` is O(1). Copies of a list
+/// share its nodes, but each of them tracks its own length.
 #[derive(Clone, Copy)]
 pub struct List<'arena, T> {
-    root: CopyCell<Option<&'arena ListNode<'arena, T>>>,
+    root: Option<&'arena ListNode<'arena, T>>,
+    len: usize,
 }
 
 impl<'arena, T> List<'arena, T> {
     /// Create a new empty `List`.
     pub const fn empty() -> Self {
         List {
-            root: CopyCell::new(None)
+            root: None,
+            len: 0,
         }
     }
 
@@ -30,42 +69,369 @@ impl<'arena, T> List<'arena, T> {
     /// Internally, all this method does is removing the reference to the
     /// first item on the list.
     #[inline]
-    pub fn clear(&self) {
-        self.root.set(None);
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
     }
 
     /// Returns an iterator over the items in the list.
     #[inline]
     pub fn iter(&self) -> ListIter<'arena, T> {
         ListIter {
-            next: self.root.get()
+            next: self.root,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over cells of the items in the list, which can be
+    /// used to update the items in place instead of rebuilding the list.
+    ///
+    /// Copies of the list share its nodes, so they will see the updates too.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let list = List::from_iter(&arena, vec![1, 2, 3]);
+    ///
+    /// for cell in unsafe { list.iter_cells() } {
+    ///     cell.set(cell.get() * 10);
+    /// }
+    ///
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), [10, 20, 30]);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// All other methods of the list hand out references to its elements,
+    /// none of which may be alive while the cells are set. This includes
+    /// references obtained from copies of the list, or from any other list
+    /// sharing its nodes.
+    #[inline]
+    pub unsafe fn iter_cells(&self) -> ListCellIter<'arena, T> {
+        ListCellIter {
+            inner: self.iter(),
         }
     }
 
     /// Checks if the list is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.root.get().is_none()
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the list, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
     }
 
     /// Returns the first element if, and only if, the list contains
     /// just that single element.
     #[inline]
     pub fn only_element(&self) -> Option<&'arena T> {
-        match self.root.get() {
-            Some(&ListNode {
-                ref value,
-                ref next,
-                ..
-            }) if next.get().is_none() => Some(value),
-            _                          => None
+        match self.root {
+            Some(node) if self.len == 1 => Some(node.value()),
+            _                                 => None
         }
     }
 
     /// Returns the reference to the first element.
     #[inline]
     pub fn first_element(&self) -> Option<&'arena T> {
-        self.root.get().map(|li| &li.value)
+        self.root.map(|li| li.value())
+    }
+
+    /// Returns the reference to the last element. This walks the whole
+    /// list, O(n).
+    #[inline]
+    pub fn last_element(&self) -> Option<&'arena T> {
+        self.last_node().map(|node| node.value())
+    }
+
+    /// Returns the reference to the element at `index`, or `None` if the
+    /// list is shorter. This walks the list up to the element, O(n).
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'arena T> {
+        self.iter().nth(index)
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    /// This walks the list until the element is found, O(n).
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Collect references to all elements into a slice on the `arena`, in a
+    /// single pass, for O(1) indexed access. The list itself is unchanged.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let list = List::from_iter(&arena, vec!["doge", "to", "the", "moon!"]);
+    /// let slice = list.to_arena_slice(&arena);
+    ///
+    /// assert_eq!(slice.len(), 4);
+    /// assert_eq!(slice[2], &"the");
+    /// ```
+    #[inline]
+    pub fn to_arena_slice(&self, arena: &'arena Arena) -> &'arena [&'arena T] {
+        arena.alloc_slice_map(self.iter(), |item| item)
+    }
+
+    /// Returns references to both the first and the last element, walking
+    /// the list once. For a single-element list both are the same element.
+    #[inline]
+    pub fn first_and_last(&self) -> Option<(&'arena T, &'arena T)> {
+        let first = self.root?;
+
+        Some((first.value(), self.last_node()?.value()))
+    }
+
+    /// Returns the last element along with a list of all the elements before
+    /// it, walking the list once. The returned list shares its nodes with
+    /// this list, so nothing is allocated.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let list = List::from_iter(&arena, vec!["a", "b", "c"]);
+    /// let mut out = String::new();
+    ///
+    /// if let Some((init, last)) = list.split_last() {
+    ///     for item in init.iter() {
+    ///         out.push_str(item);
+    ///         out.push_str(", ");
+    ///     }
+    ///
+    ///     out.push_str(last);
+    /// }
+    ///
+    /// assert_eq!(out, "a, b, c");
+    /// ```
+    #[inline]
+    pub fn split_last(&self) -> Option<(List<'arena, T>, &'arena T)> {
+        let last = self.last_node()?;
+        let init = List {
+            root: self.root,
+            len: self.len - 1,
+        };
+
+        Some((init, last.value()))
+    }
+
+    /// Keep only the elements for which `f` returns `true`.
+    ///
+    /// Nodes of the list are never modified, so copies of the list that share
+    /// them are left intact. Instead, the elements kept from before the last
+    /// removed element are copied to the `arena`, while the nodes after it
+    /// are shared with the resulting list. To unlink the removed elements in
+    /// place, use `retain_recycling`.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let mut statements = List::from_iter(&arena, vec!["a()", "unreachable()", "b()"]);
+    /// let original = statements;
+    ///
+    /// statements.retain(&arena, |statement| *statement != "unreachable()");
+    ///
+    /// assert_eq!(statements.iter().copied().collect::<Vec<_>>(), ["a()", "b()"]);
+    /// assert_eq!(statements.len(), 2);
+    /// assert_eq!(original.len(), 3);
+    /// ```
+    pub fn retain<F>(&mut self, arena: &'arena Arena, mut f: F)
+    where
+        T: Copy,
+        F: FnMut(&T) -> bool,
+    {
+        let mut kept = Vec::new();
+        let mut copied = 0;
+        let mut tail = None;
+        let mut next = self.root;
+
+        for _ in 0..self.len {
+            let node = match next {
+                Some(node) => node,
+                None       => break,
+            };
+
+            next = node.next.get();
+
+            if f(node.value()) {
+                kept.push(*node.value());
+            } else {
+                copied = kept.len();
+                tail = Some(next);
+            }
+        }
+
+        // If the last element was removed, the nodes past the end of this
+        // list must not be linked to, as they aren't a part of it.
+        let tail = match tail {
+            Some(_) if copied == kept.len() => None,
+            Some(tail)                      => tail,
+            None                            => return,
+        };
+
+        self.root = match ListNode::alloc_chain(arena, kept[..copied].iter().copied()) {
+            Some((first, last)) => {
+                last.next.set(tail);
+
+                Some(first)
+            },
+            None => tail,
+        };
+        self.len = kept.len();
+    }
+
+    /// Keep only the elements for which `f` returns `true`, unlinking all
+    /// others from the list in place in a single pass. The nodes of removed
+    /// elements are given back to the `arena` with `Arena::recycle`, so that
+    /// they can be reused by the nodes of elements pushed to lists afterwards.
+    ///
+    /// # Safety
+    ///
+    /// No references to the removed elements may be used afterwards, and
+    /// no other list may share the nodes of this list.
+    pub unsafe fn retain_recycling<F>(&mut self, arena: &'arena Arena, mut f: F)
+    where
+        T: Copy,
+        F: FnMut(&T) -> bool,
+    {
+        let mut last: Option<&'arena ListNode<'arena, T>> = None;
+        let mut next = self.root;
+        let mut remaining = self.len;
+        let mut len = 0;
+
+        while remaining > 0 {
+            let node = match next {
+                Some(node) => node,
+                None       => break,
+            };
+
+            next = node.next.get();
+
+            if f(node.value()) {
+                last = Some(node);
+                len += 1;
+            } else {
+                match last {
+                    Some(last) => last.next.set(next),
+                    None       => self.root = next,
+                }
+
+                arena.recycle_node(node);
+            }
+
+            remaining -= 1;
+        }
+
+        self.len = len;
+    }
+
+    #[inline]
+    fn last_node(&self) -> Option<&'arena ListNode<'arena, T>> {
+        let mut node = self.root?;
+
+        for _ in 1..self.len {
+            node = node.next.get()?;
+        }
+
+        Some(node)
+    }
+
+    /// Create a new list on the arena with the results of applying `f` to
+    /// all elements of this list.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let words = List::from_iter(&arena, vec!["doge", "to", "the", "moon!"]);
+    /// let lengths = words.map_in(&arena, |word| word.len());
+    ///
+    /// assert_eq!(lengths.iter().copied().collect::<Vec<_>>(), [4, 2, 3, 5]);
+    /// ```
+    #[inline]
+    pub fn map_in<U, F>(&self, arena: &'arena Arena, f: F) -> List<'arena, U>
+    where
+        U: Copy,
+        F: FnMut(&'arena T) -> U,
+    {
+        List::from_iter(arena, self.iter().map(f))
+    }
+
+    /// Create a new list on the arena with the results of applying `f` to
+    /// all elements of this list, skipping elements for which `f` returns
+    /// `None`.
+    #[inline]
+    pub fn filter_map_in<U, F>(&self, arena: &'arena Arena, f: F) -> List<'arena, U>
+    where
+        U: Copy,
+        F: FnMut(&'arena T) -> Option<U>,
+    {
+        List::from_iter(arena, self.iter().filter_map(f))
+    }
+
+    /// Create a new list on the arena by pairing up elements of this list
+    /// with elements of the `other` list and combining them with `f`.
+    ///
+    /// The resulting list is as long as the shorter of the two lists.
+    pub fn zip_with<U, R, F>(&self, arena: &'arena Arena, other: &List<'arena, U>, mut f: F) -> List<'arena, R>
+    where
+        R: Copy,
+        F: FnMut(&'arena T, &'arena U) -> R,
+    {
+        List::from_iter(arena, self.iter().zip(other.iter()).map(|(a, b)| f(a, b)))
+    }
+
+    /// Create a new list on the arena by combining every pair of adjacent
+    /// elements of this list with `f`, such as turning a sequence of nodes
+    /// into the edges between them. The resulting list is one element
+    /// shorter than this list, or empty if this list has less than two
+    /// elements.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let blocks = List::from_iter(&arena, vec!["entry", "loop", "exit"]);
+    /// let edges = blocks.map_adjacent_in(&arena, |from, to| (*from, *to));
+    ///
+    /// assert_eq!(edges.iter().copied().collect::<Vec<_>>(), [("entry", "loop"), ("loop", "exit")]);
+    /// ```
+    pub fn map_adjacent_in<U, F>(&self, arena: &'arena Arena, mut f: F) -> List<'arena, U>
+    where
+        U: Copy,
+        F: FnMut(&'arena T, &'arena T) -> U,
+    {
+        List::from_iter(arena, self.iter().zip(self.iter().skip(1)).map(|(a, b)| f(a, b)))
+    }
+
+    /// Get references to the last `n` elements of the list, in order, as a
+    /// slice on the arena. If the list is shorter than `n`, all of its
+    /// elements are returned.
+    ///
+    /// Only as many references as are returned are allocated, regardless
+    /// of how large `n` is.
+    pub fn last_n_in(&self, arena: &'arena Arena, n: usize) -> &'arena [&'arena T] {
+        let n = n.min(self.len());
+
+        arena.alloc_uninitialized_slice(n).init_with(self.iter().skip(self.len() - n))
     }
 
     /// Returns an `UnsafeList` for the current `List`. While this function is
@@ -73,9 +439,8 @@ impl<'arena, T> List<'arena, T> {
     #[inline]
     pub fn into_unsafe(self) -> UnsafeList {
         UnsafeList {
-            root: self.root.get().map(|ptr| unsafe {
-                NonZeroUsize::new_unchecked(ptr as *const ListNode<T> as usize)
-            }),
+            root: self.root.map(|ptr| NonNull::from(ptr).cast()),
+            len: self.len,
         }
     }
 }
@@ -85,10 +450,24 @@ impl<'arena, T: Copy> List<'arena, T> {
     #[inline]
     pub fn from(arena: &'arena Arena, value: T) -> List<'arena, T> {
         List {
-            root: CopyCell::new(Some(arena.alloc(ListNode {
-                value,
+            root: Some(arena.alloc_node(ListNode {
+                value: CopyCell::new(value),
                 next: CopyCell::new(None)
-            })))
+            })),
+            len: 1,
+        }
+    }
+
+    /// Create a list from a slice of items. All nodes are allocated at once,
+    /// so they are laid out next to each other on the arena.
+    #[inline]
+    pub fn from_slice(arena: &'arena Arena, items: &[T]) -> List<'arena, T> {
+        match ListNode::alloc_chain(arena, items.iter().copied()) {
+            Some((first, _)) => List {
+                root: Some(first),
+                len: items.len(),
+            },
+            None => List::empty(),
         }
     }
 
@@ -98,7 +477,7 @@ impl<'arena, T: Copy> List<'arena, T> {
     {
         let mut iter = source.into_iter();
 
-        let builder = match iter.next() {
+        let mut builder = match iter.next() {
             Some(item) => ListBuilder::new(arena, item),
             None       => return List::empty(),
         };
@@ -110,29 +489,73 @@ impl<'arena, T: Copy> List<'arena, T> {
         builder.as_list()
     }
 
+    /// Create a new list on the arena containing pairs of elements from
+    /// this list and the `other` list.
+    ///
+    /// The resulting list is as long as the shorter of the two lists.
+    #[inline]
+    pub fn zip_in<U: Copy>(&self, arena: &'arena Arena, other: &List<'arena, U>) -> List<'arena, (T, U)> {
+        self.zip_with(arena, other, |a, b| (*a, *b))
+    }
+
+    /// Group elements of this list into a new `Map` on the arena, keyed by
+    /// the result of `key_fn`. Elements within each group keep their order,
+    /// and the groups are in order of first occurrence of their key. The tree
+    /// of the map is only descended once for every element.
+    pub fn group_by_in<K, F>(&self, arena: &'arena Arena, mut key_fn: F) -> Map<'arena, K, List<'arena, T>>
+    where
+        K: Eq + Hash + Copy,
+        F: FnMut(&T) -> K,
+    {
+        let mut groups: Map<K, GrowableList<T>> = Map::new();
+
+        for item in self.iter() {
+            let cell = groups.cell_or_insert_with(arena, key_fn(item), GrowableList::new);
+            let mut group = cell.get();
+
+            group.push(arena, *item);
+            cell.set(group);
+        }
+
+        groups.map_values_in(arena, |_, group| group.as_list())
+    }
+
+    /// Create a new list on the arena by mapping each element of this list
+    /// to an iterator with `f`, and chaining all the results together.
+    pub fn flat_map_in<U, I, F>(&self, arena: &'arena Arena, f: F) -> List<'arena, U>
+    where
+        U: Copy,
+        I: IntoIterator<Item = U>,
+        F: FnMut(&'arena T) -> I,
+    {
+        List::from_iter(arena, self.iter().flat_map(f))
+    }
+
     /// Adds a new element to the beginning of the list.
     #[inline]
-    pub fn prepend(&self, arena: &'arena Arena, value: T) -> &'arena T {
-        let root = arena.alloc(
+    pub fn prepend(&mut self, arena: &'arena Arena, value: T) -> &'arena T {
+        let root = arena.alloc_node(
             ListNode {
-                value,
-                next: self.root
+                value: CopyCell::new(value),
+                next: CopyCell::new(self.root)
             }
         );
 
-        self.root.set(Some(root));
+        self.root = Some(root);
+        self.len += 1;
 
-        &root.value
+        root.value()
     }
 
     /// Removes the first element from the list and returns it.
     #[inline]
-    pub fn shift(&self) -> Option<&'arena T> {
-        let list_item = self.root.get()?;
+    pub fn shift(&mut self) -> Option<&'arena T> {
+        let list_item = self.root?;
 
-        self.root.set(list_item.next.get());
+        self.root = list_item.next.get();
+        self.len -= 1;
 
-        Some(&list_item.value)
+        Some(list_item.value())
     }
 
     /// Get the first element of the `List`, if any, then create a
@@ -143,13 +566,97 @@ impl<'arena, T: Copy> List<'arena, T> {
     ///       If you wish to modify the list use `shift` instead.
     #[inline]
     pub fn shift_ref(&mut self) -> Option<&'arena T> {
-        let list_item = self.root.get()?;
+        let list_item = self.root?;
 
         *self = List {
-            root: list_item.next
+            root: list_item.next.get(),
+            len: self.len - 1,
         };
 
-        Some(&list_item.value)
+        Some(list_item.value())
+    }
+}
+
+impl<'arena, T: Copy> List<'arena, List<'arena, T>> {
+    /// Concatenate all nested lists into a single list, relinking their nodes
+    /// where possible.
+    ///
+    /// A list is spliced in by linking its last node to the first node of the
+    /// next list, which is only done if no other node follows it yet, as is
+    /// the case for lists that were built to completion. Lists followed by
+    /// other nodes, such as prefixes of longer lists, have their elements
+    /// copied to the arena instead. The last non-empty list is always linked
+    /// to directly.
+    ///
+    /// Copies of the nested lists keep their own length, so they are left
+    /// intact, but just like with `GrowableList::append` the `GrowableList`
+    /// or `ListBuilder` a spliced list was built with must not be pushed to
+    /// anymore.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    ///
+    /// let arena = Arena::new();
+    /// let first = List::from_iter(&arena, vec![1, 2]);
+    /// let second = List::from_iter(&arena, vec![3, 4]);
+    /// let body = List::from_iter(&arena, vec![first, second]).flatten_in(&arena);
+    ///
+    /// assert_eq!(body.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    /// assert_eq!(first.iter().copied().collect::<Vec<_>>(), [1, 2]);
+    ///
+    /// // Both lists were spliced in, so no elements were copied
+    /// assert!(std::ptr::eq(body.first_element().unwrap(), first.first_element().unwrap()));
+    /// ```
+    pub fn flatten_in(&self, arena: &'arena Arena) -> List<'arena, T> {
+        let mut flat = GrowableList::new();
+        let mut lists = self.iter().filter(|list| !list.is_empty()).peekable();
+
+        while let Some(list) = lists.next() {
+            if lists.peek().is_none() {
+                // Nothing is linked after the last list, so it doesn't need
+                // to end a chain of nodes.
+                match flat.last {
+                    Some(last) => last.next.set(list.root),
+                    None       => return *list,
+                }
+
+                return List {
+                    root: flat.first,
+                    len: flat.len + list.len,
+                };
+            }
+
+            let last = match list.last_node() {
+                Some(last) => last,
+                None       => continue,
+            };
+
+            // The last node of the previous list hasn't been linked to the
+            // next one yet, but it no longer ends a chain of its own.
+            let spliced = last.next.get().is_none() && !flat.last.is_some_and(|end| std::ptr::eq(end, last));
+
+            let mut other = if spliced {
+                GrowableList {
+                    first: list.root,
+                    last: Some(last),
+                    len: list.len,
+                }
+            } else {
+                match ListNode::alloc_chain(arena, list.iter().copied()) {
+                    Some((first, last)) => GrowableList {
+                        first: Some(first),
+                        last: Some(last),
+                        len: list.len,
+                    },
+                    None => continue,
+                }
+            };
+
+            flat.append(&mut other);
+        }
+
+        flat.as_list()
     }
 }
 
@@ -177,8 +684,9 @@ impl<'a, 'arena, T> IntoIterator for &'a List<'arena, T> {
 /// allows user to push to the end of the list.
 #[derive(Clone, Copy)]
 pub struct GrowableList<'arena, T> {
-    last: CopyCell<Option<&'arena ListNode<'arena, T>>>,
-    first: CopyCell<Option<&'arena ListNode<'arena, T>>>,
+    last: Option<&'arena ListNode<'arena, T>>,
+    first: Option<&'arena ListNode<'arena, T>>,
+    len: usize,
 }
 
 impl<'arena, T> GrowableList<'arena, T>
@@ -187,18 +695,86 @@ where
 {
     /// Push a new item at the end of the `List`.
     #[inline]
-    pub fn push(&self, arena: &'arena Arena, item: T) {
-        let next = Some(&*arena.alloc(ListNode {
-            value: item,
+    pub fn push(&mut self, arena: &'arena Arena, item: T) {
+        let next = Some(arena.alloc_node(ListNode {
+            value: CopyCell::new(item),
             next: CopyCell::new(None)
         }));
 
-        match self.last.get() {
+        match self.last {
             Some(ref last) => last.next.set(next),
-            None           => self.first.set(next),
+            None           => self.first = next,
+        }
+
+        self.last = next;
+        self.len += 1;
+    }
+
+    /// Push all items of the iterator at the end of the `List`, returning
+    /// the number of items pushed. The last element is only updated once,
+    /// after all items have been linked.
+    pub fn extend<I>(&mut self, arena: &'arena Arena, iter: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+
+        let first = match iter.next() {
+            Some(value) => arena.alloc_node(ListNode {
+                value: CopyCell::new(value),
+                next: CopyCell::new(None)
+            }),
+            None => return 0,
+        };
+
+        match self.last {
+            Some(last) => last.next.set(Some(first)),
+            None       => self.first = Some(first),
+        }
+
+        let mut last = first;
+        let mut count = 1;
+
+        for value in iter {
+            let next = arena.alloc_node(ListNode {
+                value: CopyCell::new(value),
+                next: CopyCell::new(None)
+            });
+
+            last.next.set(Some(next));
+            last = next;
+            count += 1;
         }
 
-        self.last.set(next);
+        self.last = Some(last);
+        self.len += count;
+
+        count
+    }
+
+    /// Push all items of an iterator of known length at the end of the `List`.
+    /// Unlike `extend`, all nodes are allocated at once, so they are laid out
+    /// next to each other on the arena.
+    pub fn push_iter<I>(&mut self, arena: &'arena Arena, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let count = iter.len();
+
+        let (first, last) = match ListNode::alloc_chain(arena, iter) {
+            Some(chain) => chain,
+            None        => return,
+        };
+
+        match self.last {
+            Some(tail) => tail.next.set(Some(first)),
+            None       => self.first = Some(first),
+        }
+
+        self.last = Some(last);
+        self.len += count;
     }
 }
 
@@ -206,17 +782,56 @@ impl<'arena, T> GrowableList<'arena, T> {
     /// Create a new builder.
     pub const fn new() -> Self {
         GrowableList {
-            first: CopyCell::new(None),
-            last: CopyCell::new(None),
+            first: None,
+            last: None,
+            len: 0,
         }
     }
 
-    /// Get a `List` from the builder.
+    /// Get a `List` from the builder. The `List` contains the elements pushed
+    /// so far, elements pushed afterwards are not added to it.
     #[inline]
     pub fn as_list(&self) -> List<'arena, T> {
         List {
-            root: self.first
+            root: self.first,
+            len: self.len,
+        }
+    }
+
+    /// Checks if anything has been pushed to the list.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements pushed to the list, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Move all elements of the `other` list to the end of this list by
+    /// relinking the nodes, leaving `other` empty. This is an O(1) operation.
+    ///
+    /// Any copies of `other` made before the call still share the nodes
+    /// with this list, so they must not be pushed to anymore.
+    #[inline]
+    pub fn append(&mut self, other: &mut GrowableList<'arena, T>) {
+        let (first, last) = match (other.first, other.last) {
+            (Some(first), Some(last)) => (first, last),
+            _                         => return,
+        };
+
+        match self.last {
+            Some(tail) => tail.next.set(Some(first)),
+            None       => self.first = Some(first),
         }
+
+        self.last = Some(last);
+        self.len += other.len;
+        other.first = None;
+        other.last = None;
+        other.len = 0;
     }
 }
 
@@ -228,178 +843,1013 @@ impl<'arena, T> GrowableList<'arena, T> {
 #[derive(Clone, Copy)]
 pub struct ListBuilder<'arena, T> {
     first: &'arena ListNode<'arena, T>,
-    last: CopyCell<&'arena ListNode<'arena, T>>,
+    last: &'arena ListNode<'arena, T>,
+    len: usize,
 }
 
 impl<'arena, T: Copy> ListBuilder<'arena, T> {
     /// Create a new builder with the first element.
     #[inline]
     pub fn new(arena: &'arena Arena, first: T) -> Self {
-        let first = arena.alloc(ListNode {
-            value: first,
+        let first = arena.alloc_node(ListNode {
+            value: CopyCell::new(first),
             next: CopyCell::new(None)
         });
 
         ListBuilder {
             first,
-            last: CopyCell::new(first),
+            last: first,
+            len: 1,
         }
     }
 
     /// Push a new item at the end of the `List`.
     #[inline]
-    pub fn push(&self, arena: &'arena Arena, item: T) {
-        let next = arena.alloc(ListNode {
-            value: item,
+    pub fn push(&mut self, arena: &'arena Arena, item: T) {
+        let next = arena.alloc_node(ListNode {
+            value: CopyCell::new(item),
             next: CopyCell::new(None)
         });
 
-        self.last.get().next.set(Some(next));
-        self.last.set(next);
+        self.last.next.set(Some(next));
+        self.last = next;
+        self.len += 1;
+    }
+
+    /// Push all items of a slice at the end of the `List`. All nodes are
+    /// allocated at once, so they are laid out next to each other on the arena.
+    pub fn push_slice(&mut self, arena: &'arena Arena, items: &[T]) {
+        if let Some((first, last)) = ListNode::alloc_chain(arena, items.iter().copied()) {
+            self.last.next.set(Some(first));
+            self.last = last;
+            self.len += items.len();
+        }
     }
 }
 
 impl<'arena, T> ListBuilder<'arena, T> {
-    /// Get a `List` from the builder.
+    /// Get a `List` from the builder. The `List` contains the elements pushed
+    /// so far, elements pushed afterwards are not added to it.
     #[inline]
     pub fn as_list(&self) -> List<'arena, T> {
         List {
-            root: CopyCell::new(Some(self.first))
+            root: Some(self.first),
+            len: self.len,
         }
     }
+
+    /// Always `false`, since a `ListBuilder` starts with its first element.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of elements pushed to the list, including the
+    /// first one, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
 }
 
-/// Unsafe variant of the `List` that erases any lifetime information.
-#[derive(Debug, Clone, Copy)]
-pub struct UnsafeList {
-    root: Option<NonZeroUsize>,
+#[derive(Debug, PartialEq)]
+struct DoublyNode<'arena, T> {
+    value: T,
+    prev: CopyCell<Option<&'arena DoublyNode<'arena, T>>>,
+    next: CopyCell<Option<&'arena DoublyNode<'arena, T>>>,
 }
 
-impl UnsafeList {
-    /// Converts the `UnsafeList` into a regular `List`. Using this with
-    /// incorrect lifetimes of after the original arena has been dropped
-    /// will lead to undefined behavior. Use with extreme care.
-    pub unsafe fn into_list<'arena, T>(self) -> List<'arena, T> {
-        List {
-            root: CopyCell::new(self.root.map(|ptr| &*(ptr.get() as *const ListNode<'arena, T>))),
-        }
-    }
+/// A doubly-linked list, which can be iterated over in both directions and
+/// modified at both ends. Every node stores an extra link to the previous
+/// node, so prefer `List` unless walking backwards is needed.
+///
+/// Copies of a `DoublyList` share its nodes, so modifying one of them also
+/// relinks the nodes seen by the others.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::list::DoublyList;
+///
+/// let arena = Arena::new();
+/// let mut list = DoublyList::from_iter(&arena, vec!["let a = 1;", "let b = 2;"]);
+///
+/// list.push_back(&arena, "return a + b;");
+///
+/// assert_eq!(list.last_element(), Some(&"return a + b;"));
+/// assert_eq!(list.iter().rev().next(), Some(&"return a + b;"));
+/// assert_eq!(list.pop_back(), Some(&"return a + b;"));
+/// assert_eq!(list.len(), 2);
+/// ```
+#[derive(Clone, Copy)]
+pub struct DoublyList<'arena, T> {
+    first: Option<&'arena DoublyNode<'arena, T>>,
+    last: Option<&'arena DoublyNode<'arena, T>>,
+    len: usize,
 }
 
-/// An iterator over the items in the list.
-pub struct ListIter<'arena, T> {
-    next: Option<&'arena ListNode<'arena, T>>
+impl<'arena, T> Default for DoublyList<'arena, T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<'arena, T> Iterator for ListIter<'arena, T> {
-    type Item = &'arena T;
+impl<'arena, T> DoublyList<'arena, T> {
+    /// Create a new empty `DoublyList`.
+    pub const fn new() -> Self {
+        DoublyList {
+            first: None,
+            last: None,
+            len: 0,
+        }
+    }
 
+    /// Turns the list into an empty list.
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.next;
+    pub fn clear(&mut self) {
+        self.first = None;
+        self.last = None;
+        self.len = 0;
+    }
 
-        next.map(|list_item| {
-            let value = &list_item.value;
-            self.next = list_item.next.get();
-            value
-        })
+    /// Returns a double-ended iterator over the items in the list.
+    #[inline]
+    pub fn iter(&self) -> DoublyListIter<'arena, T> {
+        DoublyListIter {
+            front: self.first,
+            back: self.last,
+            len: self.len,
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Checks if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 
-    #[test]
-    fn builder() {
-        let arena = Arena::new();
-        let builder = ListBuilder::new(&arena, 10);
+    /// Returns the number of elements in the list, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-        builder.push(&arena, 20);
-        builder.push(&arena, 30);
+    /// Returns the reference to the first element.
+    #[inline]
+    pub fn first_element(&self) -> Option<&'arena T> {
+        self.first.map(|node| &node.value)
+    }
 
-        let list = builder.as_list();
+    /// Returns the reference to the last element.
+    #[inline]
+    pub fn last_element(&self) -> Option<&'arena T> {
+        self.last.map(|node| &node.value)
+    }
 
-        assert!(list.iter().eq([10, 20, 30].iter()));
+    /// Returns references to both the first and the last element.
+    #[inline]
+    pub fn first_and_last(&self) -> Option<(&'arena T, &'arena T)> {
+        Some((self.first_element()?, self.last_element()?))
     }
 
-    #[test]
-    fn empty_builder() {
-        let arena = Arena::new();
-        let builder = GrowableList::new();
+    /// Removes the first element from the list and returns it.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<&'arena T> {
+        let node = self.first?;
+        let next = node.next.get();
 
-        builder.push(&arena, 10);
-        builder.push(&arena, 20);
+        match next {
+            Some(next) => next.prev.set(None),
+            None       => self.last = None,
+        }
+
+        self.first = next;
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    /// Removes the last element from the list and returns it.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<&'arena T> {
+        let node = self.last?;
+        let prev = node.prev.get();
+
+        match prev {
+            Some(prev) => prev.next.set(None),
+            None       => self.first = None,
+        }
+
+        self.last = prev;
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+}
+
+impl<'arena, T: Copy> DoublyList<'arena, T> {
+    /// Create a list from an iterator of items.
+    pub fn from_iter<I>(arena: &'arena Arena, source: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = DoublyList::new();
+
+        for item in source {
+            list.push_back(arena, item);
+        }
+
+        list
+    }
+
+    /// Push a new item at the end of the list.
+    #[inline]
+    pub fn push_back(&mut self, arena: &'arena Arena, value: T) -> &'arena T {
+        let node = arena.alloc_node(DoublyNode {
+            value,
+            prev: CopyCell::new(self.last),
+            next: CopyCell::new(None),
+        });
+
+        match self.last {
+            Some(last) => last.next.set(Some(node)),
+            None       => self.first = Some(node),
+        }
+
+        self.last = Some(node);
+        self.len += 1;
+
+        &node.value
+    }
+
+    /// Push a new item at the beginning of the list.
+    #[inline]
+    pub fn push_front(&mut self, arena: &'arena Arena, value: T) -> &'arena T {
+        let node = arena.alloc_node(DoublyNode {
+            value,
+            prev: CopyCell::new(None),
+            next: CopyCell::new(self.first),
+        });
+
+        match self.first {
+            Some(first) => first.prev.set(Some(node)),
+            None        => self.last = Some(node),
+        }
+
+        self.first = Some(node);
+        self.len += 1;
+
+        &node.value
+    }
+}
+
+impl<'arena, T> IntoIterator for DoublyList<'arena, T> {
+    type Item = &'arena T;
+    type IntoIter = DoublyListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'arena, T> IntoIterator for &DoublyList<'arena, T> {
+    type Item = &'arena T;
+    type IntoIter = DoublyListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Number of elements stored in a single node of an `UnrolledList`.
+const UNROLLED_CHUNK: usize = 8;
+
+struct UnrolledNode<'arena, T> {
+    values: [CopyCell<T>; UNROLLED_CHUNK],
+    next: CopyCell<Option<&'arena UnrolledNode<'arena, T>>>,
+}
+
+/// A list storing up to 8 elements in every node on the arena, which cuts
+/// down pointer chasing when iterating over lists of small `Copy` items.
+/// Elements can only be pushed to the end of the list.
+///
+/// Copies of an `UnrolledList` share its nodes, so only one of them should
+/// be pushed to, as pushing to another one overwrites the elements in the
+/// last node. For the same reason, elements are returned by value rather
+/// than by reference.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::list::UnrolledList;
+///
+/// let arena = Arena::new();
+/// let mut list = UnrolledList::from_iter(&arena, 0..20u32);
+///
+/// list.push(&arena, 20);
+///
+/// assert_eq!(list.len(), 21);
+/// assert_eq!(list.get(10), Some(10));
+/// assert_eq!(list.last_element(), Some(20));
+/// assert_eq!(list.iter().sum::<u32>(), 210);
+/// ```
+#[derive(Clone, Copy)]
+pub struct UnrolledList<'arena, T> {
+    first: Option<&'arena UnrolledNode<'arena, T>>,
+    last: Option<&'arena UnrolledNode<'arena, T>>,
+    len: usize,
+}
+
+impl<'arena, T> Default for UnrolledList<'arena, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'arena, T> UnrolledList<'arena, T> {
+    /// Create a new empty `UnrolledList`.
+    pub const fn new() -> Self {
+        UnrolledList {
+            first: None,
+            last: None,
+            len: 0,
+        }
+    }
+
+    /// Turns the list into an empty list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.first = None;
+        self.last = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the items in the list.
+    #[inline]
+    pub fn iter(&self) -> UnrolledListIter<'arena, T> {
+        UnrolledListIter {
+            node: self.first,
+            index: 0,
+            len: self.len,
+        }
+    }
+
+    /// Checks if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the list, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'arena, T: Copy> UnrolledList<'arena, T> {
+    /// Create a list from an iterator of items.
+    pub fn from_iter<I>(arena: &'arena Arena, source: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = UnrolledList::new();
+
+        for item in source {
+            list.push(arena, item);
+        }
+
+        list
+    }
+
+    /// Push a new item at the end of the list. A new node is only allocated
+    /// once the last one is full.
+    #[inline]
+    pub fn push(&mut self, arena: &'arena Arena, value: T) {
+        let index = self.len % UNROLLED_CHUNK;
+
+        match self.last {
+            Some(last) if index != 0 => last.values[index].set(value),
+            last => {
+                let node = arena.alloc_node(UnrolledNode {
+                    values: std::array::from_fn(|_| CopyCell::new(value)),
+                    next: CopyCell::new(None),
+                });
+
+                match last {
+                    Some(last) => last.next.set(Some(node)),
+                    None       => self.first = Some(node),
+                }
+
+                self.last = Some(node);
+            },
+        }
+
+        self.len += 1;
+    }
+
+    /// Returns a copy of the first element.
+    #[inline]
+    pub fn first_element(&self) -> Option<T> {
+        self.first.map(|node| node.values[0].get())
+    }
+
+    /// Returns a copy of the last element.
+    #[inline]
+    pub fn last_element(&self) -> Option<T> {
+        let index = self.len.checked_sub(1)? % UNROLLED_CHUNK;
+
+        self.last.map(|node| node.values[index].get())
+    }
+
+    /// Returns a copy of the element at `index`, or `None` if the
+    /// list is shorter. This only walks the nodes up to the element.
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut node = self.first?;
+
+        for _ in 0..index / UNROLLED_CHUNK {
+            node = node.next.get()?;
+        }
+
+        Some(node.values[index % UNROLLED_CHUNK].get())
+    }
+}
+
+impl<'arena, T: Copy> IntoIterator for UnrolledList<'arena, T> {
+    type Item = T;
+    type IntoIter = UnrolledListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'arena, T: Copy> IntoIterator for &UnrolledList<'arena, T> {
+    type Item = T;
+    type IntoIter = UnrolledListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Elements are cloned in order into a new `List` on the target `Arena`.
+impl<'arena, 'target, T> CloneIn<'target> for List<'arena, T>
+where
+    T: CloneIn<'target>,
+{
+    type Cloned = List<'target, T::Cloned>;
+
+    fn clone_in(&self, arena: &'target Arena) -> Self::Cloned {
+        let mut list = GrowableList::new();
+
+        for item in self.iter() {
+            list.push(arena, item.clone_in(arena));
+        }
+
+        list.as_list()
+    }
+}
+
+unsafe impl<'arena, T: Relocate> Relocate for ListNode<'arena, T> {
+    type Relocated<'r> = ListNode<'r, T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        // `CopyCell` is `repr(transparent)`, so the value sits at the same offset.
+        out.write_field(at, self, self.value());
+        out.write_field(at, self, &self.next);
+    }
+}
+
+unsafe impl<'arena, T: Relocate> Relocate for List<'arena, T> {
+    type Relocated<'r> = List<'r, T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.root);
+        out.write_field(at, self, &self.len);
+    }
+}
+
+unsafe impl<'arena, T: Relocate> Relocate for GrowableList<'arena, T> {
+    type Relocated<'r> = GrowableList<'r, T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.last);
+        out.write_field(at, self, &self.first);
+        out.write_field(at, self, &self.len);
+    }
+}
+
+unsafe impl<'arena, T: Relocate> Relocate for ListBuilder<'arena, T> {
+    type Relocated<'r> = ListBuilder<'r, T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.first);
+        out.write_field(at, self, &self.last);
+        out.write_field(at, self, &self.len);
+    }
+}
+
+/// Unsafe variant of the `List` that erases any lifetime information.
+///
+/// The root of the list is kept as a pointer rather than an integer address,
+/// so that its provenance is preserved when converting back to a `List`.
+///
+/// To keep a list around for longer than its arena, such as in a cache that
+/// outlives the process, take a `Snapshot` of it instead, see the `snapshot`
+/// module.
+#[derive(Debug, Clone, Copy)]
+pub struct UnsafeList {
+    root: Option<NonNull<u8>>,
+    len: usize,
+}
+
+/// `UnsafeList` is only ever dereferenced in the unsafe `into_list`, it's
+/// safe to pass around between threads just like a `usize` would be.
+unsafe impl Send for UnsafeList {}
+unsafe impl Sync for UnsafeList {}
+
+impl UnsafeList {
+    /// Converts the `UnsafeList` into a regular `List`. Using this with
+    /// incorrect lifetimes of after the original arena has been dropped
+    /// will lead to undefined behavior. Use with extreme care.
+    pub unsafe fn into_list<'arena, T>(self) -> List<'arena, T> {
+        List {
+            root: self.root.map(|ptr| &*ptr.cast::<ListNode<'arena, T>>().as_ptr()),
+            len: self.len,
+        }
+    }
+}
+
+/// An iterator over the items in the list.
+///
+/// The iterator yields exactly as many items as the list had when the
+/// iterator was created, so once it returns `None` it will keep doing so,
+/// even if more items are pushed to the list afterwards.
+pub struct ListIter<'arena, T> {
+    next: Option<&'arena ListNode<'arena, T>>,
+    len: usize,
+}
+
+impl<'arena, T> ListIter<'arena, T> {
+    #[inline]
+    fn next_node(&mut self) -> Option<&'arena ListNode<'arena, T>> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let list_item = self.next?;
+
+        self.next = list_item.next.get();
+        self.len -= 1;
+
+        Some(list_item)
+    }
+}
+
+impl<'arena, T> Iterator for ListIter<'arena, T> {
+    type Item = &'arena T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|node| node.value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'arena, T> ExactSizeIterator for ListIter<'arena, T> {}
+
+impl<'arena, T> FusedIterator for ListIter<'arena, T> {}
+
+/// An iterator over the cells of the items in the list, see `List::iter_cells`.
+pub struct ListCellIter<'arena, T> {
+    inner: ListIter<'arena, T>,
+}
+
+impl<'arena, T> Iterator for ListCellIter<'arena, T> {
+    type Item = &'arena CopyCell<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_node().map(|node| &node.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'arena, T> ExactSizeIterator for ListCellIter<'arena, T> {}
+
+impl<'arena, T> FusedIterator for ListCellIter<'arena, T> {}
+
+/// A double-ended iterator over the items in a `DoublyList`.
+///
+/// The iterator yields exactly as many items as the list had when the
+/// iterator was created, from either end.
+pub struct DoublyListIter<'arena, T> {
+    front: Option<&'arena DoublyNode<'arena, T>>,
+    back: Option<&'arena DoublyNode<'arena, T>>,
+    len: usize,
+}
+
+impl<'arena, T> Iterator for DoublyListIter<'arena, T> {
+    type Item = &'arena T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.front?;
+
+        self.front = node.next.get();
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'arena, T> DoubleEndedIterator for DoublyListIter<'arena, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.back?;
+
+        self.back = node.prev.get();
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+}
+
+impl<'arena, T> ExactSizeIterator for DoublyListIter<'arena, T> {}
+
+impl<'arena, T> FusedIterator for DoublyListIter<'arena, T> {}
+
+/// An iterator over the items in an `UnrolledList`.
+pub struct UnrolledListIter<'arena, T> {
+    node: Option<&'arena UnrolledNode<'arena, T>>,
+    index: usize,
+    len: usize,
+}
+
+impl<'arena, T: Copy> Iterator for UnrolledListIter<'arena, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let mut node = self.node?;
+
+        if self.index == UNROLLED_CHUNK {
+            node = node.next.get()?;
+
+            self.node = Some(node);
+            self.index = 0;
+        }
+
+        let value = node.values[self.index].get();
+
+        self.index += 1;
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'arena, T: Copy> ExactSizeIterator for UnrolledListIter<'arena, T> {}
+
+impl<'arena, T: Copy> FusedIterator for UnrolledListIter<'arena, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let arena = Arena::new();
+        let mut builder = ListBuilder::new(&arena, 10);
+
+        builder.push(&arena, 20);
+        builder.push(&arena, 30);
+
+        let list = builder.as_list();
+
+        assert!(list.iter().eq([10, 20, 30].iter()));
+    }
+
+    #[test]
+    fn empty_builder() {
+        let arena = Arena::new();
+        let mut builder = GrowableList::new();
+
+        builder.push(&arena, 10);
+        builder.push(&arena, 20);
+        builder.push(&arena, 30);
+
+        let list = builder.as_list();
+
+        assert!(list.iter().eq([10, 20, 30].iter()));
+    }
+
+    #[test]
+    fn from_iter() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, [10, 20, 30].iter().cloned());
+
+        assert!(list.iter().eq([10, 20, 30].iter()));
+    }
+
+    #[test]
+    fn prepend() {
+        let arena = Arena::new();
+        let mut list = List::from(&arena, 30);
+
+        list.prepend(&arena, 20);
+        list.prepend(&arena, 10);
+
+        assert!(list.iter().eq([10, 20, 30].iter()));
+    }
+
+    #[test]
+    fn only_element() {
+        let arena = Arena::new();
+        let mut list = List::from(&arena, 42);
+
+        assert_eq!(list.only_element(), Some(&42));
+
+        list.prepend(&arena, 10);
+
+        assert_eq!(list.only_element(), None);
+    }
+
+    #[test]
+    fn shift() {
+        let arena = Arena::new();
+        let mut builder = GrowableList::new();
+
+        builder.push(&arena, 10);
+        builder.push(&arena, 20);
+        builder.push(&arena, 30);
+
+        let mut list = builder.as_list();
+
+        assert_eq!(list.shift(), Some(&10));
+
+        assert!(list.iter().eq([20, 30].iter()));
+    }
+
+    #[test]
+    fn shift_ref() {
+        let arena = Arena::new();
+        let mut builder = GrowableList::new();
+
+        builder.push(&arena, 10);
+        builder.push(&arena, 20);
         builder.push(&arena, 30);
 
-        let list = builder.as_list();
+        let list_a = builder.as_list();
+        let mut list_b = list_a;
+
+        assert_eq!(list_b.shift_ref(), Some(&10));
+
+        assert!(list_a.iter().eq([10, 20, 30].iter()));
+        assert!(list_b.iter().eq([20, 30].iter()));
+    }
+
+    #[test]
+    fn zip_in() {
+        let arena = Arena::new();
+        let a = List::from_iter(&arena, [10, 20, 30].iter().cloned());
+        let b = List::from_iter(&arena, ["foo", "bar"].iter().cloned());
+
+        assert!(a.zip_in(&arena, &b).iter().eq([(10, "foo"), (20, "bar")].iter()));
+        assert!(b.zip_in(&arena, &a).iter().eq([("foo", 10), ("bar", 20)].iter()));
+        assert!(a.zip_in(&arena, &List::<u8>::empty()).is_empty());
+    }
 
-        assert!(list.iter().eq([10, 20, 30].iter()));
+    #[test]
+    fn group_by_in() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1, 2, 3, 4, 5, 6, 7]);
+
+        let groups = list.group_by_in(&arena, |n| n % 3);
+
+        assert_eq!(groups.get(1), Some(List::from_iter(&arena, vec![1, 4, 7])));
+        assert_eq!(groups.get(2), Some(List::from_iter(&arena, vec![2, 5])));
+        assert_eq!(groups.get(0), Some(List::from_iter(&arena, vec![3, 6])));
+
+        let keys: Vec<_> = groups.iter().map(|(key, _)| *key).collect();
+
+        assert_eq!(keys, [1, 2, 0]);
     }
 
     #[test]
-    fn from_iter() {
+    fn group_by_in_empty() {
         let arena = Arena::new();
-        let list = List::from_iter(&arena, [10, 20, 30].iter().cloned());
+        let list: List<u32> = List::empty();
 
-        assert!(list.iter().eq([10, 20, 30].iter()));
+        let groups = list.group_by_in(&arena, |n| *n);
+
+        assert_eq!(groups.is_empty(), true);
     }
 
     #[test]
-    fn prepend() {
+    fn zip_with() {
         let arena = Arena::new();
-        let list = List::from(&arena, 30);
+        let a = List::from_iter(&arena, [1, 2, 3].iter().cloned());
+        let b = List::from_iter(&arena, [10, 20, 30].iter().cloned());
 
-        list.prepend(&arena, 20);
-        list.prepend(&arena, 10);
+        assert!(a.zip_with(&arena, &b, |a, b| a + b).iter().eq([11, 22, 33].iter()));
+    }
 
-        assert!(list.iter().eq([10, 20, 30].iter()));
+    #[test]
+    fn flat_map_in() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, [1, 2, 3].iter().cloned());
+
+        let flat = list.flat_map_in(&arena, |n| vec![*n; *n]);
+
+        assert!(flat.iter().eq([1, 2, 2, 3, 3, 3].iter()));
     }
 
     #[test]
-    fn only_element() {
+    fn flatten_in() {
         let arena = Arena::new();
-        let list = List::from(&arena, 42);
+        let a = List::from_iter(&arena, [1, 2].iter().cloned());
+        let b = List::from_iter(&arena, [3].iter().cloned());
+        let c = List::from_iter(&arena, [4, 5].iter().cloned());
+        let nested = List::from_iter(&arena, vec![a, List::empty(), b, c, List::empty()]);
 
-        assert_eq!(list.only_element(), Some(&42));
+        let flat = nested.flatten_in(&arena);
 
-        list.prepend(&arena, 10);
+        assert!(flat.iter().eq([1, 2, 3, 4, 5].iter()));
 
-        assert_eq!(list.only_element(), None);
+        // Nested lists are left untouched
+        assert!(a.iter().eq([1, 2].iter()));
+        assert!(b.iter().eq([3].iter()));
+
+        // The last list is shared rather than copied
+        assert!(std::ptr::eq(c.first_element().unwrap(), flat.iter().nth(3).unwrap()));
     }
 
     #[test]
-    fn shift() {
+    fn flatten_in_splices_lists() {
         let arena = Arena::new();
-        let builder = GrowableList::new();
+        let a = List::from_iter(&arena, vec![1u8, 2]);
+        let b = List::from_iter(&arena, vec![3u8]);
+        let nested = List::from_iter(&arena, vec![a, b]);
+        let flat = nested.flatten_in(&arena);
 
-        builder.push(&arena, 10);
-        builder.push(&arena, 20);
-        builder.push(&arena, 30);
+        assert_eq!(flat.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(std::ptr::eq(flat.first_element().unwrap(), a.first_element().unwrap()));
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), [1, 2]);
 
-        let list = builder.as_list();
+        // `a` is followed by `b` now, so it has to be copied the second time
+        let again = nested.flatten_in(&arena);
 
-        assert_eq!(list.shift(), Some(&10));
+        assert_eq!(again.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!std::ptr::eq(again.first_element().unwrap(), a.first_element().unwrap()));
+        assert_eq!(flat.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    }
 
-        assert!(list.iter().eq([20, 30].iter()));
+    #[test]
+    fn flatten_in_copies_shared_prefixes() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u8, 2, 3]);
+        let (init, _) = list.split_last().unwrap();
+        let twice = List::from_iter(&arena, vec![4u8, 5]);
+        let nested = List::from_iter(&arena, vec![init, twice, twice, List::from(&arena, 6)]);
+        let flat = nested.flatten_in(&arena);
+
+        assert_eq!(flat.iter().copied().collect::<Vec<_>>(), [1, 2, 4, 5, 4, 5, 6]);
+        assert_eq!(flat.len(), 7);
+        assert!(!std::ptr::eq(flat.first_element().unwrap(), list.first_element().unwrap()));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(twice.iter().copied().collect::<Vec<_>>(), [4, 5]);
     }
 
     #[test]
-    fn shift_ref() {
+    fn flatten_in_empty() {
+        let arena = Arena::new();
+        let nested: List<List<u32>> = List::from_iter(&arena, vec![List::empty(), List::empty()]);
+
+        assert!(nested.flatten_in(&arena).is_empty());
+        assert!(List::<List<u32>>::empty().flatten_in(&arena).is_empty());
+    }
+
+    #[test]
+    fn last_n_in() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, 1..=7);
+
+        assert_eq!(list.last_n_in(&arena, 3), &[&5, &6, &7]);
+        assert_eq!(list.last_n_in(&arena, 7), &[&1, &2, &3, &4, &5, &6, &7]);
+        assert_eq!(list.last_n_in(&arena, 10), &[&1, &2, &3, &4, &5, &6, &7]);
+        assert_eq!(list.last_n_in(&arena, 1), &[&7]);
+        assert!(list.last_n_in(&arena, 0).is_empty());
+        assert!(List::<u32>::empty().last_n_in(&arena, 3).is_empty());
+
+        // Huge `n` only allocates for the elements of the list
+        let offset = unsafe { arena.offset() };
+
+        assert_eq!(list.last_n_in(&arena, usize::MAX).len(), 7);
+        assert_eq!(unsafe { arena.offset() } - offset, 7 * std::mem::size_of::<&u32>());
+
+        // References point into the list itself
+        assert!(std::ptr::eq(list.last_n_in(&arena, 2)[0], list.iter().nth(5).unwrap()));
+    }
+
+    #[test]
+    fn growable_extend() {
+        let arena = Arena::new();
+        let mut list = GrowableList::new();
+
+        assert_eq!(list.extend(&arena, Vec::new()), 0);
+        assert_eq!(list.extend(&arena, vec![1, 2]), 2);
+
+        list.push(&arena, 3);
+
+        assert_eq!(list.extend(&arena, 4..7), 3);
+        assert!(list.as_list().iter().eq([1, 2, 3, 4, 5, 6].iter()));
+
+        list.push(&arena, 7);
+
+        assert_eq!(list.as_list().iter().last(), Some(&7));
+    }
+
+    #[test]
+    fn growable_append() {
+        let arena = Arena::new();
+        let mut a = GrowableList::new();
+        let mut b = GrowableList::new();
+
+        a.push(&arena, 10);
+        b.push(&arena, 20);
+        b.push(&arena, 30);
+
+        a.append(&mut b);
+        a.push(&arena, 40);
+
+        assert!(a.as_list().iter().eq([10, 20, 30, 40].iter()));
+        assert!(b.as_list().is_empty());
+
+        let mut c = GrowableList::new();
+
+        c.append(&mut a);
+
+        assert!(c.as_list().iter().eq([10, 20, 30, 40].iter()));
+        assert!(a.as_list().is_empty());
+    }
+
+    #[test]
+    fn iter_is_fused() {
         let arena = Arena::new();
-        let builder = GrowableList::new();
+        let mut builder = GrowableList::new();
 
         builder.push(&arena, 10);
-        builder.push(&arena, 20);
-        builder.push(&arena, 30);
 
-        let list_a = builder.as_list();
-        let mut list_b = list_a;
+        let mut iter = builder.as_list().iter();
 
-        assert_eq!(list_b.shift_ref(), Some(&10));
+        assert_eq!(iter.next(), Some(&10));
+        assert_eq!(iter.next(), None);
 
-        assert!(list_a.iter().eq([10, 20, 30].iter()));
-        assert!(list_b.iter().eq([20, 30].iter()));
+        builder.push(&arena, 20);
+
+        assert_eq!(iter.next(), None);
     }
 
     #[test]
@@ -438,4 +1888,438 @@ mod test {
         // ...that things are dropped in the right order
         drop(arena);
     }
+
+    #[test]
+    fn clone_in() {
+        let target = Arena::new();
+
+        let list = {
+            let source = Arena::new();
+            let inner = List::from_iter(&source, vec![1u8, 2]);
+            let list = List::from_iter(&source, vec![(source.alloc_str("foo"), inner), (source.alloc_str("bar"), List::empty())]);
+
+            list.clone_in(&target)
+        };
+
+        let (name, inner) = *list.first_element().unwrap();
+
+        assert_eq!(name, "foo");
+        assert_eq!(inner.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(list.iter().count(), 2);
+        assert!(target.contains(name));
+    }
+
+    #[test]
+    fn len() {
+        let arena = Arena::new();
+        let mut list = List::from_iter(&arena, vec![1u8, 2, 3]);
+
+        assert_eq!(list.len(), 3);
+
+        list.prepend(&arena, 0);
+        list.shift();
+        list.shift();
+
+        assert_eq!(list.len(), 2);
+
+        let mut rest = list;
+
+        rest.shift_ref();
+
+        assert_eq!((list.len(), rest.len()), (2, 1));
+
+        list.clear();
+
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+        assert_eq!(List::<u8>::empty().len(), 0);
+    }
+
+    #[test]
+    fn len_of_built_lists() {
+        let arena = Arena::new();
+        let mut growable = GrowableList::new();
+        let mut other = GrowableList::new();
+
+        growable.push(&arena, 1u8);
+        growable.extend(&arena, vec![2, 3]);
+        other.push(&arena, 4);
+        growable.append(&mut other);
+
+        let list = growable.as_list();
+
+        growable.push(&arena, 5);
+
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(growable.as_list().len(), 5);
+        assert!(other.as_list().is_empty());
+
+        let nested = List::from_iter(&arena, vec![list, List::from(&arena, 6)]);
+
+        assert_eq!(nested.flatten_in(&arena).len(), 5);
+        assert_eq!(unsafe { list.into_unsafe().into_list::<u8>() }.len(), 4);
+    }
+
+    #[test]
+    fn builders_len_and_is_empty() {
+        let arena = Arena::new();
+        let mut growable = GrowableList::new();
+        let mut other = GrowableList::new();
+
+        assert!(growable.is_empty());
+        assert_eq!(growable.len(), 0);
+
+        growable.extend(&arena, vec![1u8, 2]);
+        other.push(&arena, 3);
+        growable.append(&mut other);
+
+        assert!(!growable.is_empty());
+        assert_eq!(growable.len(), 3);
+        assert!(other.is_empty());
+
+        let mut builder = ListBuilder::new(&arena, 1u8);
+
+        assert!(!builder.is_empty());
+        assert_eq!(builder.len(), 1);
+
+        builder.push(&arena, 2);
+
+        assert_eq!(builder.len(), 2);
+    }
+
+    #[test]
+    fn iter_is_exact_size() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec!["doge", "to", "the", "moon!"]);
+        let mut iter = list.iter();
+
+        assert_eq!(iter.len(), 4);
+
+        iter.next();
+
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.by_ref().count(), 3);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn doubly_list() {
+        let arena = Arena::new();
+        let mut list = DoublyList::new();
+
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.pop_front(), None);
+
+        list.push_back(&arena, 2u8);
+        list.push_back(&arena, 3);
+        list.push_front(&arena, 1);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.first_element(), Some(&1));
+        assert_eq!(list.last_element(), Some(&3));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [3, 2, 1]);
+
+        assert_eq!(list.pop_back(), Some(&3));
+        assert_eq!(list.pop_front(), Some(&1));
+        assert_eq!(list.first_element(), list.last_element());
+        assert_eq!(list.pop_back(), Some(&2));
+        assert!(list.is_empty());
+        assert_eq!(list.first_element(), None);
+
+        list.push_front(&arena, 4);
+
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [4]);
+    }
+
+    #[test]
+    fn doubly_list_iter_from_both_ends() {
+        let arena = Arena::new();
+        let list = DoublyList::from_iter(&arena, 1..=5u32);
+        let mut iter = list.iter();
+
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn first_and_last() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u8, 2, 3]);
+
+        assert_eq!(list.first_and_last(), Some((&1, &3)));
+        assert_eq!(List::from(&arena, 4u8).first_and_last(), Some((&4, &4)));
+        assert_eq!(List::<u8>::empty().first_and_last(), None);
+        assert_eq!(DoublyList::from_iter(&arena, vec![1u8, 2]).first_and_last(), Some((&1, &2)));
+        assert_eq!(DoublyList::<u8>::new().first_and_last(), None);
+    }
+
+    #[test]
+    fn split_last() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u8, 2, 3]);
+
+        let (init, last) = list.split_last().unwrap();
+
+        assert_eq!(last, &3);
+        assert_eq!(init.len(), 2);
+        assert_eq!(init.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(list.len(), 3);
+
+        let (init, last) = List::from(&arena, 4u8).split_last().unwrap();
+
+        assert_eq!(last, &4);
+        assert!(init.is_empty());
+        assert_eq!(init.iter().next(), None);
+        assert!(List::<u8>::empty().split_last().is_none());
+    }
+
+    #[test]
+    fn retain() {
+        let arena = Arena::new();
+        let mut list = List::from_iter(&arena, 0..10u32);
+
+        list.retain(&arena, |n| n % 3 != 0);
+
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 4, 5, 7, 8]);
+
+        list.retain(&arena, |n| *n > 7);
+
+        assert_eq!(list.only_element(), Some(&8));
+
+        list.prepend(&arena, 1);
+        list.retain(&arena, |_| false);
+
+        assert!(list.is_empty());
+        assert_eq!(list.iter().next(), None);
+    }
+
+    #[test]
+    fn retain_in_prefix() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u8, 2, 3, 4]);
+        let (mut init, _) = list.split_last().unwrap();
+
+        init.retain(&arena, |n| *n != 3);
+
+        assert_eq!(init.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(init.len(), 2);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+        // Nodes past the end of the prefix aren't linked to
+        let mut next = init.root;
+        let mut linked = 0;
+
+        while let Some(node) = next {
+            linked += 1;
+            next = node.next.get();
+        }
+
+        assert_eq!(linked, 2);
+    }
+
+    #[test]
+    fn retain_leaves_copies_intact() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u8, 2, 3, 4, 5]);
+        let mut copy = list;
+
+        copy.retain(&arena, |&n| n != 2 && n != 4);
+
+        assert_eq!(copy.iter().copied().collect::<Vec<_>>(), [1, 3, 5]);
+        assert_eq!(list.iter().len(), 5);
+        assert_eq!(list.to_arena_slice(&arena), [&1, &2, &3, &4, &5]);
+
+        // Elements after the last removed one are shared
+        assert!(std::ptr::eq(copy.last_element().unwrap(), list.last_element().unwrap()));
+        assert!(!std::ptr::eq(copy.first_element().unwrap(), list.first_element().unwrap()));
+
+        let mut unchanged = list;
+
+        unchanged.retain(&arena, |_| true);
+
+        assert!(std::ptr::eq(unchanged.first_element().unwrap(), list.first_element().unwrap()));
+        assert_eq!(unchanged.len(), 5);
+    }
+
+    #[test]
+    fn indexed_access() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec!["doge", "to", "the", "moon!"]);
+
+        assert_eq!(list.get(0), Some(&"doge"));
+        assert_eq!(list.get(3), Some(&"moon!"));
+        assert_eq!(list.get(4), None);
+        assert_eq!(list.last_element(), Some(&"moon!"));
+        assert!(list.contains(&"the"));
+        assert!(!list.contains(&"sun"));
+        assert_eq!(List::<&str>::empty().last_element(), None);
+    }
+
+    #[test]
+    fn retain_recycling() {
+        let arena = Arena::new();
+        let mut list = List::from_iter(&arena, 0..10u64);
+        let mut growable = GrowableList::new();
+
+        unsafe { list.retain_recycling(&arena, |n| n % 2 == 0) };
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 2, 4, 6, 8]);
+        assert_eq!(arena.recycled_bytes(), 5 * std::mem::size_of::<ListNode<u64>>());
+
+        let offset = unsafe { arena.offset() };
+
+        growable.extend(&arena, 10..15);
+
+        assert_eq!(unsafe { arena.offset() }, offset);
+        assert_eq!(arena.recycled_bytes(), 0);
+        assert_eq!(growable.as_list().iter().copied().collect::<Vec<_>>(), [10, 11, 12, 13, 14]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn iter_cells() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec!["doge", "to", "the", "moon!"]);
+        let copy = list;
+
+        let mut cells = unsafe { list.iter_cells() };
+
+        assert_eq!(cells.len(), 4);
+
+        cells.nth(1).unwrap().set("through");
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(copy.iter().copied().collect::<Vec<_>>(), ["doge", "through", "the", "moon!"]);
+    }
+
+    #[test]
+    fn to_arena_slice() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, 0..100u32);
+        let slice = list.to_arena_slice(&arena);
+
+        assert_eq!(slice.len(), 100);
+        assert!(slice.iter().zip(list.iter()).all(|(a, b)| std::ptr::eq(*a, b)));
+        assert_eq!(list.len(), 100);
+        assert!(List::<u32>::empty().to_arena_slice(&arena).is_empty());
+    }
+
+    #[test]
+    fn map_in_and_filter_map_in() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec!["1", "two", "3", "4"]);
+
+        let mapped = list.map_in(&arena, |item| item.len());
+        let parsed = list.filter_map_in(&arena, |item| item.parse::<u8>().ok());
+
+        assert_eq!(mapped.iter().copied().collect::<Vec<_>>(), [1, 3, 1, 1]);
+        assert_eq!(mapped.len(), 4);
+        assert_eq!(parsed.iter().copied().collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(parsed.len(), 3);
+        assert!(list.filter_map_in(&arena, |_| None::<u8>).is_empty());
+    }
+
+    #[test]
+    fn unrolled_list() {
+        let arena = Arena::new();
+        let mut list = UnrolledList::new();
+
+        assert!(list.is_empty());
+        assert_eq!(list.last_element(), None);
+        assert_eq!(list.get(0), None);
+
+        for i in 0..UNROLLED_CHUNK * 3 + 1 {
+            list.push(&arena, i);
+
+            assert_eq!(list.last_element(), Some(i));
+        }
+
+        let copy = list;
+
+        list.push(&arena, 100);
+
+        assert_eq!(list.len(), UNROLLED_CHUNK * 3 + 2);
+        assert_eq!(copy.len(), UNROLLED_CHUNK * 3 + 1);
+        assert_eq!(list.first_element(), Some(0));
+        assert_eq!(list.get(UNROLLED_CHUNK * 2 + 3), Some(UNROLLED_CHUNK * 2 + 3));
+        assert_eq!(list.get(list.len()), None);
+        assert_eq!(copy.iter().collect::<Vec<_>>(), (0..UNROLLED_CHUNK * 3 + 1).collect::<Vec<_>>());
+        assert_eq!(list.iter().len(), list.len());
+        assert_eq!(list.iter().last(), Some(100));
+
+        list.clear();
+
+        assert!(list.is_empty());
+        assert_eq!(list.iter().next(), None);
+    }
+
+    #[test]
+    fn unrolled_list_eq() {
+        let arena = Arena::new();
+
+        let a = UnrolledList::from_iter(&arena, 0..10u8);
+        let b = UnrolledList::from_iter(&arena, 0..10u8);
+        let c = UnrolledList::from_iter(&arena, 0..9u8);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(format!("{:?}", c), format!("{:?}", (0..9).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn bulk_construction() {
+        let arena = Arena::new();
+
+        let list = List::from_slice(&arena, &[1u32, 2, 3]);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(List::<u32>::from_slice(&arena, &[]).is_empty());
+
+        let mut growable = GrowableList::new();
+
+        growable.push_iter(&arena, 0..0u32);
+        assert!(growable.is_empty());
+
+        growable.push_iter(&arena, 1..3u32);
+        growable.push(&arena, 3);
+        growable.push_iter(&arena, vec![4, 5]);
+
+        assert_eq!(growable.len(), 5);
+        assert_eq!(growable.as_list().iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        let mut builder = ListBuilder::new(&arena, 1u32);
+
+        builder.push_slice(&arena, &[2, 3]);
+        builder.push_slice(&arena, &[]);
+        builder.push(&arena, 4);
+
+        assert_eq!(builder.len(), 4);
+        assert_eq!(builder.as_list().iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn map_adjacent_in() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1, 4, 9, 16]);
+
+        let deltas = list.map_adjacent_in(&arena, |a, b| b - a);
+
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(deltas.iter().copied().collect::<Vec<_>>(), [3, 5, 7]);
+
+        let single = List::from_iter(&arena, vec![1]);
+
+        assert!(single.map_adjacent_in(&arena, |a, b| a + b).is_empty());
+        assert!(List::<u32>::empty().map_adjacent_in(&arena, |a, b| a + b).is_empty());
+    }
 }
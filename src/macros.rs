@@ -0,0 +1,88 @@
+/// Format a string directly onto the `Arena`, with the same syntax as
+/// `format!`. Returns an `&'arena str`.
+///
+/// ```rust
+/// # #[macro_use] extern crate toolshed;
+/// # use toolshed::Arena;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let id = 42;
+///
+/// let name: &str = arena_format!(arena, "$tmp{}", id);
+///
+/// assert_eq!(name, "$tmp42");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! arena_format {
+    ($arena:expr, $($arg:tt)*) => {
+        $arena.alloc_fmt(format_args!($($arg)*))
+    };
+}
+
+/// Create a `List` on the `Arena` from a list of elements.
+///
+/// ```rust
+/// # #[macro_use] extern crate toolshed;
+/// # use toolshed::Arena;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let list = list_in!(arena; 1, 2, 3);
+///
+/// assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! list_in {
+    ($arena:expr $(;)?) => {
+        $crate::list::List::empty()
+    };
+    ($arena:expr; $($item:expr),+ $(,)?) => {
+        $crate::list::List::from_iter(&$arena, [$($item),+])
+    };
+}
+
+/// Create a `Map` on the `Arena` from a list of `key => value` pairs.
+///
+/// ```rust
+/// # #[macro_use] extern crate toolshed;
+/// # use toolshed::Arena;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let map = map_in!(arena; "a" => 1, "b" => 2);
+///
+/// assert_eq!(map.get("a"), Some(1));
+/// assert_eq!(map.get("b"), Some(2));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! map_in {
+    ($arena:expr $(;)?) => {
+        $crate::map::Map::new()
+    };
+    ($arena:expr; $($key:expr => $value:expr),+ $(,)?) => {
+        $crate::map::Map::from_iter(&$arena, [$(($key, $value)),+])
+    };
+}
+
+/// Create a `Set` on the `Arena` from a list of elements.
+///
+/// ```rust
+/// # #[macro_use] extern crate toolshed;
+/// # use toolshed::Arena;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let set = set_in!(arena; "foo", "bar", "foo");
+///
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [&"foo", &"bar"]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! set_in {
+    ($arena:expr $(;)?) => {
+        $crate::set::Set::new()
+    };
+    ($arena:expr; $($item:expr),+ $(,)?) => {
+        $crate::set::Set::from_iter(&$arena, [$($item),+])
+    };
+}
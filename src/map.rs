@@ -1,13 +1,20 @@
 //! Maps of keys to values that can be used with the `Arena`.
 
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
 use std::hash::{Hash, Hasher};
-use rustc_hash::FxHasher;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
 
+use crate::alloc_in::CloneIn;
 use crate::cell::CopyCell;
 use crate::Arena;
 use crate::bloom::bloom;
+use crate::hash::DefaultHasher;
+use crate::snapshot::{Relocate, SnapshotWriter};
 
-#[derive(Clone, Copy)]
 struct MapNode<'arena, K, V> {
     pub key: K,
     pub hash: u64,
@@ -30,15 +37,79 @@ impl<'arena, K, V> MapNode<'arena, K, V> {
     }
 }
 
+type Slot<'arena, K, V> = CopyCell<Option<&'arena MapNode<'arena, K, V>>>;
+
+/// Find the slot in the tree starting at `node` that either holds the node
+/// for the `key`, or is where that node should be inserted.
+#[inline]
+fn find_slot<'a, 'arena, K: Eq, V>(mut node: &'a Slot<'arena, K, V>, key: K, hash: u64) -> &'a Slot<'arena, K, V> {
+    loop {
+        match node.get() {
+            None         => return node,
+            Some(parent) => {
+                if hash == parent.hash && key == parent.key {
+                    return node;
+                } else if hash < parent.hash {
+                    node = &parent.left;
+                } else {
+                    node = &parent.right;
+                }
+            }
+        }
+    }
+}
+
+/// Error returned by `Map::try_insert` and `Set::try_insert` when the key
+/// is already present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OccupiedError<V> {
+    existing: V,
+    value: V,
+}
+
+impl<V: Copy> OccupiedError<V> {
+    #[inline]
+    pub(crate) fn new(existing: V, value: V) -> Self {
+        OccupiedError { existing, value }
+    }
+
+    /// The value already in the map, or the element already in the set.
+    #[inline]
+    pub fn existing(&self) -> V {
+        self.existing
+    }
+
+    /// The value that was not inserted.
+    #[inline]
+    pub fn value(&self) -> V {
+        self.value
+    }
+}
+
+impl<V: fmt::Debug> fmt::Display for OccupiedError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "key is already occupied by {:?}, not inserting {:?}", self.existing, self.value)
+    }
+}
+
+impl<V: fmt::Debug> Error for OccupiedError<V> {}
+
 /// A map of keys `K` to values `V`. The map is built as a pseudo-random
 /// binary tree with hashes of keys used for balancing the tree nodes.
 ///
+/// The tree is never rebalanced, so its depth is only logarithmic in the
+/// number of entries in expectation, for keys with well distributed hashes.
+/// There is no worst-case bound: colliding hashes or subtrees grafted with
+/// a `MapCursor` can make it arbitrarily deep. Use `depth` to check.
+///
 /// All the nodes of the map are also linked to allow iteration in
 /// insertion order.
 #[derive(Clone, Copy)]
 pub struct Map<'arena, K, V> {
-    root: CopyCell<Option<&'arena MapNode<'arena, K, V>>>,
-    last: CopyCell<Option<&'arena MapNode<'arena, K, V>>>,
+    root: Option<&'arena MapNode<'arena, K, V>>,
+    first: Option<&'arena MapNode<'arena, K, V>>,
+    last: Option<&'arena MapNode<'arena, K, V>>,
+    len: usize,
 }
 
 impl<'arena, K, V> Default for Map<'arena, K, V> {
@@ -51,8 +122,10 @@ impl<'arena, K, V> Map<'arena, K, V> {
     /// Create a new, empty `Map`.
     pub const fn new() -> Self {
         Map {
-            root: CopyCell::new(None),
-            last: CopyCell::new(None),
+            root: None,
+            first: None,
+            last: None,
+            len: 0,
         }
     }
 }
@@ -62,20 +135,113 @@ impl<'arena, K, V> Map<'arena, K, V> {
     #[inline]
     pub fn iter(&self) -> MapIter<'arena, K, V> {
         MapIter {
-            next: self.root.get()
+            next: self.first,
+            last: self.last,
+        }
+    }
+
+    /// Get an iterator over the keys, in insertion order.
+    #[inline]
+    pub fn keys(&self) -> MapKeyIter<'arena, K, V> {
+        MapKeyIter {
+            inner: self.iter(),
+        }
+    }
+
+    /// Get an iterator over the values, in insertion order.
+    #[inline]
+    pub fn values(&self) -> MapValueIter<'arena, K, V> {
+        MapValueIter {
+            inner: self.iter(),
+        }
+    }
+
+    /// Get an iterator over the cells of the values, in insertion order,
+    /// which can be used to update the values in place.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let map = Map::from_iter(&arena, vec![("doge", 1), ("moon", 2)]);
+    ///
+    /// for cell in map.values_cells() {
+    ///     cell.set(cell.get() * 10);
+    /// }
+    ///
+    /// assert_eq!(map.values().collect::<Vec<_>>(), [10, 20]);
+    /// ```
+    #[inline]
+    pub fn values_cells(&self) -> MapValueCellIter<'arena, K, V> {
+        MapValueCellIter {
+            inner: self.iter(),
         }
     }
 
     /// Returns true if the map contains no elements.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.root.get().is_none()
+        self.root.is_none()
+    }
+
+    /// Returns the number of entries in the map, without walking it.
+    ///
+    /// The count is kept by each copy of the map, so it goes stale for copies
+    /// of a map that had entries removed through another copy, see `remove`.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of nodes on the longest path from the root of the
+    /// tree to a leaf, which bounds the number of comparisons done by any
+    /// lookup. This has to walk the whole tree.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut stack: Vec<_> = self.root.map(|root| (root, 1)).into_iter().collect();
+
+        while let Some((node, level)) = stack.pop() {
+            depth = depth.max(level);
+
+            stack.extend(node.left.get().map(|left| (left, level + 1)));
+            stack.extend(node.right.get().map(|right| (right, level + 1)));
+        }
+
+        depth
     }
 
     /// Clears the map.
     #[inline]
-    pub fn clear(&self) {
-        self.root.set(None);
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.first = None;
+        self.last = None;
+        self.len = 0;
+    }
+
+    /// Returns an `UnsafeMap` for the current `Map`. While this function is
+    /// safe itself, using `UnsafeMap` might lead to undefined behavior.
+    #[inline]
+    pub fn into_unsafe(self) -> UnsafeMap {
+        UnsafeMap {
+            root: self.root.map(|ptr| NonNull::from(ptr).cast()),
+            first: self.first.map(|ptr| NonNull::from(ptr).cast()),
+            last: self.last.map(|ptr| NonNull::from(ptr).cast()),
+            len: self.len,
+        }
+    }
+
+    /// Link the `node` at the end of the insertion order.
+    #[inline]
+    fn link_last(&mut self, node: &'arena MapNode<'arena, K, V>) {
+        match self.last {
+            Some(last) => last.next.set(Some(node)),
+            None       => self.first = Some(node),
+        }
+
+        self.last = Some(node);
+        self.len += 1;
     }
 }
 
@@ -86,7 +252,7 @@ where
 {
     #[inline]
     fn hash_key(key: &K) -> u64 {
-        let mut hasher = FxHasher::default();
+        let mut hasher = DefaultHasher::default();
 
         key.hash(&mut hasher);
 
@@ -94,49 +260,103 @@ where
     }
 
     #[inline]
-    fn find_slot(&self, key: K, hash: u64) -> &CopyCell<Option<&'arena MapNode<'arena, K, V>>> {
-        let mut node = &self.root;
+    fn find_node(&self, key: K, hash: u64) -> Option<&'arena MapNode<'arena, K, V>> {
+        find_slot(&CopyCell::new(self.root), key, hash).get()
+    }
 
-        loop {
-            match node.get() {
-                None         => return node,
-                Some(parent) => {
-                    if hash == parent.hash && key == parent.key {
-                        return node;
-                    } else if hash < parent.hash {
-                        node = &parent.left;
-                    } else {
-                        node = &parent.right;
-                    }
-                }
+    /// Find the node for a borrowed form of the key. `Borrow` requires the
+    /// borrowed form to hash the same way as the key.
+    fn find_node_by<Q>(&self, key: &Q) -> Option<&'arena MapNode<'arena, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut hasher = DefaultHasher::default();
+
+        key.hash(&mut hasher);
+
+        let hash = hasher.finish();
+        let mut next = self.root;
+
+        while let Some(node) = next {
+            if hash == node.hash && node.key.borrow() == key {
+                return Some(node);
             }
+
+            next = if hash < node.hash { node.left.get() } else { node.right.get() };
         }
+
+        None
+    }
+
+    /// Create a map from an iterator of key-value pairs. If a key repeats,
+    /// the last value for it is kept.
+    pub fn from_iter<I>(arena: &'arena Arena, source: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Map::new();
+
+        for (key, value) in source {
+            map.insert(arena, key, value);
+        }
+
+        map
     }
 
     /// Inserts a key-value pair into the map. If the key was previously set,
     /// old value is returned.
     #[inline]
-    pub fn insert(&self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
+    pub fn insert(&mut self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
         let hash = Self::hash_key(&key);
-        let node = self.find_slot(key, hash);
 
-        match node.get() {
-            Some(node) => {
-                let old = node.value.get();
-                node.value.set(value);
-                Some(old)
-            },
-            None => {
-                let new = Some(&*arena.alloc(MapNode::new(key, hash, value)));
+        self.insert_hashed(arena, key, hash, value)
+    }
 
-                if let Some(last) = self.last.get() {
-                    last.next.set(new);
-                }
+    /// Same as `insert`, but reuses a hash that has already been computed
+    /// for the key, such as one stored on a node of another map.
+    #[inline]
+    fn insert_hashed(&mut self, arena: &'arena Arena, key: K, hash: u64, value: V) -> Option<V> {
+        match self.node_or_insert_hashed(arena, key, hash, || value) {
+            (_, true)     => None,
+            (node, false) => Some(node.value.replace(value)),
+        }
+    }
 
-                self.last.set(new);
-                node.set(new);
-                None
-            }
+    /// Inserts a key-value pair into the map, unless the key was previously
+    /// set, in which case the map is left unchanged and the error holds the
+    /// existing value.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let mut map = Map::new();
+    ///
+    /// assert_eq!(map.try_insert(&arena, "x", 1), Ok(()));
+    ///
+    /// let err = map.try_insert(&arena, "x", 2).unwrap_err();
+    ///
+    /// assert_eq!(err.existing(), 1);
+    /// assert_eq!(err.value(), 2);
+    /// assert_eq!(map.get("x"), Some(1));
+    /// ```
+    #[inline]
+    pub fn try_insert(&mut self, arena: &'arena Arena, key: K, value: V) -> Result<(), OccupiedError<V>> {
+        self.try_insert_entry(arena, key, value)
+            .map_err(|(_, existing)| OccupiedError::new(existing, value))
+    }
+
+    /// Same as `try_insert`, but the error holds both the existing key and
+    /// its value.
+    #[inline]
+    pub(crate) fn try_insert_entry(&mut self, arena: &'arena Arena, key: K, value: V) -> Result<(), (K, V)> {
+        let hash = Self::hash_key(&key);
+
+        match self.node_or_insert_hashed(arena, key, hash, || value) {
+            (_, true)     => Ok(()),
+            (node, false) => Err((node.key, node.value.get())),
         }
     }
 
@@ -145,7 +365,7 @@ where
     pub fn get_key(&self, key: K) -> Option<&K> {
         let hash = Self::hash_key(&key);
 
-        self.find_slot(key, hash).get().map(|node| &node.key)
+        self.find_node(key, hash).map(|node| &node.key)
     }
 
     /// Returns the value corresponding to the key.
@@ -153,7 +373,73 @@ where
     pub fn get(&self, key: K) -> Option<V> {
         let hash = Self::hash_key(&key);
 
-        self.find_slot(key, hash).get().map(|node| node.value.get())
+        self.find_node(key, hash).map(|node| node.value.get())
+    }
+
+    /// Returns the value corresponding to a borrowed form of the key. This
+    /// allows looking up `&'arena str` keys with a `&str` that doesn't live
+    /// as long as the arena.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let map = Map::from_iter(&arena, vec![("doge", 1u32)]);
+    /// let key = String::from("doge");
+    ///
+    /// assert_eq!(map.get_by(key.as_str()), Some(1));
+    /// ```
+    #[inline]
+    pub fn get_by<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.find_node_by(key).map(|node| node.value.get())
+    }
+
+    /// Returns the key equal to a borrowed form of the key, see `get_by`.
+    #[inline]
+    pub fn get_key_by<Q>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.find_node_by(key).map(|node| &node.key)
+    }
+
+    /// Returns the value corresponding to the key, first inserting the value
+    /// returned by `f` if the key wasn't set. The tree is only descended
+    /// once, and `f` is only called if the value needs to be inserted.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let mut scope = Map::new();
+    ///
+    /// assert_eq!(scope.get_or_insert_with(&arena, "doge", || 1), 1);
+    /// assert_eq!(scope.get_or_insert_with(&arena, "doge", || 2), 1);
+    /// assert_eq!(scope.get("doge"), Some(1));
+    /// ```
+    #[inline]
+    pub fn get_or_insert_with<F>(&mut self, arena: &'arena Arena, key: K, f: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        self.node_or_insert_with(arena, key, f).value.get()
+    }
+
+    /// Same as `get_or_insert_with`, but returns the cell holding the value,
+    /// so that it can be updated without descending the tree again.
+    #[inline]
+    pub(crate) fn cell_or_insert_with<F>(&mut self, arena: &'arena Arena, key: K, f: F) -> &'arena CopyCell<V>
+    where
+        F: FnOnce() -> V,
+    {
+        &self.node_or_insert_with(arena, key, f).value
     }
 
     /// Returns true if the map contains a value for the specified key.
@@ -161,7 +447,326 @@ where
     pub fn contains_key(&self, key: K) -> bool {
         let hash = Self::hash_key(&key);
 
-        self.find_slot(key, hash).get().is_some()
+        self.find_node(key, hash).is_some()
+    }
+
+    #[inline]
+    fn get_node(&self, key: K) -> Option<&'arena MapNode<'arena, K, V>> {
+        let hash = Self::hash_key(&key);
+
+        self.find_node(key, hash)
+    }
+
+    /// Removes the key from the map, returning its value if the key was set.
+    ///
+    /// The node is taken out of the tree in the time of a lookup, but the
+    /// entries inserted before it are walked to take it out of the insertion
+    /// order, so this is O(n) in the worst case. The memory of the node stays
+    /// on the arena, unless it is removed with `remove_recycling`.
+    ///
+    /// The entry is unlinked in place, and copies of the map share its nodes.
+    /// Copies will no longer find the removed entry, while their `len` still
+    /// counts it, and can even come across entries inserted to this map
+    /// afterwards, in the tree or at the end of the insertion order. Only
+    /// remove entries from maps that aren't shared, or make a new map with
+    /// `filter_in` instead.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let mut scope = Map::from_iter(&arena, vec![("a", 1), ("b", 2), ("c", 3)]);
+    ///
+    /// assert_eq!(scope.remove("b"), Some(2));
+    /// assert_eq!(scope.remove("b"), None);
+    /// assert_eq!(scope.iter().collect::<Vec<_>>(), [(&"a", 1), (&"c", 3)]);
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        self.remove_node(key).map(|node| node.value.get())
+    }
+
+    /// Same as `remove`, but the node of the removed entry is given back to
+    /// the `arena` with `Arena::recycle`, so that it can be reused by the
+    /// nodes of entries inserted to maps afterwards.
+    ///
+    /// # Safety
+    ///
+    /// No references to the removed key may be used afterwards, and no other
+    /// map may share the nodes of this map.
+    #[inline]
+    pub unsafe fn remove_recycling(&mut self, arena: &'arena Arena, key: K) -> Option<V> {
+        let node = self.remove_node(key)?;
+        let value = node.value.get();
+
+        arena.recycle_node(node);
+
+        Some(value)
+    }
+
+    /// Unlink the node for the `key` from the tree and the insertion order.
+    fn remove_node(&mut self, key: K) -> Option<&'arena MapNode<'arena, K, V>> {
+        let hash = Self::hash_key(&key);
+        let root = CopyCell::new(self.root);
+        let slot = find_slot(&root, key, hash);
+        let node = slot.get()?;
+
+        match (node.left.get(), node.right.get()) {
+            (left, None)              => slot.set(left),
+            (None, right)             => slot.set(right),
+            (Some(left), Some(right)) => {
+                // Replace the node with the leftmost node of its right
+                // subtree, which has the smallest hash in that subtree
+                let mut successor_slot = &node.right;
+                let mut successor = right;
+
+                while let Some(next) = successor.left.get() {
+                    successor_slot = &successor.left;
+                    successor = next;
+                }
+
+                successor_slot.set(successor.right.get());
+                successor.left.set(Some(left));
+                successor.right.set(node.right.get());
+                slot.set(Some(successor));
+            },
+        }
+
+        self.root = root.get();
+
+        let mut prev = None;
+        let mut next = self.first;
+
+        while let Some(current) = next {
+            if std::ptr::eq(current, node) {
+                break;
+            }
+
+            prev = Some(current);
+            next = current.next.get();
+        }
+
+        match prev {
+            Some(prev) => prev.next.set(node.next.get()),
+            None       => self.first = node.next.get(),
+        }
+
+        if node.next.get().is_none() {
+            self.last = prev;
+        }
+
+        node.next.set(None);
+        self.len -= 1;
+
+        Some(node)
+    }
+
+    /// Get the node for the `key`, inserting a new node with the value
+    /// returned by `f` first if the key wasn't set, in a single lookup.
+    #[inline]
+    fn node_or_insert_with<F>(&mut self, arena: &'arena Arena, key: K, f: F) -> &'arena MapNode<'arena, K, V>
+    where
+        F: FnOnce() -> V,
+    {
+        let hash = Self::hash_key(&key);
+
+        self.node_or_insert_hashed(arena, key, hash, f).0
+    }
+
+    /// Same as `node_or_insert_with`, with the hash of the `key` already
+    /// computed. Also returns whether the node was inserted.
+    fn node_or_insert_hashed<F>(
+        &mut self,
+        arena: &'arena Arena,
+        key: K,
+        hash: u64,
+        f: F,
+    ) -> (&'arena MapNode<'arena, K, V>, bool)
+    where
+        F: FnOnce() -> V,
+    {
+        // The root is put in a cell, so that it can be replaced the same way
+        // as a child link of any node.
+        let root = CopyCell::new(self.root);
+        let slot = find_slot(&root, key, hash);
+
+        if let Some(node) = slot.get() {
+            return (node, false);
+        }
+
+        let new = arena.alloc_node(MapNode::new(key, hash, f()));
+
+        self.link_last(new);
+        slot.set(Some(new));
+        self.root = root.get();
+
+        (new, true)
+    }
+
+    /// Get a `MapCursor` at the root of the tree of the map.
+    #[inline]
+    pub fn cursor(&mut self) -> MapCursor<'_, 'arena, K, V> {
+        MapCursor {
+            node: self.root,
+            map: self,
+            path: Vec::new(),
+        }
+    }
+
+    /// Freeze the map into a `CompactMap` on the arena, which stores all entries
+    /// in flat slices sorted by hash. The `CompactMap` can no longer be modified,
+    /// but lookups avoid chasing pointers through the tree.
+    pub fn compact_in(&self, arena: &'arena Arena) -> CompactMap<'arena, K, V> {
+        let mut entries = Vec::new();
+        let mut next = self.first;
+
+        while let Some(node) = next {
+            entries.push((node.hash, node.key, node.value.get()));
+            next = node.next.get();
+        }
+
+        // Stable sort, so that colliding hashes keep their insertion order
+        entries.sort_by_key(|&(hash, _, _)| hash);
+
+        CompactMap {
+            keys: arena
+                .alloc_uninitialized_slice(entries.len())
+                .init_with(entries.iter().map(|&(hash, key, _)| (hash, key))),
+            values: arena
+                .alloc_uninitialized_slice(entries.len())
+                .init_with(entries.iter().map(|&(_, _, value)| value)),
+        }
+    }
+
+    /// Create a new map on the arena with the same keys as this map, and values
+    /// produced by calling `f` on every entry. Entries keep their insertion
+    /// order, and hashes of the keys are reused rather than recomputed.
+    pub fn map_values_in<W, F>(&self, arena: &'arena Arena, mut f: F) -> Map<'arena, K, W>
+    where
+        W: Copy,
+        F: FnMut(&K, V) -> W,
+    {
+        let mut map = Map::new();
+        let mut next = self.first;
+
+        while let Some(node) = next {
+            map.insert_hashed(arena, node.key, node.hash, f(&node.key, node.value.get()));
+            next = node.next.get();
+        }
+
+        map
+    }
+
+    /// Create a new map on the arena with only the entries for which `pred`
+    /// returns `true`, in insertion order. Hashes of the keys are reused
+    /// rather than recomputed, and all nodes of the new map are allocated
+    /// at once.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::map::Map;
+    ///
+    /// let arena = Arena::new();
+    /// let mut bindings = Map::new();
+    ///
+    /// bindings.insert(&arena, "main", true);
+    /// bindings.insert(&arena, "helper", false);
+    /// bindings.insert(&arena, "init", true);
+    ///
+    /// let exported = bindings.filter_in(&arena, |_, exported| exported);
+    ///
+    /// assert_eq!(exported.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["main", "init"]);
+    /// ```
+    pub fn filter_in<F>(&self, arena: &'arena Arena, mut pred: F) -> Map<'arena, K, V>
+    where
+        F: FnMut(&K, V) -> bool,
+    {
+        let mut entries = Vec::new();
+        let mut next = self.first;
+
+        while let Some(node) = next {
+            if pred(&node.key, node.value.get()) {
+                entries.push(node);
+            }
+
+            next = node.next.get();
+        }
+
+        let nodes = arena.alloc_node_slice(entries.into_iter().map(|node| {
+            MapNode::new(node.key, node.hash, node.value.get())
+        }));
+
+        let mut map = Map::new();
+        let root = CopyCell::new(None);
+
+        for node in nodes {
+            // Keys are unique, so the slot is always empty
+            find_slot(&root, node.key, node.hash).set(Some(node));
+            map.link_last(node);
+        }
+
+        map.root = root.get();
+        map
+    }
+
+    /// Returns `true` if both maps contain the same keys mapped to equal values,
+    /// regardless of the order in which they were inserted. Comparing maps with
+    /// `==`, on the other hand, also compares the order of entries.
+    ///
+    /// Hashes stored on the nodes of this map are reused for lookups in the
+    /// `other` map.
+    pub fn eq_unordered(&self, other: &Map<'_, K, V>) -> bool
+    where
+        V: PartialEq,
+    {
+        // Entries are counted rather than compared with `len`, which is
+        // stale for copies of maps that had entries removed.
+        let mut nodes = self.iter();
+        let mut len = 0;
+
+        while let Some(node) = nodes.next_node() {
+            match other.find_node(node.key, node.hash) {
+                Some(found) if found.value.get() == node.value.get() => {},
+                _ => return false,
+            }
+
+            len += 1;
+        }
+
+        len == other.iter().count()
+    }
+}
+
+impl<'arena, V: Copy> Map<'arena, &'arena str, V> {
+    /// Get an iterator over entries with keys starting with `prefix`, in
+    /// insertion order.
+    ///
+    /// This has to check every key of the map. For repeated queries, build
+    /// a `PrefixIndex` with `prefix_index_in` instead.
+    #[inline]
+    pub fn iter_prefix<'p>(&self, prefix: &'p str) -> impl Iterator<Item = (&'arena str, V)> + 'p
+    where
+        'arena: 'p,
+    {
+        self.iter()
+            .filter(move |(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (*key, value))
+    }
+
+    /// Build a `PrefixIndex` on the arena, with all entries of the map sorted
+    /// by key, allowing queries for keys by prefix in logarithmic time.
+    ///
+    /// The index is a snapshot: entries inserted into the map afterwards
+    /// are not included in it.
+    pub fn prefix_index_in(&self, arena: &'arena Arena) -> PrefixIndex<'arena, V> {
+        let mut entries: Vec<_> = self.iter().map(|(key, value)| (*key, value)).collect();
+
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        PrefixIndex {
+            entries: arena.alloc_slice(&entries),
+        }
     }
 }
 
@@ -173,7 +778,7 @@ where
 /// `HashMap`, even one with a fast hashing algorithm.
 #[derive(Clone, Copy)]
 pub struct BloomMap<'arena, K, V> {
-    filter: CopyCell<u64>,
+    filter: u64,
     inner: Map<'arena, K, V>,
 }
 
@@ -181,7 +786,7 @@ impl<'arena, K, V> BloomMap<'arena, K, V> {
     /// Create a new, empty `BloomMap`.
     pub const fn new() -> Self {
         BloomMap {
-            filter: CopyCell::new(0),
+            filter: 0,
             inner: Map::new(),
         }
     }
@@ -194,16 +799,41 @@ impl<'arena, K, V: Copy> BloomMap<'arena, K, V> {
         self.inner.iter()
     }
 
+    /// Get an iterator over the keys, in insertion order.
+    #[inline]
+    pub fn keys(&self) -> MapKeyIter<'arena, K, V> {
+        self.inner.keys()
+    }
+
+    /// Get an iterator over the values, in insertion order.
+    #[inline]
+    pub fn values(&self) -> MapValueIter<'arena, K, V> {
+        self.inner.values()
+    }
+
+    /// Get an iterator over the cells of the values, in insertion order,
+    /// which can be used to update the values in place.
+    #[inline]
+    pub fn values_cells(&self) -> MapValueCellIter<'arena, K, V> {
+        self.inner.values_cells()
+    }
+
     /// Returns true if the map contains no elements.
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
 
+    /// Returns the number of entries in the map, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
     /// Clears the map.
     #[inline]
-    pub fn clear(&self) {
-        self.filter.set(0);
+    pub fn clear(&mut self) {
+        self.filter = 0;
         self.inner.clear();
     }
 }
@@ -216,8 +846,8 @@ where
     /// Inserts a key-value pair into the map. If the key was previously set,
     /// old value is returned.
     #[inline]
-    pub fn insert(&self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
-        self.filter.set(self.filter.get() | bloom(key));
+    pub fn insert(&mut self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
+        self.filter |= bloom(key);
         self.inner.insert(arena, key, value)
     }
 
@@ -226,7 +856,7 @@ where
     pub fn get(&self, key: K) -> Option<V> {
         let b = bloom(key.as_ref());
 
-        if self.filter.get() & b == b {
+        if self.filter & b == b {
             self.inner.get(key)
         } else {
             None
@@ -238,65 +868,1143 @@ where
     pub fn contains_key(&self, key: K) -> bool {
         let b = bloom(key);
 
-        self.filter.get() & b == b && self.inner.contains_key(key)
+        self.filter & b == b && self.inner.contains_key(key)
+    }
+
+    /// Create a new `BloomMap` on the arena containing only the entries for
+    /// the given `keys`. Keys missing from this map are skipped, and are
+    /// usually rejected by the bloom filter without a tree lookup.
+    ///
+    /// The bloom filter of the new map is computed as entries are copied over,
+    /// and the hashes stored on the nodes of this map are reused.
+    pub fn subset_in<I>(&self, arena: &'arena Arena, keys: I) -> BloomMap<'arena, K, V>
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut subset = BloomMap::new();
+        let filter = self.filter;
+        let mut subset_filter = 0;
+
+        for key in keys {
+            let b = bloom(key);
+
+            if filter & b != b {
+                continue;
+            }
+
+            if let Some(node) = self.inner.get_node(key) {
+                subset_filter |= b;
+                subset.inner.insert_hashed(arena, node.key, node.hash, node.value.get());
+            }
+        }
+
+        subset.filter = subset_filter;
+        subset
     }
 }
 
-/// An iterator over the entries in the map.
-/// All entries are returned in insertion order.
-pub struct MapIter<'arena, K, V> {
-    next: Option<&'arena MapNode<'arena, K, V>>
+/// Default number of buckets of a `BigBloomMap`.
+const BIG_BLOOM_BUCKETS: usize = 64;
+
+/// A variant of the `BloomMap` for large maps, where a single 64-bit bloom
+/// filter would quickly saturate and stop rejecting absent keys.
+///
+/// Keys are split into buckets by a few bits of their hash, each bucket
+/// having its own bloom filter and its own tree. All entries are still
+/// linked together, so iteration happens in insertion order.
+///
+/// Unlike other maps, the buckets need to be allocated upfront, so creating
+/// a `BigBloomMap` requires the `Arena`. All copies of a `BigBloomMap` share
+/// the same buckets.
+#[derive(Clone, Copy)]
+pub struct BigBloomMap<'arena, K, V> {
+    filters: &'arena [CopyCell<u64>],
+    roots: &'arena [Slot<'arena, K, V>],
+    order: &'arena BigBloomOrder<'arena, K, V>,
 }
 
-impl<'arena, K, V: Copy> Iterator for MapIter<'arena, K, V> {
-    type Item = (&'arena K, V);
+/// Insertion order of the entries of a `BigBloomMap`, shared by all of its
+/// copies along with the buckets.
+struct BigBloomOrder<'arena, K, V> {
+    first: Slot<'arena, K, V>,
+    last: Slot<'arena, K, V>,
+    len: CopyCell<usize>,
+}
 
+impl<'arena, K, V> BigBloomMap<'arena, K, V> {
+    /// Create a new, empty `BigBloomMap` with 64 buckets.
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.next;
+    pub fn new(arena: &'arena Arena) -> Self {
+        Self::with_buckets(arena, BIG_BLOOM_BUCKETS)
+    }
 
-        next.map(|map_node| {
-            let item = (&map_node.key, map_node.value.get());
-            self.next = map_node.next.get();
-            item
-        })
+    /// Create a new, empty `BigBloomMap` with the given number of buckets,
+    /// which will be rounded up to the nearest power of two.
+    pub fn with_buckets(arena: &'arena Arena, buckets: usize) -> Self {
+        let buckets = buckets.max(1).next_power_of_two();
+
+        BigBloomMap {
+            filters: arena.alloc_node_slice((0..buckets).map(|_| CopyCell::new(0))),
+            roots: arena.alloc_node_slice((0..buckets).map(|_| CopyCell::new(None))),
+            order: arena.alloc_node(BigBloomOrder {
+                first: CopyCell::new(None),
+                last: CopyCell::new(None),
+                len: CopyCell::new(0),
+            }),
+        }
     }
-}
 
-impl<'arena, K, V: Copy> IntoIterator for Map<'arena, K, V> {
-    type Item = (&'arena K, V);
-    type IntoIter = MapIter<'arena, K, V>;
+    /// Get an iterator over key value pairs.
+    #[inline]
+    pub fn iter(&self) -> MapIter<'arena, K, V> {
+        MapIter {
+            next: self.order.first.get(),
+            last: self.order.last.get(),
+        }
+    }
 
+    /// Returns true if the map contains no elements.
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    pub fn is_empty(&self) -> bool {
+        self.order.first.get().is_none()
     }
-}
 
-impl<'arena, K, V: Copy> IntoIterator for BloomMap<'arena, K, V> {
-    type Item = (&'arena K, V);
-    type IntoIter = MapIter<'arena, K, V>;
+    /// Returns the number of entries in the map, without walking it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.order.len.get()
+    }
+
+    /// Clears the map.
+    pub fn clear(&self) {
+        for filter in self.filters {
+            filter.set(0);
+        }
+
+        for root in self.roots {
+            root.set(None);
+        }
+
+        self.order.first.set(None);
+        self.order.last.set(None);
+        self.order.len.set(0);
+    }
 
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    fn bucket(&self, hash: u64) -> usize {
+        // The bucket is picked with the lowest bits of the upper half of the
+        // hash. Trees compare whole hashes, so entries within a bucket are
+        // still ordered by all of their other bits.
+        ((hash >> 32) as usize) & (self.roots.len() - 1)
     }
 }
 
-impl<'arena, K, V> From<Map<'arena, K, V>> for BloomMap<'arena, K, V>
+impl<'arena, K, V> BigBloomMap<'arena, K, V>
 where
     K: Eq + Hash + Copy + AsRef<[u8]>,
     V: Copy,
 {
-    fn from(map: Map<'arena, K, V>) -> BloomMap<'arena, K, V> {
-        let mut filter = 0;
-
+    /// Inserts a key-value pair into the map. If the key was previously set,
+    /// old value is returned.
+    #[inline]
+    pub fn insert(&self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
+        let hash = Map::<K, V>::hash_key(&key);
+        let bucket = self.bucket(hash);
+        let filter = &self.filters[bucket];
+        let node = find_slot(&self.roots[bucket], key, hash);
+
+        match node.get() {
+            Some(node) => {
+                let old = node.value.get();
+                node.value.set(value);
+                Some(old)
+            },
+            None => {
+                let new = Some(arena.alloc_node(MapNode::new(key, hash, value)));
+
+                match self.order.last.get() {
+                    Some(last) => last.next.set(new),
+                    None       => self.order.first.set(new),
+                }
+
+                filter.set(filter.get() | bloom(key));
+                self.order.last.set(new);
+                self.order.len.set(self.order.len.get() + 1);
+                node.set(new);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn get_node(&self, key: K) -> Option<&'arena MapNode<'arena, K, V>> {
+        let hash = Map::<K, V>::hash_key(&key);
+        let bucket = self.bucket(hash);
+        let b = bloom(key.as_ref());
+
+        if self.filters[bucket].get() & b == b {
+            find_slot(&self.roots[bucket], key, hash).get()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<V> {
+        self.get_node(key).map(|node| node.value.get())
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get_node(key).is_some()
+    }
+}
+
+/// A read-only map produced by `Map::compact_in`. Keys along with their hashes
+/// and values are stored in two parallel slices sorted by hash, and looked up
+/// with a binary search.
+///
+/// Unlike other maps, iterating over a `CompactMap` yields entries in the
+/// order of their hashes, rather than in insertion order.
+#[derive(Clone, Copy)]
+pub struct CompactMap<'arena, K, V> {
+    keys: &'arena [(u64, K)],
+    values: &'arena [V],
+}
+
+impl<'arena, K, V: Copy> CompactMap<'arena, K, V> {
+    /// Get an iterator over key value pairs.
+    #[inline]
+    pub fn iter(&self) -> CompactMapIter<'arena, K, V> {
+        CompactMapIter {
+            keys: self.keys.iter(),
+            values: self.values.iter(),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<'arena, K, V> CompactMap<'arena, K, V>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+{
+    #[inline]
+    fn find(&self, key: K) -> Option<usize> {
+        let hash = Map::<K, V>::hash_key(&key);
+        let start = self.keys.partition_point(|&(h, _)| h < hash);
+
+        self.keys[start..]
+            .iter()
+            .take_while(|&&(h, _)| h == hash)
+            .position(|&(_, k)| k == key)
+            .map(|index| start + index)
+    }
+
+    /// Returns the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<V> {
+        self.find(key).map(|index| self.values[index])
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.find(key).is_some()
+    }
+}
+
+/// An iterator over the entries of a `CompactMap`, in order of their hashes.
+pub struct CompactMapIter<'arena, K, V> {
+    keys: std::slice::Iter<'arena, (u64, K)>,
+    values: std::slice::Iter<'arena, V>,
+}
+
+impl<'arena, K, V: Copy> Iterator for CompactMapIter<'arena, K, V> {
+    type Item = (&'arena K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, key) = self.keys.next()?;
+        let value = self.values.next()?;
+
+        Some((key, *value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'arena, K, V: Copy> FusedIterator for CompactMapIter<'arena, K, V> {}
+
+impl<'arena, K, V: Copy> IntoIterator for CompactMap<'arena, K, V> {
+    type Item = (&'arena K, V);
+    type IntoIter = CompactMapIter<'arena, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A read-only index of the entries of a `Map` with `&str` keys, produced by
+/// `Map::prefix_index_in`, allowing namespace-style queries such as all keys
+/// starting with `"React."`.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::map::Map;
+///
+/// let arena = Arena::new();
+/// let mut map = Map::new();
+///
+/// map.insert(&arena, "React.useState", 1);
+/// map.insert(&arena, "Vue.ref", 2);
+/// map.insert(&arena, "React.Component", 3);
+///
+/// let index = map.prefix_index_in(&arena);
+///
+/// assert_eq!(index.prefix("React."), &[("React.Component", 3), ("React.useState", 1)]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PrefixIndex<'arena, V> {
+    entries: &'arena [(&'arena str, V)],
+}
+
+impl<'arena, V> PrefixIndex<'arena, V> {
+    /// Get all entries with keys starting with `prefix`, sorted by key.
+    #[inline]
+    pub fn prefix(&self, prefix: &str) -> &'arena [(&'arena str, V)] {
+        let start = self.entries.partition_point(|(key, _)| *key < prefix);
+        let len = self.entries[start..].partition_point(|(key, _)| key.starts_with(prefix));
+
+        &self.entries[start..start + len]
+    }
+
+    /// Get all entries of the index, sorted by key.
+    #[inline]
+    pub fn entries(&self) -> &'arena [(&'arena str, V)] {
+        self.entries
+    }
+
+    /// Returns the number of entries in the index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the index contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A policy deciding which keys of a `PolicyMap` or `PolicySet` are the same,
+/// such as comparing strings case-insensitively. Rather than allocating a
+/// canonical copy of every key, the policy canonicalizes keys on the fly
+/// while hashing and comparing them, so lookups never touch the `Arena`.
+///
+/// Keys that are equal according to `eq_keys` must also produce the same
+/// hash in `hash_key`.
+///
+/// ```rust
+/// use std::hash::Hasher;
+/// use toolshed::Arena;
+/// use toolshed::map::{KeyPolicy, PolicyMap};
+///
+/// /// Unicode-aware, case-insensitive keys.
+/// struct Caseless;
+///
+/// impl KeyPolicy<&str> for Caseless {
+///     fn hash_key<H: Hasher>(key: &&str, state: &mut H) {
+///         for ch in key.chars().flat_map(char::to_lowercase) {
+///             state.write_u32(ch as u32);
+///         }
+///     }
+///
+///     fn eq_keys(a: &&str, b: &&str) -> bool {
+///         a.chars().flat_map(char::to_lowercase).eq(b.chars().flat_map(char::to_lowercase))
+///     }
+/// }
+///
+/// let arena = Arena::new();
+/// let mut map: PolicyMap<&str, u32, Caseless> = PolicyMap::new();
+///
+/// map.insert(&arena, "Größe", 1);
+///
+/// assert_eq!(map.get("GRÖSSE"), None);
+/// assert_eq!(map.get("größe"), Some(1));
+/// assert_eq!(map.get_key("GRÖßE"), Some(&"Größe"));
+/// ```
+pub trait KeyPolicy<K> {
+    /// Feed the canonical form of the `key` into the `state`.
+    fn hash_key<H: Hasher>(key: &K, state: &mut H);
+
+    /// Returns `true` if both keys have the same canonical form.
+    fn eq_keys(a: &K, b: &K) -> bool;
+}
+
+/// A `KeyPolicy` for strings ignoring ASCII case, such as HTML tag and
+/// attribute names or identifiers of case-insensitive languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AsciiCaseInsensitive;
+
+impl<K: AsRef<str>> KeyPolicy<K> for AsciiCaseInsensitive {
+    #[inline]
+    fn hash_key<H: Hasher>(key: &K, state: &mut H) {
+        for byte in key.as_ref().bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+
+        // Same as `str`, so that `"ab", "c"` and `"a", "bc"` don't collide in tuples
+        state.write_u8(0xff);
+    }
+
+    #[inline]
+    fn eq_keys(a: &K, b: &K) -> bool {
+        a.as_ref().eq_ignore_ascii_case(b.as_ref())
+    }
+}
+
+/// A key of a `PolicyMap`, hashed and compared using the policy `P`.
+pub(crate) struct PolicyKey<K, P> {
+    key: K,
+    marker: PhantomData<P>,
+}
+
+impl<K: Copy, P> Clone for PolicyKey<K, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: Copy, P> Copy for PolicyKey<K, P> {}
+
+impl<K, P: KeyPolicy<K>> Hash for PolicyKey<K, P> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        P::hash_key(&self.key, state);
+    }
+}
+
+impl<K, P: KeyPolicy<K>> PartialEq for PolicyKey<K, P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        P::eq_keys(&self.key, &other.key)
+    }
+}
+
+impl<K, P: KeyPolicy<K>> Eq for PolicyKey<K, P> {}
+
+impl<K, P> PolicyKey<K, P> {
+    #[inline]
+    fn new(key: K) -> Self {
+        PolicyKey {
+            key,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// A `Map` with keys hashed and compared according to a `KeyPolicy`, so that
+/// all call sites agree on which keys are the same. The map keeps the key
+/// it was first inserted with, and iterates in insertion order.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::map::{AsciiCaseInsensitive, PolicyMap};
+///
+/// let arena = Arena::new();
+/// let mut attributes: PolicyMap<&str, &str, AsciiCaseInsensitive> = PolicyMap::new();
+///
+/// attributes.insert(&arena, "onClick", "doge()");
+/// attributes.insert(&arena, "ONCLICK", "moon()");
+///
+/// assert_eq!(attributes.get("onclick"), Some("moon()"));
+/// assert_eq!(attributes.iter().collect::<Vec<_>>(), [(&"onClick", "moon()")]);
+/// ```
+pub struct PolicyMap<'arena, K, V, P> {
+    map: Map<'arena, PolicyKey<K, P>, V>,
+}
+
+impl<'arena, K: Copy, V: Copy, P> Clone for PolicyMap<'arena, K, V, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'arena, K: Copy, V: Copy, P> Copy for PolicyMap<'arena, K, V, P> {}
+
+impl<'arena, K, V, P> Default for PolicyMap<'arena, K, V, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'arena, K, V, P> PolicyMap<'arena, K, V, P> {
+    /// Create a new, empty `PolicyMap`.
+    pub const fn new() -> Self {
+        PolicyMap {
+            map: Map::new(),
+        }
+    }
+
+    /// Get an iterator over key value pairs.
+    #[inline]
+    pub fn iter(&self) -> PolicyMapIter<'arena, K, V, P> {
+        PolicyMapIter {
+            inner: self.map.iter(),
+        }
+    }
+
+    /// Returns true if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl<'arena, K, V, P> PolicyMap<'arena, K, V, P>
+where
+    K: Copy,
+    V: Copy,
+    P: KeyPolicy<K>,
+{
+    /// Inserts a key-value pair into the map. If an equivalent key was
+    /// previously set, old value is returned and the old key is kept.
+    #[inline]
+    pub fn insert(&mut self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
+        self.map.insert(arena, PolicyKey::new(key), value)
+    }
+
+    /// Same as `Map::try_insert`, comparing keys with the policy.
+    #[inline]
+    pub fn try_insert(&mut self, arena: &'arena Arena, key: K, value: V) -> Result<(), OccupiedError<V>> {
+        self.map.try_insert(arena, PolicyKey::new(key), value)
+    }
+
+    /// Returns the key stored in the map that is equivalent to the `key`.
+    #[inline]
+    pub fn get_key(&self, key: K) -> Option<&K> {
+        self.map.get_key(PolicyKey::new(key)).map(|key| &key.key)
+    }
+
+    /// Returns the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<V> {
+        self.map.get(PolicyKey::new(key))
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.map.contains_key(PolicyKey::new(key))
+    }
+}
+
+/// An iterator over the entries in a `PolicyMap`, in insertion order.
+pub struct PolicyMapIter<'arena, K, V, P> {
+    inner: MapIter<'arena, PolicyKey<K, P>, V>,
+}
+
+impl<'arena, K, V: Copy, P> Iterator for PolicyMapIter<'arena, K, V, P> {
+    type Item = (&'arena K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (&key.key, value))
+    }
+}
+
+impl<'arena, K, V: Copy, P> FusedIterator for PolicyMapIter<'arena, K, V, P> {}
+
+impl<'arena, K, V: Copy, P> IntoIterator for PolicyMap<'arena, K, V, P> {
+    type Item = (&'arena K, V);
+    type IntoIter = PolicyMapIter<'arena, K, V, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A multiset counting occurrences of keys, such as identifiers or opcodes,
+/// which iterates in order of first occurrence.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::map::Counter;
+///
+/// let arena = Arena::new();
+/// let mut counter = Counter::from_iter(&arena, "to the moon and to the stars".split(' '));
+///
+/// assert_eq!(counter.count("to"), 2);
+/// assert_eq!(counter.count("doge"), 0);
+/// assert_eq!(counter.add(&arena, "moon"), 2);
+/// assert_eq!(counter.most_common_in(&arena, 2), &[("to", 2), ("the", 2)]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Counter<'arena, K> {
+    map: Map<'arena, K, u32>,
+}
+
+impl<'arena, K> Default for Counter<'arena, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'arena, K> Counter<'arena, K> {
+    /// Create a new, empty `Counter`.
+    pub const fn new() -> Self {
+        Counter {
+            map: Map::new(),
+        }
+    }
+
+    /// Get an iterator over keys and their counts, in order of first occurrence.
+    #[inline]
+    pub fn iter(&self) -> CounterIter<'arena, K> {
+        CounterIter {
+            inner: self.map.iter(),
+        }
+    }
+
+    /// Returns true if no keys have been counted.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the counter.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<'arena, K> Counter<'arena, K>
+where
+    K: Eq + Hash + Copy,
+{
+    /// Create a counter from an iterator of keys.
+    pub fn from_iter<I>(arena: &'arena Arena, source: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut counter = Counter::new();
+
+        for key in source {
+            counter.add(arena, key);
+        }
+
+        counter
+    }
+
+    /// Count one more occurrence of the `key`, returning the new count.
+    #[inline]
+    pub fn add(&mut self, arena: &'arena Arena, key: K) -> u32 {
+        let node = self.map.node_or_insert_with(arena, key, || 0);
+        let count = node.value.get() + 1;
+
+        node.value.set(count);
+        count
+    }
+
+    /// Returns how many times the `key` has been counted.
+    #[inline]
+    pub fn count(&self, key: K) -> u32 {
+        self.map.get(key).unwrap_or(0)
+    }
+
+    /// Get up to `k` keys with the highest counts, as a slice on the arena
+    /// ordered by count. Keys with equal counts are in order of first
+    /// occurrence.
+    pub fn most_common_in(&self, arena: &'arena Arena, k: usize) -> &'arena [(K, u32)] {
+        let mut entries: Vec<(K, u32)> = self.iter().collect();
+
+        entries.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        entries.truncate(k);
+
+        arena.alloc_slice(&entries)
+    }
+}
+
+/// An iterator over the keys in a `Counter` and their counts.
+pub struct CounterIter<'arena, K> {
+    inner: MapIter<'arena, K, u32>,
+}
+
+impl<'arena, K: Copy> Iterator for CounterIter<'arena, K> {
+    type Item = (K, u32);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, count)| (*key, count))
+    }
+}
+
+impl<'arena, K: Copy> FusedIterator for CounterIter<'arena, K> {}
+
+impl<'arena, K: Copy> IntoIterator for Counter<'arena, K> {
+    type Item = (K, u32);
+    type IntoIter = CounterIter<'arena, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A cursor walking the tree of a `Map`, for implementing bulk merges or
+/// persistence layers on top of the nodes of the map.
+///
+/// Nodes with smaller hashes than their parent are on its left, all other
+/// nodes are on its right. The cursor keeps track of the path it took from
+/// the root, so it can move back up to parents.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::map::Map;
+///
+/// let arena = Arena::new();
+/// let mut map = Map::from_iter(&arena, (0..100u32).map(|n| (n, n * 2)));
+/// let mut cursor = map.cursor();
+///
+/// assert_eq!(cursor.key(), Some(&0));
+///
+/// while cursor.right() {}
+///
+/// let key = *cursor.key().unwrap();
+///
+/// assert_eq!(cursor.replace_value(0), Some(key * 2));
+/// assert_eq!(map.get(key), Some(0));
+/// ```
+pub struct MapCursor<'m, 'arena, K, V> {
+    map: &'m mut Map<'arena, K, V>,
+    path: Vec<&'arena MapNode<'arena, K, V>>,
+    node: Option<&'arena MapNode<'arena, K, V>>,
+}
+
+impl<'m, 'arena, K, V> MapCursor<'m, 'arena, K, V>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+{
+    /// Returns the key of the current node, or `None` if the map is empty.
+    #[inline]
+    pub fn key(&self) -> Option<&'arena K> {
+        self.node.map(|node| &node.key)
+    }
+
+    /// Returns the value of the current node.
+    #[inline]
+    pub fn value(&self) -> Option<V> {
+        self.node.map(|node| node.value.get())
+    }
+
+    /// Returns the hash of the key of the current node, which determines its
+    /// position in the tree.
+    #[inline]
+    pub fn hash(&self) -> Option<u64> {
+        self.node.map(|node| node.hash)
+    }
+
+    /// Number of nodes above the current node.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Move to the left child of the current node. Returns `false` and stays
+    /// in place if there is none.
+    #[inline]
+    pub fn left(&mut self) -> bool {
+        self.descend(|node| node.left.get())
+    }
+
+    /// Move to the right child of the current node. Returns `false` and
+    /// stays in place if there is none.
+    #[inline]
+    pub fn right(&mut self) -> bool {
+        self.descend(|node| node.right.get())
+    }
+
+    /// Move to the parent of the current node. Returns `false` and stays in
+    /// place at the root.
+    #[inline]
+    pub fn parent(&mut self) -> bool {
+        match self.path.pop() {
+            Some(parent) => {
+                self.node = Some(parent);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Set the value of the current node, returning the old value.
+    #[inline]
+    pub fn replace_value(&self, value: V) -> Option<V> {
+        let node = self.node?;
+        let old = node.value.get();
+
+        node.value.set(value);
+
+        Some(old)
+    }
+
+    /// Attach the tree of the `subtree` map as the left child of the current
+    /// node, appending its entries to the insertion order of the map. The
+    /// nodes are moved into the map, leaving the `subtree` empty, same as
+    /// `GrowableList::append` does.
+    ///
+    /// Returns `false` and leaves both maps unchanged if the current node
+    /// has a left child already, or if the hash of any key in the `subtree`
+    /// doesn't belong there. Keys with the same hash as an ancestor never
+    /// belong in the `subtree`, as they could duplicate the key of that
+    /// ancestor.
+    ///
+    /// Copies of the `subtree` made before grafting still share its nodes
+    /// with the map, so inserting into them would modify the map, and later
+    /// inserts into the map would show up in them. Don't use them afterwards.
+    #[inline]
+    pub fn graft_left(&mut self, subtree: &mut Map<'arena, K, V>) -> bool {
+        match self.node {
+            Some(node) => {
+                let (min, _) = self.bounds();
+
+                self.graft(&node.left, subtree, min, Some(node.hash))
+            },
+            None => false,
+        }
+    }
+
+    /// Attach the tree of the `subtree` map as the right child of the
+    /// current node, same as `graft_left` does for the left child.
+    #[inline]
+    pub fn graft_right(&mut self, subtree: &mut Map<'arena, K, V>) -> bool {
+        match self.node {
+            Some(node) => {
+                let (_, max) = self.bounds();
+
+                self.graft(&node.right, subtree, Some(node.hash), max)
+            },
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn descend<F>(&mut self, f: F) -> bool
+    where
+        F: FnOnce(&'arena MapNode<'arena, K, V>) -> Option<&'arena MapNode<'arena, K, V>>,
+    {
+        match self.node.and_then(|node| Some((node, f(node)?))) {
+            Some((node, child)) => {
+                self.path.push(node);
+                self.node = Some(child);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Range of hashes that can be added to the subtree of the current node,
+    /// between the exclusive lower and upper bounds, if there are any.
+    fn bounds(&self) -> (Option<u64>, Option<u64>) {
+        let mut min = None;
+        let mut max = None;
+
+        for (index, ancestor) in self.path.iter().enumerate() {
+            let child = self.path.get(index + 1).copied().or(self.node);
+
+            match child {
+                Some(child) if child.hash < ancestor.hash => max = Some(ancestor.hash),
+                _                                         => min = Some(ancestor.hash),
+            }
+        }
+
+        (min, max)
+    }
+
+    fn graft(&mut self, slot: &Slot<'arena, K, V>, subtree: &mut Map<'arena, K, V>, min: Option<u64>, max: Option<u64>) -> bool {
+        let (root, last) = match (subtree.root, subtree.last) {
+            (Some(root), Some(last)) if slot.get().is_none() => (root, last),
+            _                                                => return false,
+        };
+
+        let mut next = subtree.first;
+
+        while let Some(node) = next {
+            if min.is_some_and(|min| node.hash <= min) || max.is_some_and(|max| node.hash >= max) {
+                return false;
+            }
+
+            next = node.next.get();
+        }
+
+        slot.set(Some(root));
+
+        match self.map.last {
+            Some(tail) => tail.next.set(subtree.first),
+            None       => self.map.first = subtree.first,
+        }
+
+        self.map.last = Some(last);
+        self.map.len += subtree.len;
+
+        *subtree = Map::new();
+
+        true
+    }
+}
+
+/// An iterator over the entries in the map.
+/// All entries are returned in insertion order.
+///
+/// Only the entries in the map at the time the iterator was created are
+/// returned, entries inserted to the map afterwards are not. The iterator
+/// stops at the last entry of the map rather than counting the entries, so
+/// it isn't exact-sized: copies of a map that had entries removed through
+/// another copy can't know how many of them are left, see `Map::remove`.
+pub struct MapIter<'arena, K, V> {
+    next: Option<&'arena MapNode<'arena, K, V>>,
+    last: Option<&'arena MapNode<'arena, K, V>>,
+}
+
+impl<'arena, K, V> MapIter<'arena, K, V> {
+    #[inline]
+    fn next_node(&mut self) -> Option<&'arena MapNode<'arena, K, V>> {
+        let map_node = self.next?;
+
+        self.next = match self.last {
+            Some(last) if std::ptr::eq(map_node, last) => None,
+            _                                          => map_node.next.get(),
+        };
+
+        Some(map_node)
+    }
+}
+
+impl<'arena, K, V: Copy> Iterator for MapIter<'arena, K, V> {
+    type Item = (&'arena K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|map_node| (&map_node.key, map_node.value.get()))
+    }
+}
+
+impl<'arena, K, V: Copy> FusedIterator for MapIter<'arena, K, V> {}
+
+/// An iterator over the keys in the map, in insertion order.
+pub struct MapKeyIter<'arena, K, V> {
+    inner: MapIter<'arena, K, V>,
+}
+
+impl<'arena, K, V> Iterator for MapKeyIter<'arena, K, V> {
+    type Item = &'arena K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_node().map(|map_node| &map_node.key)
+    }
+}
+
+impl<'arena, K, V> FusedIterator for MapKeyIter<'arena, K, V> {}
+
+/// An iterator over the values in the map, in insertion order.
+pub struct MapValueIter<'arena, K, V> {
+    inner: MapIter<'arena, K, V>,
+}
+
+impl<'arena, K, V: Copy> Iterator for MapValueIter<'arena, K, V> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_node().map(|map_node| map_node.value.get())
+    }
+}
+
+impl<'arena, K, V: Copy> FusedIterator for MapValueIter<'arena, K, V> {}
+
+/// An iterator over the cells of the values in the map, in insertion order,
+/// see `Map::values_cells`.
+pub struct MapValueCellIter<'arena, K, V> {
+    inner: MapIter<'arena, K, V>,
+}
+
+impl<'arena, K, V> Iterator for MapValueCellIter<'arena, K, V> {
+    type Item = &'arena CopyCell<V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_node().map(|map_node| &map_node.value)
+    }
+}
+
+impl<'arena, K, V> FusedIterator for MapValueCellIter<'arena, K, V> {}
+
+impl<'arena, K, V: Copy> IntoIterator for Map<'arena, K, V> {
+    type Item = (&'arena K, V);
+    type IntoIter = MapIter<'arena, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'arena, K, V: Copy> IntoIterator for BloomMap<'arena, K, V> {
+    type Item = (&'arena K, V);
+    type IntoIter = MapIter<'arena, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'arena, K, V: Copy> IntoIterator for BigBloomMap<'arena, K, V> {
+    type Item = (&'arena K, V);
+    type IntoIter = MapIter<'arena, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Unsafe variant of the `Map` that erases any lifetime information, same
+/// as `UnsafeList` does for `List`.
+#[derive(Debug, Clone, Copy)]
+pub struct UnsafeMap {
+    root: Option<NonNull<u8>>,
+    first: Option<NonNull<u8>>,
+    last: Option<NonNull<u8>>,
+    len: usize,
+}
+
+/// `UnsafeMap` is only ever dereferenced in the unsafe `into_map`, it's
+/// safe to pass around between threads just like a `usize` would be.
+unsafe impl Send for UnsafeMap {}
+unsafe impl Sync for UnsafeMap {}
+
+impl UnsafeMap {
+    /// Converts the `UnsafeMap` into a regular `Map`.
+    ///
+    /// # Safety
+    ///
+    /// Using this with incorrect types, lifetimes or after the original arena
+    /// has been dropped will lead to undefined behavior. Use with extreme care.
+    pub unsafe fn into_map<'arena, K, V>(self) -> Map<'arena, K, V> {
+        Map {
+            root: self.root.map(|ptr| &*ptr.cast::<MapNode<'arena, K, V>>().as_ptr()),
+            first: self.first.map(|ptr| &*ptr.cast::<MapNode<'arena, K, V>>().as_ptr()),
+            last: self.last.map(|ptr| &*ptr.cast::<MapNode<'arena, K, V>>().as_ptr()),
+            len: self.len,
+        }
+    }
+}
+
+/// Entries are cloned in insertion order into a new `Map` on the target
+/// `Arena`. Hashes are recomputed for the cloned keys.
+impl<'arena, 'target, K, V> CloneIn<'target> for Map<'arena, K, V>
+where
+    K: CloneIn<'target>,
+    K::Cloned: Eq + Hash,
+    V: CloneIn<'target> + Copy,
+{
+    type Cloned = Map<'target, K::Cloned, V::Cloned>;
+
+    fn clone_in(&self, arena: &'target Arena) -> Self::Cloned {
+        let mut map = Map::new();
+        let mut next = self.first;
+
+        while let Some(node) = next {
+            map.insert(arena, node.key.clone_in(arena), node.value.get().clone_in(arena));
+            next = node.next.get();
+        }
+
+        map
+    }
+}
+
+unsafe impl<'arena, K: Relocate, V: Relocate + Copy> Relocate for MapNode<'arena, K, V> {
+    type Relocated<'r> = MapNode<'r, K::Relocated<'r>, V::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.key);
+        out.write_field(at, self, &self.hash);
+        out.write_field(at, self, &self.value);
+        out.write_field(at, self, &self.left);
+        out.write_field(at, self, &self.right);
+        out.write_field(at, self, &self.next);
+    }
+}
+
+unsafe impl<'arena, K: Relocate, V: Relocate + Copy> Relocate for Map<'arena, K, V> {
+    type Relocated<'r> = Map<'r, K::Relocated<'r>, V::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.root);
+        out.write_field(at, self, &self.first);
+        out.write_field(at, self, &self.last);
+        out.write_field(at, self, &self.len);
+    }
+}
+
+unsafe impl<'arena, K: Relocate, V: Relocate + Copy> Relocate for BloomMap<'arena, K, V> {
+    type Relocated<'r> = BloomMap<'r, K::Relocated<'r>, V::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.filter);
+        out.write_field(at, self, &self.inner);
+    }
+}
+
+impl<'arena, K, V> From<Map<'arena, K, V>> for BloomMap<'arena, K, V>
+where
+    K: Eq + Hash + Copy + AsRef<[u8]>,
+    V: Copy,
+{
+    fn from(map: Map<'arena, K, V>) -> BloomMap<'arena, K, V> {
+        let mut filter = 0;
+
         for (key, _) in map.iter() {
             filter |= bloom(key.as_ref());
         }
 
         BloomMap {
-            filter: CopyCell::new(filter),
+            filter,
             inner: map,
         }
     }
@@ -316,7 +2024,105 @@ mod test {
     #[test]
     fn map() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, "foo", 10u64);
+        map.insert(&arena, "bar", 20);
+        map.insert(&arena, "doge", 30);
+
+        assert_eq!(map.contains_key("foo"), true);
+        assert_eq!(map.contains_key("bar"), true);
+        assert_eq!(map.contains_key("doge"), true);
+        assert_eq!(map.contains_key("moon"), false);
+
+        assert_eq!(map.get("foo"), Some(10));
+        assert_eq!(map.get("bar"), Some(20));
+        assert_eq!(map.get("doge"), Some(30));
+        assert_eq!(map.get("moon"), None);
+    }
+
+    #[test]
+    fn iter_prefix() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, "React.useState", 1);
+        map.insert(&arena, "Vue.ref", 2);
+        map.insert(&arena, "React.Component", 3);
+        map.insert(&arena, "React", 4);
+
+        let react: Vec<_> = map.iter_prefix("React.").collect();
+
+        assert_eq!(react, [("React.useState", 1), ("React.Component", 3)]);
+        assert_eq!(map.iter_prefix("Angular").count(), 0);
+        assert_eq!(map.iter_prefix("").count(), 4);
+    }
+
+    #[test]
+    fn prefix_index_in() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        for (value, key) in ["b.x", "a", "b", "b.y", "ba", "c.z", "b.a"].iter().enumerate() {
+            map.insert(&arena, *key, value);
+        }
+
+        let index = map.prefix_index_in(&arena);
+
+        assert_eq!(index.len(), 7);
+        assert_eq!(index.prefix("b."), &[("b.a", 6), ("b.x", 0), ("b.y", 3)]);
+        assert_eq!(index.prefix("b"), &[("b", 2), ("b.a", 6), ("b.x", 0), ("b.y", 3), ("ba", 4)]);
+        assert_eq!(index.prefix("c.z"), &[("c.z", 5)]);
+        assert!(index.prefix("d").is_empty());
+        assert!(index.prefix("c.zz").is_empty());
+        assert_eq!(index.prefix("").len(), 7);
+
+        map.insert(&arena, "b.b", 7);
+
+        assert_eq!(index.prefix("b.").len(), 3);
+    }
+
+    #[test]
+    fn iter_is_fused() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, "foo", 10u64);
+
+        let mut iter = map.iter();
+
+        assert_eq!(iter.next(), Some((&"foo", 10)));
+        assert_eq!(iter.next(), None);
+
+        map.insert(&arena, "bar", 20);
+
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bloom_map() {
+        let arena = Arena::new();
+        let mut map = BloomMap::new();
+
+        map.insert(&arena, "foo", 10u64);
+        map.insert(&arena, "bar", 20);
+        map.insert(&arena, "doge", 30);
+
+        assert_eq!(map.contains_key("foo"), true);
+        assert_eq!(map.contains_key("bar"), true);
+        assert_eq!(map.contains_key("doge"), true);
+        assert_eq!(map.contains_key("moon"), false);
+
+        assert_eq!(map.get("foo"), Some(10));
+        assert_eq!(map.get("bar"), Some(20));
+        assert_eq!(map.get("doge"), Some(30));
+        assert_eq!(map.get("moon"), None);
+    }
+
+    #[test]
+    fn big_bloom_map() {
+        let arena = Arena::new();
+        let map = BigBloomMap::with_buckets(&arena, 4);
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -331,32 +2137,100 @@ mod test {
         assert_eq!(map.get("bar"), Some(20));
         assert_eq!(map.get("doge"), Some(30));
         assert_eq!(map.get("moon"), None);
+
+        assert_eq!(map.insert(&arena, "bar", 42), Some(20));
+
+        let mut iter = map.iter();
+
+        assert_eq!(iter.next(), Some((&"foo", 10)));
+        assert_eq!(iter.next(), Some((&"bar", 42)));
+        assert_eq!(iter.next(), Some((&"doge", 30)));
+        assert_eq!(iter.next(), None);
+
+        map.clear();
+
+        assert_eq!(map.is_empty(), true);
+        assert_eq!(map.get("foo"), None);
+    }
+
+    #[test]
+    fn big_bloom_map_many_keys() {
+        let arena = Arena::new();
+        let map = BigBloomMap::new(&arena);
+        let keys: Vec<String> = (0..1000).map(|n| format!("key{}", n)).collect();
+
+        for (n, key) in keys.iter().enumerate() {
+            map.insert(&arena, key.as_str(), n);
+        }
+
+        for (n, key) in keys.iter().enumerate() {
+            assert_eq!(map.get(key.as_str()), Some(n));
+        }
+
+        assert_eq!(map.get("key1000"), None);
+        assert!(map.iter().map(|(_, n)| n).eq(0..1000));
+
+        // Buckets with their own filters are far from saturated
+        let saturated = map.filters.iter().filter(|filter| filter.get() == u64::MAX).count();
+
+        assert_eq!(saturated, 0);
+    }
+
+    #[test]
+    fn compact_in() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        for n in 0..100u64 {
+            map.insert(&arena, n, n * 10);
+        }
+
+        let compact = map.compact_in(&arena);
+
+        assert_eq!(compact.len(), 100);
+
+        for n in 0..100u64 {
+            assert_eq!(compact.get(n), Some(n * 10));
+        }
+
+        assert_eq!(compact.get(100), None);
+        assert_eq!(compact.contains_key(42), true);
+        assert_eq!(compact.contains_key(1000), false);
+
+        let mut entries: Vec<_> = compact.iter().map(|(key, value)| (*key, value)).collect();
+
+        entries.sort();
+
+        assert!(entries.into_iter().eq((0..100).map(|n| (n, n * 10))));
     }
 
     #[test]
-    fn bloom_map() {
+    fn compact_in_empty() {
         let arena = Arena::new();
-        let map = BloomMap::new();
+        let map: Map<&str, u64> = Map::new();
 
-        map.insert(&arena, "foo", 10u64);
-        map.insert(&arena, "bar", 20);
-        map.insert(&arena, "doge", 30);
+        let compact = map.compact_in(&arena);
 
-        assert_eq!(map.contains_key("foo"), true);
-        assert_eq!(map.contains_key("bar"), true);
-        assert_eq!(map.contains_key("doge"), true);
-        assert_eq!(map.contains_key("moon"), false);
+        assert_eq!(compact.is_empty(), true);
+        assert_eq!(compact.get("foo"), None);
+    }
 
-        assert_eq!(map.get("foo"), Some(10));
-        assert_eq!(map.get("bar"), Some(20));
-        assert_eq!(map.get("doge"), Some(30));
-        assert_eq!(map.get("moon"), None);
+    #[test]
+    fn from_iter() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, vec![("foo", 10u64), ("bar", 20), ("foo", 30)]);
+
+        let mut iter = map.iter();
+
+        assert_eq!(iter.next(), Some((&"foo", 30)));
+        assert_eq!(iter.next(), Some((&"bar", 20)));
+        assert_eq!(iter.next(), None);
     }
 
     #[test]
     fn iter() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -373,7 +2247,7 @@ mod test {
     #[test]
     fn insert_replace() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
 
         map.insert(&arena, "foo", 10u64);
         map.insert(&arena, "bar", 20);
@@ -396,16 +2270,55 @@ mod test {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn eq_unordered() {
+        let arena = Arena::new();
+        let a = Map::from_iter(&arena, vec![("foo", 1), ("bar", 2), ("doge", 3)]);
+        let b = Map::from_iter(&arena, vec![("doge", 3), ("foo", 1), ("bar", 2)]);
+        let c = Map::from_iter(&arena, vec![("doge", 3), ("foo", 1), ("bar", 20)]);
+        let d = Map::from_iter(&arena, vec![("doge", 3), ("foo", 1)]);
+
+        assert_ne!(a, b);
+        assert!(a.eq_unordered(&b));
+        assert!(b.eq_unordered(&a));
+        assert!(!a.eq_unordered(&c));
+        assert!(!a.eq_unordered(&d));
+        assert!(!d.eq_unordered(&a));
+        assert!(Map::<&str, u8>::new().eq_unordered(&Map::new()));
+    }
+
+    #[test]
+    fn map_values_in() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, "foo", 10u64);
+        map.insert(&arena, "bar", 20);
+        map.insert(&arena, "doge", 30);
+
+        let mapped = map.map_values_in(&arena, |key, value| (key.len(), value * 2));
+
+        let mut iter = mapped.iter();
+
+        assert_eq!(iter.next(), Some((&"foo", (3, 20))));
+        assert_eq!(iter.next(), Some((&"bar", (3, 40))));
+        assert_eq!(iter.next(), Some((&"doge", (4, 60))));
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(mapped.get("doge"), Some((4, 60)));
+        assert_eq!(mapped.get("moon"), None);
+    }
+
     #[test]
     fn from_eq() {
         let arena = Arena::new();
-        let map = Map::new();
+        let mut map = Map::new();
 
         map.insert(&arena, "foo", 10);
         map.insert(&arena, "bar", 20);
         map.insert(&arena, "doge", 30);
 
-        let bloom_map = BloomMap::new();
+        let mut bloom_map = BloomMap::new();
 
         bloom_map.insert(&arena, "foo", 10);
         bloom_map.insert(&arena, "bar", 20);
@@ -414,4 +2327,495 @@ mod test {
         assert_eq!(map, Map::from(bloom_map));
         assert_eq!(BloomMap::from(map), bloom_map);
     }
+
+    #[test]
+    fn bloom_map_subset_in() {
+        let arena = Arena::new();
+        let mut map = BloomMap::new();
+
+        map.insert(&arena, "foo", 10u64);
+        map.insert(&arena, "bar", 20);
+        map.insert(&arena, "doge", 30);
+
+        let subset = map.subset_in(&arena, ["doge", "moon", "foo"].iter().cloned());
+
+        assert_eq!(subset.get("foo"), Some(10));
+        assert_eq!(subset.get("doge"), Some(30));
+        assert_eq!(subset.get("bar"), None);
+        assert_eq!(subset.get("moon"), None);
+
+        let mut iter = subset.iter();
+
+        assert_eq!(iter.next(), Some((&"doge", 30)));
+        assert_eq!(iter.next(), Some((&"foo", 10)));
+        assert_eq!(iter.next(), None);
+
+        // Filter only carries the keys of the subset
+        assert_eq!(subset.filter, bloom("doge") | bloom("foo"));
+    }
+
+    #[test]
+    fn try_insert() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        assert_eq!(map.try_insert(&arena, "foo", 10), Ok(()));
+        assert_eq!(map.try_insert(&arena, "bar", 20), Ok(()));
+        assert_eq!(map.try_insert(&arena, "foo", 30), Err(OccupiedError::new(10, 30)));
+        assert_eq!(map.get("foo"), Some(10));
+        assert_eq!(map.iter().count(), 2);
+        assert_eq!(OccupiedError::new(10, 30).to_string(), "key is already occupied by 10, not inserting 30");
+    }
+
+    #[test]
+    fn clone_in() {
+        let target = Arena::new();
+
+        let map = {
+            let source = Arena::new();
+            let mut map = Map::new();
+
+            map.insert(&source, source.alloc_str("foo"), 10u32);
+            map.insert(&source, source.alloc_str("bar"), 20);
+
+            map.clone_in(&target)
+        };
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"foo", 10), (&"bar", 20)]);
+        assert_eq!(map.get("bar"), Some(20));
+        assert!(target.contains(*map.get_key("foo").unwrap()));
+    }
+
+    #[test]
+    fn policy_map() {
+        let arena = Arena::new();
+        let mut map: PolicyMap<&str, u32, AsciiCaseInsensitive> = PolicyMap::new();
+
+        assert_eq!(map.insert(&arena, "Content-Type", 1), None);
+        assert_eq!(map.insert(&arena, "content-type", 2), Some(1));
+        assert_eq!(map.try_insert(&arena, "CONTENT-TYPE", 3), Err(OccupiedError::new(2, 3)));
+        assert_eq!(map.insert(&arena, "Accept", 4), None);
+
+        assert_eq!(map.get("CONTENT-type"), Some(2));
+        assert_eq!(map.get_key("accept"), Some(&"Accept"));
+        assert!(!map.contains_key("Host"));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"Content-Type", 2), (&"Accept", 4)]);
+
+        map.clear();
+
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn ascii_case_insensitive_hash() {
+        fn hash(key: &str) -> u64 {
+            let mut hasher = DefaultHasher::default();
+
+            <AsciiCaseInsensitive as KeyPolicy<&str>>::hash_key(&key, &mut hasher);
+
+            hasher.finish()
+        }
+
+        assert_eq!(hash("FooBar"), hash("fOObAR"));
+        assert_ne!(hash("foo"), hash("bar"));
+    }
+
+    #[test]
+    fn filter_in() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, (0..100u32).map(|n| (n, n * 2)));
+
+        let mut even = map.filter_in(&arena, |key, _| key % 2 == 0);
+
+        assert_eq!(even.iter().count(), 50);
+        assert_eq!(even.get(42), Some(84));
+        assert_eq!(even.get(43), None);
+        assert!(even.iter().map(|(key, _)| *key).eq((0..100).step_by(2)));
+
+        even.insert(&arena, 43, 0);
+
+        assert_eq!(even.get(43), Some(0));
+        assert_eq!(map.get(43), Some(86));
+        assert!(map.filter_in(&arena, |_, _| false).is_empty());
+    }
+
+    #[test]
+    fn counter() {
+        let arena = Arena::new();
+        let mut counter = Counter::new();
+
+        assert!(counter.is_empty());
+        assert_eq!(counter.most_common_in(&arena, 3), &[]);
+
+        for op in ["push", "pop", "add", "push", "call", "push", "add"] {
+            counter.add(&arena, op);
+        }
+
+        assert!(!counter.is_empty());
+        assert_eq!(counter.count("push"), 3);
+        assert_eq!(counter.count("jump"), 0);
+        assert_eq!(counter.iter().collect::<Vec<_>>(), [("push", 3), ("pop", 1), ("add", 2), ("call", 1)]);
+        assert_eq!(counter.most_common_in(&arena, 3), &[("push", 3), ("add", 2), ("pop", 1)]);
+        assert_eq!(counter.most_common_in(&arena, 10).len(), 4);
+
+        counter.clear();
+
+        assert_eq!(counter.count("push"), 0);
+    }
+
+    #[test]
+    fn get_by_borrowed_key() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, (0..100u32).map(|n| (arena.alloc_str(&n.to_string()), n)));
+
+        for n in 0..100u32 {
+            let key = n.to_string();
+
+            assert_eq!(map.get_by(key.as_str()), Some(n));
+            assert_eq!(map.get_key_by(key.as_str()), Some(&key.as_str()));
+        }
+
+        assert_eq!(map.get_by("100"), None);
+        assert_eq!(Map::<&str, u32>::new().get_by("0"), None);
+    }
+
+    #[test]
+    fn cursor_walks_the_tree() {
+        let arena = Arena::new();
+        let mut map = Map::from_iter(&arena, (0..1000u32).map(|n| (n, n)));
+        let mut cursor = map.cursor();
+
+        assert!(!cursor.parent());
+        assert_eq!(cursor.key(), Some(&0));
+
+        let root = cursor.hash().unwrap();
+
+        // Smaller hashes are on the left, either child may be missing
+        if cursor.left() {
+            assert!(cursor.hash().unwrap() < root);
+        } else {
+            assert!(cursor.right());
+            assert!(cursor.hash().unwrap() >= root);
+        }
+
+        assert!(cursor.left() || cursor.right());
+        assert_eq!(cursor.depth(), 2);
+
+        let key = *cursor.key().unwrap();
+
+        assert_eq!(cursor.replace_value(5000), Some(key));
+
+        assert!(cursor.parent());
+        assert!(cursor.parent());
+        assert_eq!(cursor.hash(), Some(root));
+        assert_eq!(map.get(key), Some(5000));
+
+        let mut empty: Map<u32, u32> = Map::new();
+        let mut cursor = empty.cursor();
+
+        assert_eq!(cursor.key(), None);
+        assert!(!cursor.left());
+        assert_eq!(cursor.replace_value(1), None);
+    }
+
+    #[test]
+    fn cursor_grafts_subtrees() {
+        let arena = Arena::new();
+        let mut map = Map::from_iter(&arena, vec![(1u32, "a")]);
+        let root = map.cursor().hash().unwrap();
+
+        let (lower, upper): (Vec<u32>, Vec<u32>) = (2..200).partition(|n| Map::<u32, &str>::hash_key(n) < root);
+
+        let mut left = Map::from_iter(&arena, lower.iter().map(|&n| (n, "l")));
+        let mut right = Map::from_iter(&arena, upper.iter().map(|&n| (n, "r")));
+        let mut duplicate = Map::from_iter(&arena, vec![(1u32, "b")]);
+
+        let mut cursor = map.cursor();
+
+        // Hashes on the wrong side
+        assert!(!cursor.graft_left(&mut right));
+        assert!(!cursor.graft_right(&mut left));
+
+        // Same hash as the current node, and the same key
+        assert!(!cursor.graft_right(&mut duplicate));
+        assert_eq!(duplicate.len(), 1);
+
+        assert!(cursor.graft_left(&mut left));
+        assert!(cursor.graft_right(&mut right));
+
+        // Grafted nodes are moved into the map
+        assert!(left.is_empty());
+        assert!(right.is_empty());
+
+        let mut left = Map::from_iter(&arena, lower.iter().map(|&n| (n, "l")));
+
+        // Children are taken now
+        assert!(!cursor.graft_left(&mut left));
+
+        assert_eq!(map.iter().count(), 199);
+        assert_eq!(map.get(1), Some("a"));
+
+        for &n in &lower {
+            assert_eq!(map.get(n), Some("l"));
+        }
+
+        for &n in &upper {
+            assert_eq!(map.get(n), Some("r"));
+        }
+
+        assert_eq!(map.iter().nth(1).map(|(key, _)| *key), lower.first().copied());
+        assert_eq!(map.iter().last().map(|(key, _)| *key), upper.last().copied());
+    }
+
+    #[test]
+    fn unsafe_map() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, vec![("foo", 1u32), ("bar", 2)]);
+
+        let raw = map.into_unsafe();
+        let mut map: Map<&str, u32> = unsafe { raw.into_map() };
+
+        assert_eq!(map.get("bar"), Some(2));
+
+        // Insertion order is kept, with new entries appended
+        map.insert(&arena, "baz", 3);
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"foo", 1), (&"bar", 2), (&"baz", 3)]);
+
+        let empty: Map<&str, u32> = unsafe { Map::<&str, u32>::new().into_unsafe().into_map() };
+
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn remove() {
+        let arena = Arena::new();
+        let mut map = Map::from_iter(&arena, (0..64u32).map(|n| (n, n * 10)));
+
+        // Root, which also heads the insertion order
+        assert_eq!(map.remove(0), Some(0));
+        // Last inserted
+        assert_eq!(map.remove(63), Some(630));
+        // Everything else in between, hitting nodes with any number of children
+        for n in (1..63).step_by(3) {
+            assert_eq!(map.remove(n), Some(n * 10));
+        }
+
+        assert_eq!(map.remove(0), None);
+        assert_eq!(map.remove(100), None);
+
+        let expected: Vec<u32> = (1..63).filter(|n| (n - 1) % 3 != 0).collect();
+
+        assert_eq!(map.iter().map(|(key, _)| *key).collect::<Vec<_>>(), expected);
+
+        for n in 0..64 {
+            assert_eq!(map.get(n), expected.contains(&n).then_some(n * 10));
+        }
+
+        map.insert(&arena, 0, 1);
+
+        assert_eq!(map.iter().last(), Some((&0, 1)));
+
+        for n in expected.iter().copied().chain(Some(0)) {
+            assert!(map.remove(n).is_some());
+        }
+
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+
+        map.insert(&arena, 7, 70);
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&7, 70)]);
+    }
+
+    #[test]
+    fn remove_recycling() {
+        let arena = Arena::new();
+        let mut map = Map::from_iter(&arena, (0..10u64).map(|n| (n, n * 10)));
+
+        for n in (1..10).step_by(2) {
+            assert_eq!(unsafe { map.remove_recycling(&arena, n) }, Some(n * 10));
+        }
+
+        assert_eq!(unsafe { map.remove_recycling(&arena, 1) }, None);
+        assert_eq!(arena.recycled_bytes(), 5 * std::mem::size_of::<MapNode<u64, u64>>());
+
+        let offset = unsafe { arena.offset() };
+
+        for n in 10..15 {
+            map.insert(&arena, n, n * 10);
+        }
+
+        assert_eq!(unsafe { arena.offset() }, offset);
+        assert_eq!(arena.recycled_bytes(), 0);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 4, 6, 8, 10, 11, 12, 13, 14]);
+
+        for n in 0..15 {
+            assert_eq!(map.get(n), (n % 2 == 0 || n >= 10).then_some(n * 10));
+        }
+    }
+
+    #[test]
+    fn len() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        assert_eq!(map.len(), 0);
+
+        map.insert(&arena, "foo", 1);
+        map.insert(&arena, "bar", 2);
+        map.insert(&arena, "foo", 3);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.iter().count(), 2);
+
+        let mut iter = map.iter();
+        iter.next();
+        map.insert(&arena, "doge", 4);
+
+        // The iterator keeps to the entries it was created with
+        assert_eq!(iter.collect::<Vec<_>>(), [(&"bar", 2)]);
+
+        map.remove("foo");
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.iter().count(), 2);
+
+        let raw = map.into_unsafe();
+
+        assert_eq!(unsafe { raw.into_map::<&str, i32>() }.len(), 2);
+
+        map.clear();
+
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    #[test]
+    fn copies_after_remove() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, vec![("a", 1), ("b", 2), ("c", 3)]);
+        let mut copy = map;
+
+        copy.remove("c");
+
+        // The copy shares the nodes, but not the count of entries
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.iter().count(), 2);
+        assert_eq!(map.prefix_index_in(&arena).prefix("").len(), 2);
+        assert!(map.eq_unordered(&copy));
+    }
+
+    #[test]
+    fn bloom_maps_len() {
+        let arena = Arena::new();
+        let mut bloom = BloomMap::new();
+        let big = BigBloomMap::new(&arena);
+
+        for key in ["doge", "to", "the", "moon", "to"] {
+            bloom.insert(&arena, key, key.len());
+            big.insert(&arena, key, key.len());
+        }
+
+        assert_eq!(bloom.len(), 4);
+        assert_eq!(big.len(), 4);
+        assert_eq!(big.iter().count(), 4);
+
+        big.clear();
+
+        assert_eq!(big.len(), 0);
+    }
+
+    #[test]
+    fn cross_type_eq() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, [("foo", 1), ("bar", 2)]);
+        let mut bloom = BloomMap::new();
+        let big = BigBloomMap::new(&arena);
+
+        for (key, value) in [("foo", 1), ("bar", 2)] {
+            bloom.insert(&arena, key, value);
+            big.insert(&arena, key, value);
+        }
+
+        assert_eq!(map, bloom);
+        assert_eq!(bloom, map);
+        assert_eq!(map, big);
+        assert_eq!(big, map);
+        assert_eq!(bloom, big);
+        assert_eq!(big, bloom);
+
+        big.insert(&arena, "bar", 3);
+
+        assert_ne!(map, big);
+        assert_ne!(big, bloom);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+        let mut calls = 0;
+
+        for key in ["foo", "bar", "foo", "baz", "bar"] {
+            let value = map.get_or_insert_with(&arena, key, || {
+                calls += 1;
+                calls
+            });
+
+            assert_eq!(Some(value), map.get(key));
+        }
+
+        assert_eq!(calls, 3);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"foo", 1), (&"bar", 2), (&"baz", 3)]);
+    }
+
+    #[test]
+    fn keys_and_values() {
+        let arena = Arena::new();
+        let map = Map::from_iter(&arena, vec![("foo", 1), ("bar", 2), ("doge", 3)]);
+
+        assert_eq!(map.keys().count(), 3);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["foo", "bar", "doge"]);
+        assert_eq!(map.values().collect::<Vec<_>>(), [1, 2, 3]);
+
+        for cell in map.values_cells() {
+            cell.set(cell.get() + 10);
+        }
+
+        assert_eq!(map.get("bar"), Some(12));
+
+        let bloom = BloomMap::from(map);
+
+        assert_eq!(bloom.keys().copied().collect::<Vec<_>>(), ["foo", "bar", "doge"]);
+        assert_eq!(bloom.values().collect::<Vec<_>>(), [11, 12, 13]);
+
+        bloom.values_cells().next().unwrap().set(0);
+
+        assert_eq!(bloom.get("foo"), Some(0));
+        assert_eq!(map.get("foo"), Some(0));
+    }
+
+    #[test]
+    fn depth() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        assert_eq!(map.depth(), 0);
+
+        map.insert(&arena, 0u32, ());
+
+        assert_eq!(map.depth(), 1);
+
+        for n in 1..1024u32 {
+            map.insert(&arena, n, ());
+        }
+
+        let depth = map.depth();
+
+        // At least a perfectly balanced tree, and far from a linked list
+        assert!(depth >= 10);
+        assert!(depth < 64, "depth of {} for 1024 entries", depth);
+    }
 }
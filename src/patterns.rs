@@ -0,0 +1,540 @@
+//! Small helpers composing the primitives of this crate into the idioms
+//! they are most often used for, so that they can be called rather than
+//! copied around.
+//!
+//! + `Scope`: a chain of lexical scopes, each with its own `Map` of bindings.
+//! + `intern_all`: interning a batch of strings into a slice of `Symbol`s.
+//! + `CheckpointBuilder`: a slice builder that can roll back to a `Checkpoint`.
+//! + `StructuralHash`: memoized structural hashes of trees on the arena.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::arena::Arena;
+use crate::cell::CopyCell;
+use crate::hash::DefaultHasher;
+use crate::interner::{StringInterner, Symbol};
+use crate::map::Map;
+use crate::vec::ArenaVec;
+
+/// A lexical scope with a `Map` of bindings and a reference to its parent.
+/// Lookups walk up the chain of parents until a binding is found.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::patterns::Scope;
+///
+/// let arena = Arena::new();
+/// let global = Scope::root(&arena);
+///
+/// global.declare(&arena, "x", 1);
+/// global.declare(&arena, "y", 2);
+///
+/// let function = global.child(&arena);
+///
+/// function.declare(&arena, "x", 10);
+///
+/// assert_eq!(function.lookup("x"), Some(10));
+/// assert_eq!(function.lookup("y"), Some(2));
+/// assert_eq!(global.lookup("x"), Some(1));
+/// assert_eq!(function.lookup("z"), None);
+/// ```
+pub struct Scope<'arena, K, V> {
+    bindings: CopyCell<Map<'arena, K, V>>,
+    parent: Option<&'arena Scope<'arena, K, V>>,
+}
+
+impl<'arena, K, V> Scope<'arena, K, V>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+{
+    /// Allocate a new scope without a parent on the arena.
+    #[inline]
+    pub fn root(arena: &'arena Arena) -> &'arena Self {
+        arena.alloc_node(Scope {
+            bindings: CopyCell::new(Map::new()),
+            parent: None,
+        })
+    }
+
+    /// Allocate a new scope with this scope as its parent on the arena.
+    #[inline]
+    pub fn child(&'arena self, arena: &'arena Arena) -> &'arena Self {
+        arena.alloc_node(Scope {
+            bindings: CopyCell::new(Map::new()),
+            parent: Some(self),
+        })
+    }
+
+    /// Get the parent of this scope, if any.
+    #[inline]
+    pub fn parent(&self) -> Option<&'arena Self> {
+        self.parent
+    }
+
+    /// Get the number of scopes above this one.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut scope = self.parent;
+
+        while let Some(parent) = scope {
+            depth += 1;
+            scope = parent.parent;
+        }
+
+        depth
+    }
+
+    /// Bind the `key` to the `value` in this scope, shadowing bindings of
+    /// the same `key` in parent scopes.
+    #[inline]
+    pub fn declare(&self, arena: &'arena Arena, key: K, value: V) -> Option<V> {
+        let mut bindings = self.bindings.get();
+        let previous = bindings.insert(arena, key, value);
+
+        self.bindings.set(bindings);
+        previous
+    }
+
+    /// Get the value bound to the `key` in this scope, ignoring its parents.
+    #[inline]
+    pub fn lookup_local(&self, key: K) -> Option<V> {
+        self.bindings.get().get(key)
+    }
+
+    /// Get the value bound to the `key` in this scope or the closest parent
+    /// scope that has it.
+    #[inline]
+    pub fn lookup(&self, key: K) -> Option<V> {
+        self.resolve(key).map(|(value, _)| value)
+    }
+
+    /// Get the value bound to the `key`, along with the number of scopes that
+    /// had to be walked up to find it.
+    pub fn resolve(&self, key: K) -> Option<(V, usize)> {
+        let mut scope = self;
+        let mut distance = 0;
+
+        loop {
+            if let Some(value) = scope.bindings.get().get(key) {
+                return Some((value, distance));
+            }
+
+            scope = scope.parent?;
+            distance += 1;
+        }
+    }
+
+    /// Get the bindings of this scope, ignoring its parents.
+    #[inline]
+    pub fn bindings(&self) -> Map<'arena, K, V> {
+        self.bindings.get()
+    }
+}
+
+/// Intern all `strings` and return their `Symbol`s as a slice on the arena,
+/// in the same order.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::interner::StringInterner;
+/// use toolshed::patterns::intern_all;
+///
+/// let arena = Arena::new();
+/// let mut interner = StringInterner::new(&arena);
+///
+/// let symbols = intern_all(&arena, &mut interner, "a b a c".split(' '));
+///
+/// assert_eq!(symbols[0], symbols[2]);
+/// assert_eq!(interner.len(), 3);
+/// ```
+pub fn intern_all<'arena, I, S>(arena: &'arena Arena, interner: &mut StringInterner<'_>, strings: I) -> &'arena [Symbol]
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut symbols = ArenaVec::new(arena);
+
+    symbols.extend(strings.into_iter().map(|string| interner.intern(string.as_ref())));
+    symbols.into_slice()
+}
+
+/// A point in a `CheckpointBuilder` that it can be rolled back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// A builder of slices on the arena that can be rolled back to a
+/// `Checkpoint`. This is useful in parsers that try one branch of the
+/// grammar and backtrack if it doesn't match.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::patterns::CheckpointBuilder;
+///
+/// let arena = Arena::new();
+/// let mut builder = CheckpointBuilder::new(&arena);
+///
+/// builder.push(1);
+///
+/// let checkpoint = builder.checkpoint();
+///
+/// builder.push(2);
+/// builder.push(3);
+/// builder.rollback(checkpoint);
+/// builder.push(4);
+///
+/// assert_eq!(builder.finish(), &[1, 4]);
+/// ```
+pub struct CheckpointBuilder<'arena, T: Copy> {
+    items: ArenaVec<'arena, T>,
+}
+
+impl<'arena, T: Copy> CheckpointBuilder<'arena, T> {
+    /// Create a new, empty `CheckpointBuilder`.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        CheckpointBuilder {
+            items: ArenaVec::new(arena),
+        }
+    }
+
+    /// Append an element to the end of the builder.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        self.items.push(value);
+    }
+
+    /// Get the elements pushed so far.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Get a `Checkpoint` at the current state of the builder.
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.items.len())
+    }
+
+    /// Remove all elements pushed since the `checkpoint` was taken. Rolling
+    /// back to a checkpoint taken after the current state does nothing.
+    #[inline]
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        while self.items.len() > checkpoint.0 {
+            self.items.pop();
+        }
+    }
+
+    /// Run `f` on the builder, rolling back all elements it pushed if it
+    /// returns `None`.
+    pub fn attempt<R, F>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Self) -> Option<R>,
+    {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+
+        if result.is_none() {
+            self.rollback(checkpoint);
+        }
+
+        result
+    }
+
+    /// Finish building and return a reference to the elements.
+    #[inline]
+    pub fn finish(self) -> &'arena [T] {
+        self.items.into_slice()
+    }
+}
+
+/// Computes 64-bit structural hashes of trees on the arena, caching the hash
+/// of every node in a side `Map` keyed by the address of the node. Equal
+/// subtrees get equal hashes no matter where they are allocated, which is
+/// the basis for hash-consing or detecting common subexpressions.
+///
+/// The tree is described by a closure, which feeds the data of a single node
+/// into a `NodeHasher` and passes its children to `NodeHasher::child`. Hashes
+/// of children are computed first, without recursion, so the closure may be
+/// called more than once for a node. Trees must not contain cycles.
+///
+/// ```rust
+/// use std::hash::Hash;
+/// use toolshed::Arena;
+/// use toolshed::patterns::StructuralHash;
+///
+/// #[derive(Clone, Copy)]
+/// enum Expr<'arena> {
+///     Num(i64),
+///     Add(&'arena Expr<'arena>, &'arena Expr<'arena>),
+/// }
+///
+/// let arena = Arena::new();
+/// let one = arena.alloc(Expr::Num(1));
+/// let a = arena.alloc(Expr::Add(one, arena.alloc(Expr::Num(2))));
+/// let b = arena.alloc(Expr::Add(arena.alloc(Expr::Num(1)), arena.alloc(Expr::Num(2))));
+/// let c = arena.alloc(Expr::Add(arena.alloc(Expr::Num(2)), one));
+///
+/// let mut hashes = StructuralHash::new(&arena, |expr: &Expr, hasher| match *expr {
+///     Expr::Num(n) => n.hash(hasher),
+///     Expr::Add(left, right) => {
+///         "add".hash(hasher);
+///         hasher.child(left);
+///         hasher.child(right);
+///     }
+/// });
+///
+/// assert_eq!(hashes.hash(a), hashes.hash(b));
+/// assert_ne!(hashes.hash(a), hashes.hash(c));
+/// ```
+pub struct StructuralHash<'arena, N, F> {
+    arena: &'arena Arena,
+    cache: Map<'arena, *const N, u64>,
+    describe: F,
+}
+
+impl<'arena, N, F> StructuralHash<'arena, N, F>
+where
+    F: FnMut(&'arena N, &mut NodeHasher<'_, 'arena, N>),
+{
+    /// Create a new `StructuralHash` with an empty cache on the arena, using
+    /// `describe` to hash the data and list the children of every node.
+    #[inline]
+    pub fn new(arena: &'arena Arena, describe: F) -> Self {
+        StructuralHash {
+            arena,
+            cache: Map::new(),
+            describe,
+        }
+    }
+
+    /// Get the structural hash of the tree starting at `node`, computing
+    /// and caching hashes of all nodes in it that weren't hashed yet.
+    pub fn hash(&mut self, node: &'arena N) -> u64 {
+        if let Some(hash) = self.cache.get(node) {
+            return hash;
+        }
+
+        let mut stack = vec![node];
+        let mut missing = Vec::new();
+
+        while let Some(&node) = stack.last() {
+            // Nodes shared within the tree might have been pushed more than once
+            if self.cache.contains_key(node) {
+                stack.pop();
+                continue;
+            }
+
+            let mut hasher = NodeHasher {
+                state: DefaultHasher::default(),
+                cache: self.cache,
+                missing: &mut missing,
+            };
+
+            (self.describe)(node, &mut hasher);
+
+            if hasher.missing.is_empty() {
+                let hash = hasher.state.finish();
+
+                self.cache.insert(self.arena, node, hash);
+                stack.pop();
+            } else {
+                stack.append(&mut missing);
+            }
+        }
+
+        self.cache.get(node).expect("Hash of the root node is cached")
+    }
+
+    /// Get the cached hash of `node`, if it was already hashed on its own
+    /// or as a part of a larger tree.
+    #[inline]
+    pub fn get(&self, node: &'arena N) -> Option<u64> {
+        self.cache.get(node)
+    }
+}
+
+impl<'arena, N, F> fmt::Debug for StructuralHash<'arena, N, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StructuralHash")
+            .field("cached", &self.cache.iter().count())
+            .finish()
+    }
+}
+
+/// A `Hasher` for the data of a single node, passed to the closure of a
+/// `StructuralHash`. Children of the node are added with `child`.
+pub struct NodeHasher<'a, 'arena, N> {
+    state: DefaultHasher,
+    cache: Map<'arena, *const N, u64>,
+    missing: &'a mut Vec<&'arena N>,
+}
+
+impl<'a, 'arena, N> NodeHasher<'a, 'arena, N> {
+    /// Add the structural hash of a child node to the hash of this node.
+    /// Children are hashed in the order they are added.
+    #[inline]
+    pub fn child(&mut self, node: &'arena N) {
+        match self.cache.get(node) {
+            Some(hash) => self.state.write_u64(hash),
+            None       => self.missing.push(node),
+        }
+    }
+}
+
+impl<'a, 'arena, N> Hasher for NodeHasher<'a, 'arena, N> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.state.write(bytes);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.state.finish()
+    }
+}
+
+impl<'a, 'arena, N> fmt::Debug for NodeHasher<'a, 'arena, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("NodeHasher")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scope_chain() {
+        let arena = Arena::new();
+        let global = Scope::root(&arena);
+        let outer = global.child(&arena);
+        let inner = outer.child(&arena);
+
+        global.declare(&arena, "a", 1);
+        outer.declare(&arena, "b", 2);
+        inner.declare(&arena, "a", 3);
+
+        assert_eq!(inner.resolve("a"), Some((3, 0)));
+        assert_eq!(inner.resolve("b"), Some((2, 1)));
+        assert_eq!(outer.resolve("a"), Some((1, 1)));
+        assert_eq!(inner.lookup_local("b"), None);
+        assert_eq!(inner.depth(), 2);
+        assert!(std::ptr::eq(inner.parent().unwrap(), outer));
+        assert!(global.parent().is_none());
+    }
+
+    #[test]
+    fn intern_all_symbols() {
+        let arena = Arena::new();
+        let mut interner = StringInterner::new(&arena);
+        let foo = interner.intern("foo");
+
+        let symbols = intern_all(&arena, &mut interner, vec![String::from("bar"), String::from("foo")]);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[1], foo);
+        assert_eq!(interner.resolve(symbols[0]), "bar");
+    }
+
+    #[test]
+    fn checkpoint_attempt() {
+        let arena = Arena::new();
+        let mut builder = CheckpointBuilder::new(&arena);
+
+        builder.push(1u32);
+
+        let failed = builder.attempt(|builder| {
+            builder.push(2);
+            builder.push(3);
+
+            None::<()>
+        });
+
+        assert_eq!(failed, None);
+        assert_eq!(builder.as_slice(), &[1]);
+
+        let succeeded = builder.attempt(|builder| {
+            builder.push(4);
+
+            Some(())
+        });
+
+        assert_eq!(succeeded, Some(()));
+        assert_eq!(builder.finish(), &[1, 4]);
+    }
+
+    #[test]
+    fn rollback_forward_is_noop() {
+        let arena = Arena::new();
+        let mut builder = CheckpointBuilder::new(&arena);
+
+        builder.push(1u8);
+
+        let checkpoint = builder.checkpoint();
+
+        builder.rollback(Checkpoint(5));
+
+        assert_eq!(builder.checkpoint(), checkpoint);
+    }
+
+    #[derive(Clone, Copy)]
+    enum Tree<'arena> {
+        Leaf(u32),
+        Node(&'arena Tree<'arena>, &'arena Tree<'arena>),
+    }
+
+    fn describe<'arena>(tree: &'arena Tree<'arena>, hasher: &mut NodeHasher<'_, 'arena, Tree<'arena>>) {
+        match *tree {
+            Tree::Leaf(n) => n.hash(hasher),
+            Tree::Node(left, right) => {
+                hasher.write_u8(0xff);
+                hasher.child(left);
+                hasher.child(right);
+            }
+        }
+    }
+
+    #[test]
+    fn structural_hash() {
+        let arena = Arena::new();
+        let leaf = arena.alloc(Tree::Leaf(1));
+        let shared = arena.alloc(Tree::Node(leaf, leaf));
+        let tree = arena.alloc(Tree::Node(shared, shared));
+        let copy = arena.alloc(Tree::Node(
+            arena.alloc(Tree::Node(arena.alloc(Tree::Leaf(1)), arena.alloc(Tree::Leaf(1)))),
+            arena.alloc(Tree::Node(arena.alloc(Tree::Leaf(1)), arena.alloc(Tree::Leaf(1)))),
+        ));
+        let mut hashes = StructuralHash::new(&arena, describe);
+
+        assert_eq!(hashes.get(shared), None);
+        assert_eq!(hashes.hash(tree), hashes.hash(copy));
+        assert_eq!(hashes.get(shared), Some(hashes.hash(left_of(copy))));
+        assert_ne!(hashes.hash(leaf), hashes.hash(shared));
+    }
+
+    fn left_of<'arena>(tree: &'arena Tree<'arena>) -> &'arena Tree<'arena> {
+        match *tree {
+            Tree::Node(left, _) => left,
+            Tree::Leaf(_)       => tree,
+        }
+    }
+
+    #[test]
+    fn structural_hash_of_deep_tree() {
+        let arena = Arena::new();
+        let mut tree = &*arena.alloc(Tree::Leaf(0));
+
+        for n in 1..100_000 {
+            tree = arena.alloc(Tree::Node(arena.alloc(Tree::Leaf(n)), tree));
+        }
+
+        let mut hashes = StructuralHash::new(&arena, describe);
+        let hash = hashes.hash(tree);
+
+        assert_eq!(hashes.hash(tree), hash);
+        assert_eq!(format!("{:?}", hashes), "StructuralHash { cached: 199999 }");
+    }
+}
@@ -0,0 +1,364 @@
+//! An indentation aware pretty printer for writing out trees of data on the
+//! `Arena`, such as ASTs, without allocating on the heap.
+
+use std::fmt::{self, Write};
+use std::io;
+
+/// A pretty printer tracking the indentation level and current column,
+/// writing into any `fmt::Write`, such as an `ArenaString`. Use
+/// `PrettyPrinter::io` to write into an `io::Write` instead.
+///
+/// Indentation is emitted lazily at the start of every line, so empty lines
+/// never have trailing whitespace. Text written with `word` is wrapped onto
+/// a new line whenever it would exceed the maximum width.
+///
+/// ```rust
+/// use std::fmt::Write;
+/// use toolshed::Arena;
+/// use toolshed::list::List;
+/// use toolshed::pretty::PrettyPrinter;
+/// use toolshed::string::ArenaString;
+///
+/// let arena = Arena::new();
+/// let body = List::from_iter(&arena, vec!["let a = 1;", "let b = 2;"]);
+///
+/// let mut printer = PrettyPrinter::new(ArenaString::new(&arena)).with_indent(2);
+///
+/// printer.write_str("fn main() {");
+/// printer.indented(|printer| {
+///     for statement in body.iter() {
+///         printer.newline();
+///         printer.write_str(statement);
+///     }
+/// });
+/// printer.newline();
+/// printer.write_str("}");
+///
+/// assert_eq!(printer.into_inner().into_str(), "fn main() {\n  let a = 1;\n  let b = 2;\n}");
+/// ```
+pub struct PrettyPrinter<W> {
+    out: W,
+    indent_width: usize,
+    max_width: usize,
+    level: usize,
+    column: usize,
+    result: fmt::Result,
+}
+
+impl<W: Write> PrettyPrinter<W> {
+    /// Create a new `PrettyPrinter` writing into `out`, indenting with 4
+    /// spaces per level and wrapping words at 80 columns.
+    #[inline]
+    pub fn new(out: W) -> Self {
+        PrettyPrinter {
+            out,
+            indent_width: 4,
+            max_width: 80,
+            level: 0,
+            column: 0,
+            result: Ok(()),
+        }
+    }
+
+    /// Set the number of spaces per indentation level.
+    #[inline]
+    pub fn with_indent(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    /// Set the column at which `word` wraps onto a new line.
+    #[inline]
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Get the current indentation level.
+    #[inline]
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Get the column, in characters, at which the next character will be written.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Increase the indentation level for the following lines.
+    #[inline]
+    pub fn indent(&mut self) {
+        self.level += 1;
+    }
+
+    /// Decrease the indentation level for the following lines.
+    #[inline]
+    pub fn dedent(&mut self) {
+        self.level = self.level.saturating_sub(1);
+    }
+
+    /// Call `f` with the indentation level increased by one.
+    #[inline]
+    pub fn indented<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.indent();
+        f(self);
+        self.dedent();
+    }
+
+    /// Start a new line.
+    #[inline]
+    pub fn newline(&mut self) {
+        self.emit("\n");
+        self.column = 0;
+    }
+
+    /// Write a string, indenting every line in it.
+    pub fn write_str(&mut self, text: &str) {
+        let mut lines = text.split('\n');
+
+        if let Some(first) = lines.next() {
+            self.write_line(first);
+        }
+
+        for line in lines {
+            self.newline();
+            self.write_line(line);
+        }
+    }
+
+    /// Write a single word, separated from the preceding text by a space,
+    /// starting a new line first if it wouldn't fit within the maximum width.
+    pub fn word(&mut self, word: &str) {
+        let width = word.chars().count();
+
+        if self.column > self.indentation() {
+            if self.column + 1 + width > self.max_width {
+                self.newline();
+            } else {
+                self.write_line(" ");
+            }
+        }
+
+        self.write_line(word);
+    }
+
+    /// Write all `items` with `f`, separated with `separator`. This works on
+    /// any iterator, such as the ones of `List`s or `Map`s, without collecting
+    /// the items first.
+    pub fn join<I, F>(&mut self, items: I, separator: &str, mut f: F)
+    where
+        I: IntoIterator,
+        F: FnMut(&mut Self, I::Item),
+    {
+        for (index, item) in items.into_iter().enumerate() {
+            if index != 0 {
+                self.write_str(separator);
+            }
+
+            f(self, item);
+        }
+    }
+
+    /// Get the first error returned by the output, if any. After an error,
+    /// all further writes are skipped.
+    #[inline]
+    pub fn result(&self) -> fmt::Result {
+        self.result
+    }
+
+    /// Get a reference to the output.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    /// Finish printing and return the output.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    #[inline]
+    fn indentation(&self) -> usize {
+        self.level * self.indent_width
+    }
+
+    /// Write text that contains no newlines.
+    fn write_line(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if self.column == 0 {
+            for _ in 0..self.indentation() {
+                self.emit(" ");
+            }
+
+            self.column = self.indentation();
+        }
+
+        self.emit(text);
+        self.column += text.chars().count();
+    }
+
+    #[inline]
+    fn emit(&mut self, text: &str) {
+        if self.result.is_ok() {
+            self.result = self.out.write_str(text);
+        }
+    }
+}
+
+impl<W: io::Write> PrettyPrinter<IoWriter<W>> {
+    /// Create a new `PrettyPrinter` writing into an `io::Write`.
+    #[inline]
+    pub fn io(out: W) -> Self {
+        PrettyPrinter::new(IoWriter {
+            out,
+            error: None,
+        })
+    }
+}
+
+impl<W: Write> Write for PrettyPrinter<W> {
+    #[inline]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        PrettyPrinter::write_str(self, text);
+
+        self.result
+    }
+}
+
+impl<W> fmt::Debug for PrettyPrinter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PrettyPrinter")
+            .field("level", &self.level)
+            .field("column", &self.column)
+            .finish()
+    }
+}
+
+/// Adapter writing the output of a `PrettyPrinter` into an `io::Write`.
+#[derive(Debug)]
+pub struct IoWriter<W> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W> IoWriter<W> {
+    /// Take the `io::Error` that stopped the output, if any.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Get the wrapped `io::Write`.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    #[inline]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.out.write_all(text.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Arena;
+    use crate::list::List;
+    use crate::string::ArenaString;
+
+    #[test]
+    fn indentation() {
+        let arena = Arena::new();
+        let mut printer = PrettyPrinter::new(ArenaString::new(&arena));
+
+        printer.write_str("a {\n");
+        printer.indent();
+        printer.write_str("b\n\nc");
+        printer.dedent();
+        printer.dedent();
+        printer.newline();
+        printer.write_str("}");
+
+        assert_eq!(printer.level(), 0);
+        assert_eq!(printer.into_inner().into_str(), "a {\n    b\n\n    c\n}");
+    }
+
+    #[test]
+    fn word_wrapping() {
+        let arena = Arena::new();
+        let words = List::from_iter(&arena, vec!["doge", "to", "the", "moon", "and", "back"]);
+        let mut printer = PrettyPrinter::new(ArenaString::new(&arena)).with_indent(2).with_max_width(12);
+
+        printer.indented(|printer| {
+            for word in words.iter() {
+                printer.word(word);
+            }
+        });
+
+        assert_eq!(printer.column(), 10);
+        assert_eq!(printer.into_inner().into_str(), "  doge to\n  the moon\n  and back");
+    }
+
+    #[test]
+    fn join_and_write_fmt() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1, 2, 3]);
+        let mut printer = PrettyPrinter::new(ArenaString::new(&arena));
+
+        printer.write_str("[");
+        printer.join(list.iter(), ", ", |printer, n| {
+            write!(printer, "{}", n).unwrap();
+        });
+        printer.write_str("]");
+
+        assert_eq!(printer.result(), Ok(()));
+        assert_eq!(printer.get_ref().as_str(), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn io() {
+        let mut printer = PrettyPrinter::io(Vec::new());
+
+        printer.indent();
+        printer.write_str("doge");
+
+        assert_eq!(printer.into_inner().into_inner(), b"    doge");
+    }
+
+    #[test]
+    fn io_error() {
+        struct Broken;
+
+        impl io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut printer = PrettyPrinter::io(Broken);
+
+        printer.write_str("doge");
+
+        assert_eq!(printer.result(), Err(fmt::Error));
+        assert_eq!(printer.into_inner().take_error().unwrap().to_string(), "broken");
+    }
+}
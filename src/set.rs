@@ -1,9 +1,18 @@
 //! Sets of values that can be used with the `Arena`.
 
-use std::hash::Hash;
-
-use crate::map::{Map, BloomMap, MapIter};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{Copied, FusedIterator};
+use std::slice;
+
+use crate::alloc_in::CloneIn;
+use crate::map::{Map, BloomMap, MapIter, OccupiedError, KeyPolicy, PolicyMap, PolicyMapIter, UnsafeMap};
+use crate::snapshot::{Relocate, SnapshotWriter};
 use crate::Arena;
+use crate::cell::CopyCell;
+use crate::hash::DefaultHasher;
 
 /// A set of values. This structure is using a `Map` with value
 /// type set to `()` internally.
@@ -42,32 +51,122 @@ impl<'arena, I> Set<'arena, I> {
 
     /// Clears the map.
     #[inline]
-    pub fn clear(&self) {
+    pub fn clear(&mut self) {
         self.map.clear()
     }
+
+    /// Returns an `UnsafeSet` for the current `Set`. While this function is
+    /// safe itself, using `UnsafeSet` might lead to undefined behavior.
+    #[inline]
+    pub fn into_unsafe(self) -> UnsafeSet {
+        UnsafeSet {
+            map: self.map.into_unsafe(),
+        }
+    }
+
+    /// Get an iterator over the elements in the set, sorted with the `cmp`
+    /// function. Elements are sorted in a scratch slice on the arena, so
+    /// no heap allocation is needed.
+    pub fn iter_sorted_in<F>(&self, arena: &'arena Arena, mut cmp: F) -> Copied<slice::Iter<'arena, &'arena I>>
+    where
+        F: FnMut(&I, &I) -> Ordering,
+    {
+        let len = self.iter().count();
+        let sorted = arena.alloc_uninitialized_slice(len).init_with(self.iter());
+
+        sorted.sort_by(|a, b| cmp(a, b));
+        sorted.iter().copied()
+    }
+
+    /// Create a new set on the arena containing the results of calling `f`
+    /// on every element of this set. Elements are inserted in iteration order,
+    /// and duplicates produced by `f` are only inserted once.
+    pub fn map_in<J, F>(&self, arena: &'arena Arena, mut f: F) -> Set<'arena, J>
+    where
+        J: Eq + Hash + Copy,
+        F: FnMut(&'arena I) -> J,
+    {
+        let mut set = Set::new();
+
+        for item in self.iter() {
+            set.insert(arena, f(item));
+        }
+
+        set
+    }
 }
 
 impl<'arena, I> Set<'arena, I>
 where
     I: Eq + Hash + Copy,
 {
+    /// Create a set from an iterator of values.
+    pub fn from_iter<S>(arena: &'arena Arena, source: S) -> Self
+    where
+        S: IntoIterator<Item = I>,
+    {
+        let mut set = Set::new();
+
+        for item in source {
+            set.insert(arena, item);
+        }
+
+        set
+    }
+
     /// Inserts a value into the set.
     #[inline]
-    pub fn insert(&self, arena: &'arena Arena, item: I) {
+    pub fn insert(&mut self, arena: &'arena Arena, item: I) {
         self.map.insert(arena, item, ());
     }
 
+    /// Inserts a value into the set, unless an equal value is already in
+    /// it, in which case the error holds the existing value.
+    #[inline]
+    pub fn try_insert(&mut self, arena: &'arena Arena, item: I) -> Result<(), OccupiedError<I>> {
+        self.map.try_insert_entry(arena, item, ())
+            .map_err(|(existing, _)| OccupiedError::new(existing, item))
+    }
+
     /// Gets a reference to the existing value in the set, if it exists
     #[inline]
     pub fn get(&self, key: I) -> Option<&I> {
         self.map.get_key(key)
     }
 
+    /// Gets a reference to the existing value in the set equal to a borrowed
+    /// form of the value, see `Map::get_by`.
+    #[inline]
+    pub fn get_by<Q>(&self, key: &Q) -> Option<&I>
+    where
+        I: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map.get_key_by(key)
+    }
+
     /// Returns `true` if the set contains a value.
     #[inline]
     pub fn contains(&self, item: I) -> bool {
         self.map.contains_key(item)
     }
+
+    /// Returns `true` if both sets contain the same elements, regardless of
+    /// the order in which they were inserted. Comparing sets with `==`, on
+    /// the other hand, also compares the order of elements.
+    pub fn set_eq_unordered(&self, other: &Set<'_, I>) -> bool {
+        let mut len = 0;
+
+        for item in self.iter() {
+            if !other.contains(*item) {
+                return false;
+            }
+
+            len += 1;
+        }
+
+        len == other.iter().count()
+    }
 }
 
 /// A set of values with a bloom filter. This structure is
@@ -101,7 +200,7 @@ impl<'arena, I> BloomSet<'arena, I> {
 
     /// Clears the map.
     #[inline]
-    pub fn clear(&self) {
+    pub fn clear(&mut self) {
         self.map.clear()
     }
 }
@@ -112,7 +211,7 @@ where
 {
     /// Inserts a value into the set.
     #[inline]
-    pub fn insert(&self, arena: &'arena Arena, item: I) {
+    pub fn insert(&mut self, arena: &'arena Arena, item: I) {
         self.map.insert(arena, item, ());
     }
 
@@ -121,9 +220,328 @@ where
     pub fn contains(&self, item: I) -> bool {
         self.map.contains_key(item)
     }
+
+    /// Returns `true` if both sets contain the same elements, regardless of
+    /// the order in which they were inserted. Elements missing from the
+    /// `other` set are usually rejected by its bloom filter without a tree
+    /// lookup.
+    pub fn set_eq_unordered(&self, other: &BloomSet<'_, I>) -> bool {
+        let mut len = 0;
+
+        for item in self.iter() {
+            if !other.contains(*item) {
+                return false;
+            }
+
+            len += 1;
+        }
+
+        len == other.iter().count()
+    }
+}
+
+/// A set of values compared according to a `KeyPolicy`. This structure is
+/// using a `PolicyMap` with value type set to `()` internally.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::map::AsciiCaseInsensitive;
+/// use toolshed::set::PolicySet;
+///
+/// let arena = Arena::new();
+/// let mut keywords: PolicySet<&str, AsciiCaseInsensitive> = PolicySet::new();
+///
+/// keywords.insert(&arena, "BEGIN");
+/// keywords.insert(&arena, "End");
+///
+/// assert!(keywords.contains("begin"));
+/// assert!(keywords.contains("END"));
+/// assert!(!keywords.contains("loop"));
+/// ```
+pub struct PolicySet<'arena, I, P> {
+    map: PolicyMap<'arena, I, (), P>,
+}
+
+impl<'arena, I: Copy, P> Clone for PolicySet<'arena, I, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'arena, I: Copy, P> Copy for PolicySet<'arena, I, P> {}
+
+impl<I, P> Default for PolicySet<'_, I, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'arena, I, P> PolicySet<'arena, I, P> {
+    /// Creates a new, empty `PolicySet`.
+    pub const fn new() -> Self {
+        PolicySet {
+            map: PolicyMap::new(),
+        }
+    }
+
+    /// Get an iterator over the elements in the set
+    #[inline]
+    pub fn iter(&self) -> PolicySetIter<'arena, I, P> {
+        PolicySetIter {
+            inner: self.map.iter()
+        }
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<'arena, I, P> PolicySet<'arena, I, P>
+where
+    I: Copy,
+    P: KeyPolicy<I>,
+{
+    /// Inserts a value into the set, unless an equivalent value is
+    /// already in it.
+    #[inline]
+    pub fn insert(&mut self, arena: &'arena Arena, item: I) {
+        self.map.insert(arena, item, ());
+    }
+
+    /// Gets a reference to the value in the set equivalent to `item`, if any.
+    #[inline]
+    pub fn get(&self, item: I) -> Option<&I> {
+        self.map.get_key(item)
+    }
+
+    /// Returns `true` if the set contains a value equivalent to `item`.
+    #[inline]
+    pub fn contains(&self, item: I) -> bool {
+        self.map.contains_key(item)
+    }
+}
+
+/// A single fixed-size bloom filter in the chain of a `ScalableBloom`.
+struct BloomLayer<'arena> {
+    bits: &'arena [CopyCell<u64>],
+    hashes: u32,
+    capacity: usize,
+    len: CopyCell<usize>,
+    next: CopyCell<Option<&'arena BloomLayer<'arena>>>,
+}
+
+impl<'arena> BloomLayer<'arena> {
+    /// Allocate a layer for `capacity` items at the given false positive rate.
+    fn new(arena: &'arena Arena, capacity: usize, error_rate: f64) -> Self {
+        // Number of bits that gives the exact error rate at capacity for the
+        // number of hashes, which is rounded up from the optimal one.
+        let hashes = (-error_rate.log2()).ceil().max(1.0);
+        let bits = (-hashes * capacity as f64 / (1.0 - error_rate.powf(1.0 / hashes)).ln()).ceil() as usize;
+        let hashes = hashes as u32;
+        let words = bits.div_ceil(64).max(1);
+
+        BloomLayer {
+            bits: arena.alloc_node_slice((0..words).map(|_| CopyCell::new(0))),
+            hashes,
+            capacity,
+            len: CopyCell::new(0),
+            next: CopyCell::new(None),
+        }
+    }
+
+    /// Bit indexes for an item, derived from its hash with double hashing.
+    #[inline]
+    fn indexes(&self, (h1, h2): (u64, u64)) -> impl Iterator<Item = usize> {
+        let bits = self.bits.len() as u64 * 64;
+
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits) as usize)
+    }
+
+    #[inline]
+    fn contains(&self, hash: (u64, u64)) -> bool {
+        self.indexes(hash).all(|index| self.bits[index / 64].get() & (1 << (index % 64)) != 0)
+    }
+
+    #[inline]
+    fn insert(&self, hash: (u64, u64)) {
+        for index in self.indexes(hash) {
+            let word = &self.bits[index / 64];
+
+            word.set(word.get() | 1 << (index % 64));
+        }
+
+        self.len.set(self.len.get() + 1);
+    }
+}
+
+/// A scalable bloom filter for deduplicating a number of items that isn't
+/// known upfront, such as millions of identifiers or URLs.
+///
+/// Items are added to a chain of fixed-size bloom filters on the `Arena`.
+/// Whenever the last filter reaches its capacity, a new one twice as large
+/// and with a tighter false positive rate is added to the chain, so that the
+/// false positive rate of the whole chain stays below the one requested.
+/// Items are hashed with the default hasher of the crate.
+///
+/// Like with any bloom filter, `may_contain` never returns `false` for an
+/// inserted item, but might return `true` for one that was never inserted.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::set::ScalableBloom;
+///
+/// let arena = Arena::new();
+/// let mut seen = ScalableBloom::new(1000, 0.01);
+///
+/// let new = (0..10_000u32).filter(|id| seen.insert(&arena, id)).count();
+///
+/// // A few items may have been mistaken for already seen ones
+/// assert!(new > 9_900);
+/// assert!(seen.may_contain(&42u32));
+/// assert!(!seen.insert(&arena, &42u32));
+/// assert_eq!(seen.len(), new);
+/// ```
+#[derive(Clone, Copy)]
+pub struct ScalableBloom<'arena> {
+    first: Option<&'arena BloomLayer<'arena>>,
+    last: Option<&'arena BloomLayer<'arena>>,
+    initial_capacity: usize,
+    error_rate: f64,
+}
+
+impl<'arena> ScalableBloom<'arena> {
+    /// Create a new, empty `ScalableBloom` with a first filter sized for
+    /// `initial_capacity` items, keeping the false positive rate below
+    /// `error_rate`. Nothing is allocated until the first insert.
+    ///
+    /// Panics if `error_rate` isn't between `0` and `1`.
+    pub fn new(initial_capacity: usize, error_rate: f64) -> Self {
+        assert!(error_rate > 0.0 && error_rate < 1.0, "error rate must be between 0 and 1, got {}", error_rate);
+
+        ScalableBloom {
+            first: None,
+            last: None,
+            initial_capacity: initial_capacity.max(1),
+            error_rate,
+        }
+    }
+
+    /// Insert an item into the filter. Returns `true` if the item definitely
+    /// wasn't in the filter before, or `false` if it may have been, in which
+    /// case the filter is left unchanged.
+    pub fn insert<T: Hash + ?Sized>(&mut self, arena: &'arena Arena, item: &T) -> bool {
+        let hash = Self::hash_item(item);
+
+        if self.contains_hash(hash) {
+            return false;
+        }
+
+        let layer = match self.last {
+            Some(last) if last.len.get() < last.capacity => last,
+            last => {
+                // Each layer doubles the capacity and halves the error rate,
+                // so that the rates of all layers sum up to `error_rate`.
+                let capacity = last.map_or(self.initial_capacity, |last| last.capacity * 2);
+                let error_rate = self.error_rate / 2f64.powi(self.layers() as i32 + 1);
+
+                let layer = arena.alloc_node(BloomLayer::new(arena, capacity, error_rate));
+
+                match last {
+                    Some(last) => last.next.set(Some(layer)),
+                    None       => self.first = Some(layer),
+                }
+
+                self.last = Some(layer);
+                layer
+            }
+        };
+
+        layer.insert(hash);
+        true
+    }
+
+    /// Returns `true` if the item may have been inserted into the filter,
+    /// or `false` if it definitely wasn't.
+    #[inline]
+    pub fn may_contain<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(Self::hash_item(item))
+    }
+
+    /// Number of items inserted into the filter, not counting the ones for
+    /// which `insert` returned `false`.
+    pub fn len(&self) -> usize {
+        self.layer_iter().map(|layer| layer.len.get()).sum()
+    }
+
+    /// Returns `true` if nothing was inserted into the filter.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none()
+    }
+
+    /// Number of filters in the chain.
+    pub fn layers(&self) -> usize {
+        self.layer_iter().count()
+    }
+
+    fn layer_iter(&self) -> impl Iterator<Item = &'arena BloomLayer<'arena>> {
+        std::iter::successors(self.first, |layer| layer.next.get())
+    }
+
+    #[inline]
+    fn contains_hash(&self, hash: (u64, u64)) -> bool {
+        self.layer_iter().any(|layer| layer.contains(hash))
+    }
+
+    /// Hash the item into two hashes for double hashing. The default hasher
+    /// doesn't necessarily mix its output well, so both are run through the
+    /// finalizer of MurmurHash3.
+    #[inline]
+    fn hash_item<T: Hash + ?Sized>(item: &T) -> (u64, u64) {
+        let mut hasher = DefaultHasher::default();
+
+        item.hash(&mut hasher);
+
+        let hash = fmix64(hasher.finish());
+
+        (hash, fmix64(hash ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+}
+
+/// Finalizer of MurmurHash3, mixing all bits of the input into the output.
+#[inline]
+fn fmix64(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ hash >> 33
+}
+
+impl fmt::Debug for ScalableBloom<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScalableBloom")
+            .field("len", &self.len())
+            .field("layers", &self.layers())
+            .field("error_rate", &self.error_rate)
+            .finish()
+    }
 }
 
 /// An iterator over the elements in the set.
+///
+/// Once the iterator returns `None` it will keep doing so, even if more
+/// elements are inserted to the set afterwards.
 pub struct SetIter<'arena, I> {
     inner: MapIter<'arena, I, ()>
 }
@@ -137,6 +555,24 @@ impl<'arena, I> Iterator for SetIter<'arena, I> {
     }
 }
 
+impl<'arena, I> FusedIterator for SetIter<'arena, I> {}
+
+/// An iterator over the elements in a `PolicySet`, in insertion order.
+pub struct PolicySetIter<'arena, I, P> {
+    inner: PolicyMapIter<'arena, I, (), P>
+}
+
+impl<'arena, I, P> Iterator for PolicySetIter<'arena, I, P> {
+    type Item = &'arena I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+impl<'arena, I, P> FusedIterator for PolicySetIter<'arena, I, P> {}
+
 impl<'arena, I> IntoIterator for Set<'arena, I> {
     type Item = &'arena I;
     type IntoIter = SetIter<'arena, I>;
@@ -157,6 +593,70 @@ impl<'arena, I> IntoIterator for BloomSet<'arena, I> {
     }
 }
 
+impl<'arena, I, P> IntoIterator for PolicySet<'arena, I, P> {
+    type Item = &'arena I;
+    type IntoIter = PolicySetIter<'arena, I, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Unsafe variant of the `Set` that erases any lifetime information, same
+/// as `UnsafeList` does for `List`.
+#[derive(Debug, Clone, Copy)]
+pub struct UnsafeSet {
+    map: UnsafeMap,
+}
+
+impl UnsafeSet {
+    /// Converts the `UnsafeSet` into a regular `Set`.
+    ///
+    /// # Safety
+    ///
+    /// Using this with incorrect types, lifetimes or after the original arena
+    /// has been dropped will lead to undefined behavior. Use with extreme care.
+    pub unsafe fn into_set<'arena, I>(self) -> Set<'arena, I> {
+        Set {
+            map: self.map.into_map(),
+        }
+    }
+}
+
+impl<'arena, 'target, I> CloneIn<'target> for Set<'arena, I>
+where
+    I: CloneIn<'target>,
+    I::Cloned: Eq + Hash,
+{
+    type Cloned = Set<'target, I::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'target Arena) -> Self::Cloned {
+        Set {
+            map: self.map.clone_in(arena),
+        }
+    }
+}
+
+unsafe impl<'arena, I: Relocate> Relocate for Set<'arena, I> {
+    type Relocated<'r> = Set<'r, I::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.map);
+    }
+}
+
+unsafe impl<'arena, I: Relocate> Relocate for BloomSet<'arena, I> {
+    type Relocated<'r> = BloomSet<'r, I::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_field(at, self, &self.map);
+    }
+}
+
 impl<'arena, I> From<Set<'arena, I>> for BloomSet<'arena, I>
 where
     I: Eq + Hash + Copy + AsRef<[u8]>,
@@ -181,11 +681,53 @@ impl<'arena, I> From<BloomSet<'arena, I>> for Set<'arena, I> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::map::AsciiCaseInsensitive;
+
+    #[test]
+    fn iter_sorted_in() {
+        let arena = Arena::new();
+        let set = Set::from_iter(&arena, vec!["doge", "to", "the", "moon"]);
+
+        let sorted: Vec<_> = set.iter_sorted_in(&arena, |a, b| a.cmp(b)).collect();
+
+        assert_eq!(sorted, [&"doge", &"moon", &"the", &"to"]);
+
+        let by_len: Vec<_> = set.iter_sorted_in(&arena, |a, b| a.len().cmp(&b.len())).collect();
+
+        assert_eq!(by_len, [&"to", &"the", &"doge", &"moon"]);
+    }
+
+    #[test]
+    fn iter_is_fused() {
+        let arena = Arena::new();
+        let mut set = Set::new();
+
+        set.insert(&arena, "foo");
+
+        let mut iter = set.iter();
+
+        assert_eq!(iter.next(), Some(&"foo"));
+        assert_eq!(iter.next(), None);
+
+        set.insert(&arena, "bar");
+
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn from_iter() {
+        let arena = Arena::new();
+        let set = Set::from_iter(&arena, vec!["foo", "bar", "foo"]);
+
+        assert_eq!(set.contains("foo"), true);
+        assert_eq!(set.contains("bar"), true);
+        assert_eq!(set.iter().count(), 2);
+    }
 
     #[test]
     fn set() {
         let arena = Arena::new();
-        let set = Set::new();
+        let mut set = Set::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -197,10 +739,27 @@ mod test {
         assert_eq!(set.contains("moon"), false);
     }
 
+    #[test]
+    fn map_in() {
+        let arena = Arena::new();
+        let mut set = Set::new();
+
+        set.insert(&arena, "foo");
+        set.insert(&arena, "bar");
+        set.insert(&arena, "doge");
+
+        let lengths = set.map_in(&arena, |item| item.len());
+
+        assert!(lengths.iter().eq([3, 4].iter()));
+        assert_eq!(lengths.contains(3), true);
+        assert_eq!(lengths.contains(4), true);
+        assert_eq!(lengths.contains(5), false);
+    }
+
     #[test]
     fn bloom_set() {
         let arena = Arena::new();
-        let set = BloomSet::new();
+        let mut set = BloomSet::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -215,7 +774,7 @@ mod test {
     #[test]
     fn set_iter() {
         let arena = Arena::new();
-        let set = Set::new();
+        let mut set = Set::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -231,7 +790,7 @@ mod test {
     #[test]
     fn bloom_set_iter() {
         let arena = Arena::new();
-        let set = BloomSet::new();
+        let mut set = BloomSet::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
@@ -247,13 +806,13 @@ mod test {
     #[test]
     fn from_eq() {
         let arena = Arena::new();
-        let set = Set::new();
+        let mut set = Set::new();
 
         set.insert(&arena, "foo");
         set.insert(&arena, "bar");
         set.insert(&arena, "doge");
 
-        let bloom_set = BloomSet::new();
+        let mut bloom_set = BloomSet::new();
 
         bloom_set.insert(&arena, "foo");
         bloom_set.insert(&arena, "bar");
@@ -262,4 +821,158 @@ mod test {
         assert_eq!(set, Set::from(bloom_set));
         assert_eq!(BloomSet::from(set), bloom_set);
     }
+
+    #[test]
+    fn set_eq_unordered() {
+        let arena = Arena::new();
+        let a = Set::from_iter(&arena, vec!["foo", "bar", "doge"]);
+        let b = Set::from_iter(&arena, vec!["doge", "foo", "bar"]);
+        let c = Set::from_iter(&arena, vec!["doge", "foo"]);
+        let d = Set::from_iter(&arena, vec!["doge", "foo", "baz"]);
+
+        assert_ne!(a, b);
+        assert!(a.set_eq_unordered(&b));
+        assert!(b.set_eq_unordered(&a));
+        assert!(!a.set_eq_unordered(&c));
+        assert!(!c.set_eq_unordered(&a));
+        assert!(!a.set_eq_unordered(&d));
+        assert!(Set::<&str>::new().set_eq_unordered(&Set::new()));
+    }
+
+    #[test]
+    fn bloom_set_eq_unordered() {
+        let arena = Arena::new();
+        let mut a = BloomSet::new();
+        let mut b = BloomSet::new();
+
+        for word in ["foo", "bar", "doge"].iter() {
+            a.insert(&arena, *word);
+        }
+
+        for word in ["bar", "doge", "foo"].iter() {
+            b.insert(&arena, *word);
+        }
+
+        assert!(a.set_eq_unordered(&b));
+
+        b.insert(&arena, "moon");
+
+        assert!(!a.set_eq_unordered(&b));
+        assert!(!b.set_eq_unordered(&a));
+    }
+
+    #[test]
+    fn try_insert() {
+        let arena = Arena::new();
+        let mut set = Set::new();
+        let first = String::from("doge");
+        let second = String::from("doge");
+
+        assert_eq!(set.try_insert(&arena, first.as_str()), Ok(()));
+
+        let err = set.try_insert(&arena, second.as_str()).unwrap_err();
+
+        assert!(std::ptr::eq(err.existing(), first.as_str()));
+        assert!(std::ptr::eq(err.value(), second.as_str()));
+        assert_eq!(set.iter().count(), 1);
+    }
+
+    #[test]
+    fn scalable_bloom() {
+        let arena = Arena::new();
+        let mut seen = ScalableBloom::new(100, 0.01);
+
+        assert!(seen.is_empty());
+        assert_eq!(seen.layers(), 0);
+
+        let inserted = (0..100_000u64).filter(|n| seen.insert(&arena, n)).count();
+
+        // A handful of items may be rejected as false positives on insert
+        assert!(inserted > 99_000, "{} inserted", inserted);
+        assert_eq!(seen.len(), inserted);
+        assert!(seen.layers() > 5);
+        assert!((0..100_000u64).all(|n| seen.may_contain(&n)));
+
+        let false_positives = (100_000..200_000u64).filter(|n| seen.may_contain(n)).count();
+
+        assert!(false_positives < 1_000, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn scalable_bloom_strings() {
+        let arena = Arena::new();
+        let mut seen = ScalableBloom::new(4, 0.001);
+
+        assert!(seen.insert(&arena, "https://example.com/"));
+        assert!(seen.insert(&arena, "https://example.com/doge"));
+        assert!(!seen.insert(&arena, "https://example.com/"));
+        assert!(!seen.may_contain("https://example.com/moon"));
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen.layers(), 1);
+    }
+
+    #[test]
+    fn clone_in() {
+        let target = Arena::new();
+
+        let set = {
+            let source = Arena::new();
+            let set = Set::from_iter(&source, vec![source.alloc_str("doge"), source.alloc_str("moon")]);
+
+            set.clone_in(&target)
+        };
+
+        assert!(set.contains("doge"));
+        assert!(set.contains("moon"));
+        assert_eq!(set.iter().count(), 2);
+    }
+
+    #[test]
+    fn policy_set() {
+        let arena = Arena::new();
+        let mut set: PolicySet<&str, AsciiCaseInsensitive> = PolicySet::new();
+
+        assert!(set.is_empty());
+
+        set.insert(&arena, "Div");
+        set.insert(&arena, "SPAN");
+        set.insert(&arena, "div");
+
+        assert_eq!(set.get("DIV"), Some(&"Div"));
+        assert!(set.contains("span"));
+        assert!(!set.contains("p"));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), ["Div", "SPAN"]);
+        assert_eq!(format!("{:?}", set), r#"{"Div", "SPAN"}"#);
+    }
+
+    #[test]
+    fn unsafe_set() {
+        let arena = Arena::new();
+        let set = Set::from_iter(&arena, vec!["foo", "bar"]);
+
+        let raw = set.into_unsafe();
+        let set: Set<&str> = unsafe { raw.into_set() };
+
+        assert!(set.contains("foo"));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn cross_type_eq() {
+        let arena = Arena::new();
+        let set = Set::from_iter(&arena, ["foo", "bar", "doge"]);
+        let mut bloom_set = BloomSet::new();
+
+        for item in ["foo", "bar", "doge"] {
+            bloom_set.insert(&arena, item);
+        }
+
+        assert_eq!(set, bloom_set);
+        assert_eq!(bloom_set, set);
+
+        bloom_set.insert(&arena, "moon");
+
+        assert_ne!(set, bloom_set);
+        assert_ne!(bloom_set, set);
+    }
 }
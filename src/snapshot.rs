@@ -0,0 +1,926 @@
+//! Relocatable snapshots of data structures on the `Arena`.
+//!
+//! A `Snapshot` copies a `List`, `Map` or any other tree of types
+//! implementing `Relocate` into a single contiguous byte buffer, with all
+//! references turned into offsets from the start of that buffer. Loading a
+//! snapshot into an `Arena` is then a single `memcpy` followed by patching
+//! the offsets back into pointers, with no per-node allocation.
+//!
+//! Snapshots use the native layout of the types they contain, so they can
+//! only be loaded by the same build of the program on the same target.
+//! Maps keep the hashes of their keys, so they also have to be queried with
+//! the same hasher they were built with.
+//!
+//! Snapshots can be written to disk with `Snapshot::to_bytes` and read back
+//! in a later run of the program with `Snapshot::from_bytes_versioned`. The
+//! format of the bytes is versioned by `FORMAT_VERSION`, and a snapshot can
+//! carry its own version of the data inside it, so that caches written by
+//! an older version of a program are rejected rather than misread.
+//!
+//! ```rust
+//! use toolshed::Arena;
+//! use toolshed::map::Map;
+//! use toolshed::snapshot::Snapshot;
+//!
+//! let snapshot = {
+//!     let arena = Arena::new();
+//!     let mut map = Map::new();
+//!
+//!     map.insert(&arena, "doge", 10u32);
+//!     map.insert(&arena, "moon", 20u32);
+//!
+//!     Snapshot::new(&map)
+//! };
+//!
+//! let arena = Arena::new();
+//! let map = snapshot.load(&arena);
+//!
+//! assert_eq!(map.get("doge"), Some(10));
+//! assert_eq!(map.get("moon"), Some(20));
+//! ```
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of, size_of_val};
+use std::ptr;
+
+use crate::arena::Arena;
+use crate::cell::CopyCell;
+
+/// Types that can be written into a `Snapshot`.
+///
+/// Implementations for structs write each of their fields with
+/// `SnapshotWriter::write_field`:
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::list::List;
+/// use toolshed::snapshot::{Relocate, Snapshot, SnapshotWriter};
+///
+/// #[derive(Clone, Copy)]
+/// struct Node<'arena> {
+///     name: &'arena str,
+///     children: List<'arena, Node<'arena>>,
+/// }
+///
+/// unsafe impl<'arena> Relocate for Node<'arena> {
+///     type Relocated<'r> = Node<'r>;
+///
+///     fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+///         out.write_field(at, self, &self.name);
+///         out.write_field(at, self, &self.children);
+///     }
+/// }
+///
+/// let snapshot = {
+///     let arena = Arena::new();
+///     let leaf = Node { name: "leaf", children: List::empty() };
+///     let root = Node { name: "root", children: List::from_iter(&arena, vec![leaf, leaf]) };
+///
+///     Snapshot::new(&root)
+/// };
+///
+/// let arena = Arena::new();
+/// let root = snapshot.load(&arena);
+///
+/// assert_eq!(root.name, "root");
+/// assert_eq!(root.children.iter().map(|node| node.name).collect::<Vec<_>>(), ["leaf", "leaf"]);
+/// ```
+///
+/// # Safety
+///
+/// `Relocated<'r>` must be the same type as `Self`, with all of its arena
+/// lifetimes replaced by `'r`. `relocate` must write every field of `self`,
+/// and every reference reachable from it must be written with `write_ref`,
+/// `write_slice` or `write_str`, either directly or through the `Relocate`
+/// implementation of a field. Types that contain enums other than `Option`
+/// of a reference, raw pointers, or interior mutability other than
+/// `CopyCell`, can't implement this trait, and neither can types that need
+/// to be dropped.
+pub unsafe trait Relocate {
+    /// `Self` with all of its arena lifetimes replaced by `'r`.
+    type Relocated<'r>: 'r;
+
+    /// Write `self` into the `out` snapshot, at the `at` byte offset of a
+    /// zeroed slot reserved for it.
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize);
+}
+
+/// A pending write of a value referenced from the snapshot.
+struct Pending {
+    value: *const u8,
+    at: usize,
+    relocate: unsafe fn(*const u8, &mut SnapshotWriter, usize),
+}
+
+unsafe fn relocate_erased<T: Relocate>(value: *const u8, out: &mut SnapshotWriter, at: usize) {
+    (*(value as *const T)).relocate(out, at)
+}
+
+/// The byte buffer a `Snapshot` is being written into, handed to
+/// `Relocate::relocate`.
+///
+/// Values referenced more than once, such as the nodes of a `Map` that are
+/// linked both in its tree and in insertion order, are only written once,
+/// so shared and cyclic references are preserved in the snapshot.
+pub struct SnapshotWriter {
+    data: Vec<u8>,
+    relocations: Vec<usize>,
+    align: usize,
+    written: HashMap<(usize, usize), usize>,
+    pending: Vec<Pending>,
+}
+
+impl SnapshotWriter {
+    fn new() -> Self {
+        SnapshotWriter {
+            data: Vec::new(),
+            relocations: Vec::new(),
+            align: 1,
+            written: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Write the raw `bytes` at the `at` offset.
+    #[inline]
+    pub fn write_bytes(&mut self, at: usize, bytes: &[u8]) {
+        self.data[at..at + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Write the `field` of `parent`, where `parent` is being written at the
+    /// `at` offset.
+    #[inline]
+    pub fn write_field<P, F: Relocate>(&mut self, at: usize, parent: &P, field: &F) {
+        let offset = field as *const F as usize - parent as *const P as usize;
+
+        debug_assert!(offset + size_of::<F>() <= size_of::<P>(), "field is not a part of its parent");
+
+        field.relocate(self, at + offset);
+    }
+
+    /// Write the reference to `value` at the `at` offset, and `value` itself
+    /// into the snapshot, unless it already has been.
+    pub fn write_ref<T: Relocate>(&mut self, at: usize, value: &T) {
+        let key = (value as *const T as usize, size_of::<T>());
+
+        let offset = match self.written.get(&key) {
+            Some(&offset) => offset,
+            None          => {
+                let offset = self.reserve(size_of::<T>(), align_of::<T>());
+
+                self.written.insert(key, offset);
+
+                // Values are written later rather than recursively, so that
+                // long chains of nodes don't overflow the stack.
+                self.pending.push(Pending {
+                    value: value as *const T as *const u8,
+                    at: offset,
+                    relocate: relocate_erased::<T>,
+                });
+
+                offset
+            }
+        };
+
+        self.write_pointer(at, offset);
+    }
+
+    /// Write the `slice` reference at the `at` offset, and the elements of
+    /// the `slice` into the snapshot, unless they already have been.
+    pub fn write_slice<T: Relocate>(&mut self, at: usize, slice: &[T]) {
+        let key = (slice.as_ptr() as usize, size_of_val(slice));
+
+        let offset = match self.written.get(&key) {
+            Some(&offset) => offset,
+            None          => {
+                let offset = self.reserve(size_of_val(slice), align_of::<T>());
+
+                self.written.insert(key, offset);
+
+                for (index, item) in slice.iter().enumerate() {
+                    item.relocate(self, offset + index * size_of::<T>());
+                }
+
+                offset
+            }
+        };
+
+        self.write_fat_pointer(at, offset, slice.len());
+    }
+
+    /// Write the `string` reference at the `at` offset, and the contents of
+    /// the `string` into the snapshot, unless they already have been.
+    #[inline]
+    pub fn write_str(&mut self, at: usize, string: &str) {
+        self.write_slice(at, string.as_bytes());
+    }
+
+    /// Reserve a zeroed slot of `size` bytes aligned to `align`, returning
+    /// its offset.
+    fn reserve(&mut self, size: usize, align: usize) -> usize {
+        let offset = (self.data.len() + align - 1) & !(align - 1);
+
+        self.data.resize(offset + size, 0);
+        self.align = self.align.max(align);
+
+        offset
+    }
+
+    fn write_pointer(&mut self, at: usize, offset: usize) {
+        self.write_bytes(at, &offset.to_ne_bytes());
+        self.relocations.push(at);
+    }
+
+    fn write_fat_pointer(&mut self, at: usize, offset: usize, len: usize) {
+        let (pointer, length) = fat_pointer_layout();
+
+        self.write_pointer(at + pointer, offset);
+        self.write_bytes(at + length, &len.to_ne_bytes());
+    }
+
+    fn finish(mut self) -> Self {
+        while let Some(Pending { value, at, relocate }) = self.pending.pop() {
+            // The pointer was taken from a reference reachable from the root,
+            // which is borrowed for as long as the writer is alive.
+            unsafe { relocate(value, &mut self, at) };
+        }
+
+        self
+    }
+}
+
+impl fmt::Debug for SnapshotWriter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SnapshotWriter")
+            .field("len", &self.data.len())
+            .field("relocations", &self.relocations.len())
+            .finish()
+    }
+}
+
+/// Byte offsets of the pointer and the length within a slice reference.
+/// Their order isn't guaranteed by the language, so it's detected instead.
+fn fat_pointer_layout() -> (usize, usize) {
+    let bytes = [0u8; 2];
+    let slice: &[u8] = &bytes[..1];
+    let words: [usize; 2] = unsafe { std::mem::transmute(slice) };
+
+    if words[0] == bytes.as_ptr() as usize {
+        (0, size_of::<usize>())
+    } else {
+        (size_of::<usize>(), 0)
+    }
+}
+
+/// A relocatable copy of a value of type `T`, along with everything it
+/// references, in a single contiguous byte buffer.
+///
+/// A snapshot doesn't borrow the `Arena` it was taken from, so `T` is the
+/// type of the value with all of its arena lifetimes set to `'static`, such
+/// as `List<'static, &'static str>` for a `List<'arena, &'arena str>`.
+pub struct Snapshot<T> {
+    data: Vec<u8>,
+    relocations: Vec<usize>,
+    root: usize,
+    align: usize,
+    version: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Version of the byte format written by `Snapshot::to_bytes`. Bytes
+/// written with a different format version are rejected when read.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 8] = b"TSHDSNAP";
+const ENDIAN_MARKER: u32 = 0x0102_0304;
+const HEADER_SIZE: usize = 64;
+
+impl<T: Relocate> Snapshot<T> {
+    /// Take a snapshot of `root` and everything reachable from it.
+    pub fn new<R>(root: &R) -> Self
+    where
+        R: Relocate<Relocated<'static> = T>,
+    {
+        let mut writer = SnapshotWriter::new();
+        let root_offset = writer.reserve(size_of::<R>(), align_of::<R>());
+
+        root.relocate(&mut writer, root_offset);
+
+        let writer = writer.finish();
+
+        Snapshot {
+            data: writer.data,
+            relocations: writer.relocations,
+            root: root_offset,
+            align: writer.align,
+            version: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Copy the snapshot into the `arena`, returning a reference to the copy
+    /// of the root value.
+    pub fn load<'arena>(&self, arena: &'arena Arena) -> &'arena T::Relocated<'arena> {
+        debug_assert_eq!(size_of::<T::Relocated<'arena>>(), size_of::<T>());
+
+        let base = arena.require_aligned(self.data.len(), self.align);
+
+        unsafe {
+            ptr::copy_nonoverlapping(self.data.as_ptr(), base, self.data.len());
+
+            for &at in self.relocations.iter() {
+                let slot = base.add(at) as *mut *mut u8;
+                let offset = *(slot as *const usize);
+
+                *slot = base.add(offset);
+            }
+
+            &*(base.add(self.root) as *const T::Relocated<'arena>)
+        }
+    }
+
+    /// Number of bytes the snapshot will take up in the `Arena` when loaded.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the snapshot is of a zero-sized value.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Set the version of the data in the snapshot, checked when reading it
+    /// back with `Snapshot::from_bytes_versioned`. Defaults to `0`.
+    #[inline]
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Get the version of the data in the snapshot.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Serialize the snapshot into bytes that can be written to a file and
+    /// read back with `Snapshot::from_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.relocations.len() * 8 + self.data.len());
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&ENDIAN_MARKER.to_ne_bytes());
+        bytes.extend_from_slice(&(size_of::<usize>() as u32).to_ne_bytes());
+        bytes.extend_from_slice(&FORMAT_VERSION.to_ne_bytes());
+        bytes.extend_from_slice(&self.version.to_ne_bytes());
+        bytes.extend_from_slice(&(size_of::<T>() as u64).to_ne_bytes());
+        bytes.extend_from_slice(&(self.align as u64).to_ne_bytes());
+        bytes.extend_from_slice(&(self.root as u64).to_ne_bytes());
+        bytes.extend_from_slice(&(self.relocations.len() as u64).to_ne_bytes());
+        bytes.extend_from_slice(&(self.data.len() as u64).to_ne_bytes());
+
+        for &at in self.relocations.iter() {
+            bytes.extend_from_slice(&(at as u64).to_ne_bytes());
+        }
+
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Read a snapshot serialized with `Snapshot::to_bytes`, regardless of
+    /// the version of its data.
+    ///
+    /// The header and all offsets are validated, so that loading the
+    /// snapshot never reads or writes outside of its own memory.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must have been produced by `to_bytes` of a `Snapshot<T>`
+    /// taken by the same build of the program. The contents of the values
+    /// can't be validated, so a mismatched type or corrupted data will lead
+    /// to undefined behavior once the snapshot is loaded.
+    #[inline]
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::read(bytes, None)
+    }
+
+    /// Read a snapshot serialized with `Snapshot::to_bytes`, rejecting it
+    /// unless the version of its data is `version`.
+    ///
+    /// ```rust
+    /// use toolshed::Arena;
+    /// use toolshed::list::List;
+    /// use toolshed::snapshot::{Snapshot, SnapshotError};
+    ///
+    /// type Cached = List<'static, &'static str>;
+    ///
+    /// let bytes = {
+    ///     let arena = Arena::new();
+    ///     let list = List::from_iter(&arena, vec!["doge", "moon"]);
+    ///
+    ///     Snapshot::new(&list).with_version(2).to_bytes()
+    /// };
+    ///
+    /// let stale = unsafe { Snapshot::<Cached>::from_bytes_versioned(&bytes, 3) };
+    ///
+    /// assert_eq!(stale.unwrap_err(), SnapshotError::VersionMismatch { found: 2, expected: 3 });
+    ///
+    /// let snapshot = unsafe { Snapshot::<Cached>::from_bytes_versioned(&bytes, 2) }.unwrap();
+    /// let arena = Arena::new();
+    ///
+    /// assert_eq!(snapshot.load(&arena).iter().copied().collect::<Vec<_>>(), ["doge", "moon"]);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Same as `Snapshot::from_bytes`. Bumping the version whenever the types
+    /// in the snapshot change is what makes upholding this practical.
+    #[inline]
+    pub unsafe fn from_bytes_versioned(bytes: &[u8], version: u32) -> Result<Self, SnapshotError> {
+        Self::read(bytes, Some(version))
+    }
+
+    unsafe fn read(bytes: &[u8], expected: Option<u32>) -> Result<Self, SnapshotError> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::InvalidHeader);
+        }
+
+        if reader.u32()? != ENDIAN_MARKER || reader.u32()? as usize != size_of::<usize>() {
+            return Err(SnapshotError::IncompatibleTarget);
+        }
+
+        let format = reader.u32()?;
+
+        if format != FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedFormat(format));
+        }
+
+        let version = reader.u32()?;
+
+        match expected {
+            Some(expected) if expected != version => {
+                return Err(SnapshotError::VersionMismatch { found: version, expected });
+            }
+            _ => {}
+        }
+
+        if reader.usize()? != size_of::<T>() {
+            return Err(SnapshotError::TypeMismatch);
+        }
+
+        let align = reader.usize()?;
+        let root = reader.usize()?;
+        let count = reader.usize()?;
+        let len = reader.usize()?;
+
+        if !align.is_power_of_two() || align < align_of::<T>() || root % align_of::<T>() != 0 {
+            return Err(SnapshotError::TypeMismatch);
+        }
+
+        let mut relocations = Vec::with_capacity(count.min(reader.remaining() / 8));
+
+        for _ in 0..count {
+            relocations.push(reader.usize()?);
+        }
+
+        let data = reader.take(reader.remaining())?.to_vec();
+
+        let root_end = root.checked_add(size_of::<T>()).ok_or(SnapshotError::Corrupted)?;
+
+        if data.len() != len || root_end > len {
+            return Err(SnapshotError::Corrupted);
+        }
+
+        for &at in relocations.iter() {
+            let end = at.checked_add(size_of::<usize>()).ok_or(SnapshotError::Corrupted)?;
+
+            if at % align_of::<usize>() != 0 || end > data.len() {
+                return Err(SnapshotError::Corrupted);
+            }
+
+            let mut offset = [0; size_of::<usize>()];
+
+            offset.copy_from_slice(&data[at..end]);
+
+            if usize::from_ne_bytes(offset) > data.len() {
+                return Err(SnapshotError::Corrupted);
+            }
+        }
+
+        Ok(Snapshot {
+            data,
+            relocations,
+            root,
+            align,
+            version,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for Snapshot<T> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            data: self.data.clone(),
+            relocations: self.relocations.clone(),
+            root: self.root,
+            align: self.align,
+            version: self.version,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Snapshot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("len", &self.data.len())
+            .field("relocations", &self.relocations.len())
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if len > self.remaining() {
+            return Err(SnapshotError::InvalidHeader);
+        }
+
+        let bytes = &self.bytes[self.position..self.position + len];
+
+        self.position += len;
+
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        let mut bytes = [0; 4];
+
+        bytes.copy_from_slice(self.take(4)?);
+
+        Ok(u32::from_ne_bytes(bytes))
+    }
+
+    fn usize(&mut self) -> Result<usize, SnapshotError> {
+        let mut bytes = [0; 8];
+
+        bytes.copy_from_slice(self.take(8)?);
+
+        usize::try_from(u64::from_ne_bytes(bytes)).map_err(|_| SnapshotError::Corrupted)
+    }
+}
+
+/// Error returned by `Snapshot::from_bytes` for bytes that can't be loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The bytes don't start with a snapshot header, or are truncated.
+    InvalidHeader,
+
+    /// The snapshot was written on a target with a different pointer width
+    /// or endianness.
+    IncompatibleTarget,
+
+    /// The bytes were written in an unsupported version of the format.
+    UnsupportedFormat(u32),
+
+    /// The version of the data in the snapshot is not the expected one.
+    VersionMismatch {
+        /// Version found in the snapshot.
+        found: u32,
+
+        /// Version that was expected.
+        expected: u32,
+    },
+
+    /// The snapshot was taken of a value of a different type.
+    TypeMismatch,
+
+    /// The snapshot contains offsets outside of its data.
+    Corrupted,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::InvalidHeader      => f.write_str("invalid snapshot header"),
+            SnapshotError::IncompatibleTarget => f.write_str("snapshot was written on an incompatible target"),
+            SnapshotError::UnsupportedFormat(format) => {
+                write!(f, "unsupported snapshot format version {}, expected {}", format, FORMAT_VERSION)
+            }
+            SnapshotError::VersionMismatch { found, expected } => {
+                write!(f, "snapshot data has version {}, expected {}", found, expected)
+            }
+            SnapshotError::TypeMismatch       => f.write_str("snapshot was taken of a different type"),
+            SnapshotError::Corrupted          => f.write_str("snapshot data is corrupted"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+macro_rules! impl_relocate_primitive {
+    ($( $ty:ty ),*) => {
+        $(
+            unsafe impl Relocate for $ty {
+                type Relocated<'r> = $ty;
+
+                #[inline]
+                fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+                    out.write_bytes(at, &self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_relocate_primitive!(
+    f32, f64,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize
+);
+
+unsafe impl Relocate for () {
+    type Relocated<'r> = ();
+
+    #[inline]
+    fn relocate(&self, _: &mut SnapshotWriter, _: usize) {}
+}
+
+unsafe impl Relocate for bool {
+    type Relocated<'r> = bool;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_bytes(at, &[*self as u8]);
+    }
+}
+
+unsafe impl Relocate for char {
+    type Relocated<'r> = char;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_bytes(at, &(*self as u32).to_ne_bytes());
+    }
+}
+
+unsafe impl<T: Relocate> Relocate for &T {
+    type Relocated<'r> = &'r T::Relocated<'r>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_ref(at, *self);
+    }
+}
+
+/// `None` is represented by a null pointer, which is what the zeroed slot
+/// already holds.
+unsafe impl<T: Relocate> Relocate for Option<&T> {
+    type Relocated<'r> = Option<&'r T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        if let Some(value) = *self {
+            out.write_ref(at, value);
+        }
+    }
+}
+
+unsafe impl<T: Relocate> Relocate for &[T] {
+    type Relocated<'r> = &'r [T::Relocated<'r>];
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_slice(at, self);
+    }
+}
+
+unsafe impl Relocate for &str {
+    type Relocated<'r> = &'r str;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        out.write_str(at, self);
+    }
+}
+
+unsafe impl<T: Relocate + Copy> Relocate for CopyCell<T> {
+    type Relocated<'r> = CopyCell<T::Relocated<'r>>;
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        // `CopyCell` is `repr(transparent)`, so the value sits at the same offset.
+        self.get().relocate(out, at);
+    }
+}
+
+unsafe impl<T: Relocate, const N: usize> Relocate for [T; N] {
+    type Relocated<'r> = [T::Relocated<'r>; N];
+
+    #[inline]
+    fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+        for item in self.iter() {
+            out.write_field(at, self, item);
+        }
+    }
+}
+
+macro_rules! impl_relocate_tuple {
+    ($( ($( $name:ident $index:tt ),*) )*) => {
+        $(
+            unsafe impl<$( $name: Relocate ),*> Relocate for ($( $name, )*) {
+                type Relocated<'r> = ($( $name::Relocated<'r>, )*);
+
+                #[inline]
+                fn relocate(&self, out: &mut SnapshotWriter, at: usize) {
+                    $( out.write_field(at, self, &self.$index); )*
+                }
+            }
+        )*
+    };
+}
+
+impl_relocate_tuple!(
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::list::List;
+    use crate::map::Map;
+    use crate::set::Set;
+    use crate::arena::ARENA_BLOCK;
+
+    #[test]
+    fn list_roundtrip() {
+        let snapshot = {
+            let arena = Arena::new();
+            let list = List::from_iter(&arena, vec!["doge", "to", "the", "moon"]);
+
+            Snapshot::new(&list)
+        };
+
+        let arena = Arena::new();
+        let list = snapshot.load(&arena);
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), ["doge", "to", "the", "moon"]);
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK);
+    }
+
+    #[test]
+    fn long_list() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, 0..100_000u64);
+
+        let copy = Snapshot::new(&list).load(&arena);
+
+        assert_eq!(copy.iter().sum::<u64>(), list.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn map_and_set() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+        let mut set = Set::new();
+
+        for (index, word) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            map.insert(&arena, *word, index as u32);
+            set.insert(&arena, *word);
+        }
+
+        let map_snapshot = Snapshot::new(&map);
+        let set_snapshot = Snapshot::new(&set);
+
+        let other = Arena::new();
+        let mut map_copy = *map_snapshot.load(&other);
+        let set_copy = set_snapshot.load(&other);
+
+        assert_eq!(map_copy.iter().collect::<Vec<_>>(), map.iter().collect::<Vec<_>>());
+        assert_eq!(map_copy.get("c"), Some(2));
+        assert!(set_copy.contains("e"));
+        assert!(!set_copy.contains("f"));
+
+        map_copy.insert(&other, "f", 5);
+
+        assert_eq!(map_copy.get("f"), Some(5));
+        assert_eq!(map.get("f"), None);
+    }
+
+    #[test]
+    fn shared_references() {
+        let arena = Arena::new();
+        let shared: &u64 = arena.alloc(42);
+        let pair = (shared, shared);
+
+        let snapshot = Snapshot::new(&pair);
+        let (a, b) = *snapshot.load(&arena);
+
+        assert_eq!(*a, 42);
+        assert!(std::ptr::eq(a, b));
+        assert_eq!(snapshot.len(), 2 * size_of::<&u64>() + size_of::<u64>());
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let arena = Arena::new();
+        let mut map = Map::new();
+
+        map.insert(&arena, 1u32, "one");
+        map.insert(&arena, 2u32, "two");
+
+        let bytes = Snapshot::new(&map).to_bytes();
+        let snapshot = unsafe { Snapshot::<Map<u32, &str>>::from_bytes(&bytes) }.unwrap();
+
+        let other = Arena::new();
+        let copy = snapshot.load(&other);
+
+        assert_eq!(copy.get(1), Some("one"));
+        assert_eq!(copy.get(2), Some("two"));
+    }
+
+    #[test]
+    fn versions() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u64, 2, 3]);
+        let bytes = Snapshot::new(&list).with_version(7).to_bytes();
+
+        let mut future = bytes.clone();
+
+        future[16..20].copy_from_slice(&(FORMAT_VERSION + 1).to_ne_bytes());
+
+        unsafe {
+            let snapshot = Snapshot::<List<u64>>::from_bytes(&bytes).unwrap();
+
+            assert_eq!(snapshot.version(), 7);
+            assert_eq!(Snapshot::<List<u64>>::from_bytes_versioned(&bytes, 7).unwrap().version(), 7);
+            assert_eq!(
+                Snapshot::<List<u64>>::from_bytes_versioned(&bytes, 8).unwrap_err(),
+                SnapshotError::VersionMismatch { found: 7, expected: 8 }
+            );
+            assert_eq!(
+                Snapshot::<List<u64>>::from_bytes(&future).unwrap_err(),
+                SnapshotError::UnsupportedFormat(FORMAT_VERSION + 1)
+            );
+        }
+
+        let other = Arena::new();
+        let copy = unsafe { Snapshot::<List<u64>>::from_bytes_versioned(&bytes, 7) }.unwrap().load(&other);
+
+        assert_eq!(copy.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(SnapshotError::VersionMismatch { found: 7, expected: 8 }.to_string(), "snapshot data has version 7, expected 8");
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let arena = Arena::new();
+        let list = List::from_iter(&arena, vec![1u64, 2, 3]);
+        let bytes = Snapshot::new(&list).to_bytes();
+
+        let mut corrupted = bytes.clone();
+
+        // Point the first relocation past the end of the data.
+        corrupted[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&u64::MAX.to_ne_bytes());
+
+        // Aligned offsets so close to `usize::MAX` that adding the size of
+        // the value at them overflows.
+        let near_max = (usize::MAX & !7) as u64;
+        let mut overflowing_root = bytes.clone();
+        let mut overflowing_relocation = bytes.clone();
+
+        overflowing_root[40..48].copy_from_slice(&near_max.to_ne_bytes());
+        overflowing_relocation[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&near_max.to_ne_bytes());
+
+        unsafe {
+            assert_eq!(Snapshot::<List<u64>>::from_bytes(b"doge").unwrap_err(), SnapshotError::InvalidHeader);
+            assert_eq!(Snapshot::<List<u64>>::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), SnapshotError::Corrupted);
+            assert_eq!(Snapshot::<(List<u64>, u64)>::from_bytes(&bytes).unwrap_err(), SnapshotError::TypeMismatch);
+            assert_eq!(Snapshot::<List<u64>>::from_bytes(&corrupted).unwrap_err(), SnapshotError::Corrupted);
+            assert_eq!(Snapshot::<List<u64>>::from_bytes(&overflowing_root).unwrap_err(), SnapshotError::Corrupted);
+            assert_eq!(Snapshot::<List<u64>>::from_bytes(&overflowing_relocation).unwrap_err(), SnapshotError::Corrupted);
+            assert!(Snapshot::<List<u64>>::from_bytes(&bytes).is_ok());
+        }
+    }
+}
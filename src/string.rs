@@ -0,0 +1,353 @@
+//! A growable string that can be built directly on the `Arena`.
+
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::str::{self, Utf8Error};
+
+use crate::arena::Arena;
+use crate::vec::ArenaVec;
+
+/// A growable string builder that keeps its contents on the `Arena`.
+///
+/// If the builder is the last thing allocated on the current page of the
+/// arena, it will grow in place. Otherwise the contents are copied to a
+/// new, larger region of the arena, leaving the old one behind.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// # use toolshed::string::ArenaString;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let mut string = ArenaString::new(&arena);
+///
+/// string.push_str("doge");
+/// string.push(' ');
+/// string.push_str("to the moon!");
+///
+/// let str: &str = string.into_str();
+///
+/// assert_eq!(str, "doge to the moon!");
+/// # }
+/// ```
+pub struct ArenaString<'arena> {
+    vec: ArenaVec<'arena, u8>,
+}
+
+impl<'arena> ArenaString<'arena> {
+    /// Create a new, empty `ArenaString`. Nothing is allocated until
+    /// the first push.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        ArenaString {
+            vec: ArenaVec::new(arena),
+        }
+    }
+
+    /// Create a new, empty `ArenaString` with space for at least
+    /// `capacity` bytes.
+    #[inline]
+    pub fn with_capacity(arena: &'arena Arena, capacity: usize) -> Self {
+        ArenaString {
+            vec: ArenaVec::with_capacity(arena, capacity),
+        }
+    }
+
+    /// Length of the string in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns `true` if the string is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Number of bytes the string can hold without having to grow.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Make sure the string has enough space to push at least `additional`
+    /// more bytes without growing.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional)
+    }
+
+    /// Append a string slice to the end of this string.
+    #[inline]
+    pub fn push_str(&mut self, val: &str) {
+        self.vec.extend_from_slice(val.as_bytes());
+    }
+
+    /// Append a `char` to the end of this string.
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Get the contents of the string as `&str`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(self.vec.as_slice()) }
+    }
+
+    /// Finish building the string and return a reference to it. Unused
+    /// capacity is returned to the arena, if possible.
+    #[inline]
+    pub fn into_str(self) -> &'arena str {
+        unsafe { str::from_utf8_unchecked(self.vec.into_slice()) }
+    }
+}
+
+impl<'arena> Deref for ArenaString<'arena> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'arena> AsRef<str> for ArenaString<'arena> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'arena> fmt::Write for ArenaString<'arena> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+
+        Ok(())
+    }
+}
+
+impl<'arena> fmt::Debug for ArenaString<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'arena> fmt::Display for ArenaString<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+/// A writer that appends bytes directly to the `Arena`, implementing both
+/// `std::io::Write` and `std::fmt::Write`, so that serializers and formatters
+/// can target the arena. Created with `Arena::writer`.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// use std::io::Write;
+///
+/// let arena = Arena::new();
+/// let mut writer = arena.writer();
+///
+/// writer.write_all(b"doge").unwrap();
+/// write!(writer, " to the {}!", "moon").unwrap();
+///
+/// assert_eq!(writer.into_str(), Ok("doge to the moon!"));
+/// ```
+pub struct ArenaWriter<'arena> {
+    vec: ArenaVec<'arena, u8>,
+}
+
+impl<'arena> ArenaWriter<'arena> {
+    /// Create a new, empty `ArenaWriter`. Nothing is allocated until
+    /// the first write.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        ArenaWriter {
+            vec: ArenaVec::new(arena),
+        }
+    }
+
+    /// Number of bytes written so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns `true` if nothing has been written yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Get the bytes written so far.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.vec.as_slice()
+    }
+
+    /// Finish writing and return a reference to the bytes. Unused capacity
+    /// is returned to the arena, if possible.
+    #[inline]
+    pub fn into_bytes(self) -> &'arena [u8] {
+        self.vec.into_slice()
+    }
+
+    /// Finish writing and return a reference to the bytes as `&str`, or an
+    /// error if they are not valid UTF-8, which can only happen if invalid
+    /// bytes were written through `std::io::Write`.
+    #[inline]
+    pub fn into_str(self) -> Result<&'arena str, Utf8Error> {
+        str::from_utf8(self.into_bytes())
+    }
+}
+
+impl<'arena> io::Write for ArenaWriter<'arena> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.vec.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.vec.extend_from_slice(buf);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'arena> fmt::Write for ArenaWriter<'arena> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.vec.extend_from_slice(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+impl<'arena> fmt::Debug for ArenaWriter<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ArenaWriter").field(&self.as_bytes()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_str() {
+        let arena = Arena::new();
+        let mut string = ArenaString::new(&arena);
+
+        assert_eq!(string.is_empty(), true);
+        assert_eq!(unsafe { arena.offset() }, 0);
+
+        string.push_str("foo");
+        string.push_str("bar");
+        string.push('!');
+
+        assert_eq!(string.as_str(), "foobar!");
+        assert_eq!(string.len(), 7);
+        assert_eq!(string.into_str(), "foobar!");
+        assert_eq!(unsafe { arena.offset() }, 8);
+    }
+
+    #[test]
+    fn grows_in_place() {
+        let arena = Arena::new();
+        let mut string = ArenaString::with_capacity(&arena, 8);
+        let ptr = string.as_ptr();
+
+        string.push_str("doge to the moon!");
+
+        // Nothing else got allocated, string didn't have to move
+        assert_eq!(string.as_ptr(), ptr);
+
+        assert_eq!(string.into_str(), "doge to the moon!");
+        assert_eq!(unsafe { arena.offset() }, 24);
+    }
+
+    #[test]
+    fn moves_when_not_last() {
+        let arena = Arena::new();
+        let mut string = ArenaString::with_capacity(&arena, 8);
+
+        string.push_str("doge");
+
+        let other = arena.alloc(42u64);
+
+        string.push_str(" to the moon!");
+
+        assert_eq!(other, &42);
+        assert_eq!(string.into_str(), "doge to the moon!");
+        assert_eq!(unsafe { arena.offset() }, 8 + 8 + 24);
+    }
+
+    #[test]
+    fn write_fmt() {
+        use std::fmt::Write;
+
+        let arena = Arena::new();
+        let mut string = ArenaString::new(&arena);
+
+        let (who, target) = ("doge", "moon");
+
+        write!(string, "{} to the {}!", who, target).unwrap();
+
+        assert_eq!(&*string, "doge to the moon!");
+    }
+
+    #[test]
+    fn writer() {
+        use std::io::Write;
+
+        let arena = Arena::new();
+        let mut writer = arena.writer();
+
+        assert_eq!(writer.is_empty(), true);
+
+        writer.write_all(b"doge").unwrap();
+        fmt::Write::write_fmt(&mut writer, format_args!(" {}", 42)).unwrap();
+
+        assert_eq!(writer.len(), 7);
+        assert_eq!(writer.as_bytes(), b"doge 42");
+        assert_eq!(writer.into_str(), Ok("doge 42"));
+        assert_eq!(unsafe { arena.offset() }, 8);
+    }
+
+    #[test]
+    fn writer_invalid_utf8() {
+        use std::io::Write;
+
+        let arena = Arena::new();
+        let mut writer = arena.writer();
+
+        writer.write_all(&[0xFF, 0xFE]).unwrap();
+
+        assert!(writer.into_str().is_err());
+
+        let mut writer = ArenaWriter::new(&arena);
+
+        writer.write_all(&[0xFF, 0xFE]).unwrap();
+
+        assert_eq!(writer.into_bytes(), &[0xFF, 0xFE]);
+    }
+}
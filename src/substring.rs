@@ -0,0 +1,258 @@
+//! A substring index built on the `Arena`.
+
+use crate::arena::Arena;
+use crate::list::List;
+use crate::map::Map;
+use crate::vec::ArenaVec;
+
+const NONE: usize = usize::MAX;
+
+#[derive(Clone, Copy)]
+struct State<'arena> {
+    /// Length, in chars, of the longest substring recognized by this state.
+    len: usize,
+    /// Length, in bytes, of the longest substring recognized by this state.
+    byte_len: usize,
+    /// Suffix link.
+    link: usize,
+    /// Byte index right after the end of the first occurrence.
+    end: usize,
+    /// Cloned states don't correspond to an occurrence of their own.
+    cloned: bool,
+    next: Map<'arena, char, usize>,
+    children: List<'arena, usize>,
+}
+
+/// An index of all substrings of a `&str`, implemented as a suffix automaton.
+/// Building the index takes linear time and space, and all of its state is
+/// allocated on the `Arena`.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// # use toolshed::substring::SubstringIndex;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let index = SubstringIndex::new(&arena, "doge to the moon");
+///
+/// assert!(index.contains("to the"));
+/// assert!(!index.contains("to a"));
+/// assert_eq!(index.find_all(&arena, "o"), &[1, 6, 13, 14]);
+/// assert_eq!(index.longest_common_substring("the moonwalk"), "the moon");
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct SubstringIndex<'arena> {
+    text: &'arena str,
+    states: &'arena [State<'arena>],
+}
+
+impl<'arena> SubstringIndex<'arena> {
+    /// Build a new index for the `text`.
+    pub fn new(arena: &'arena Arena, text: &'arena str) -> Self {
+        let mut states = ArenaVec::with_capacity(arena, 2 * text.chars().count() + 1);
+
+        states.push(State {
+            len: 0,
+            byte_len: 0,
+            link: NONE,
+            end: 0,
+            cloned: false,
+            next: Map::new(),
+            children: List::empty(),
+        });
+
+        let mut last = 0;
+
+        for (index, ch) in text.char_indices() {
+            let cur = states.len();
+
+            states.push(State {
+                len: states[last].len + 1,
+                byte_len: states[last].byte_len + ch.len_utf8(),
+                link: 0,
+                end: index + ch.len_utf8(),
+                cloned: false,
+                next: Map::new(),
+                children: List::empty(),
+            });
+
+            let mut p = last;
+
+            while p != NONE && !states[p].next.contains_key(ch) {
+                states[p].next.insert(arena, ch, cur);
+                p = states[p].link;
+            }
+
+            if p != NONE {
+                let q = states[p].next.get(ch).unwrap();
+
+                if states[p].len + 1 == states[q].len {
+                    states[cur].link = q;
+                } else {
+                    let clone = states.len();
+
+                    states.push(State {
+                        len: states[p].len + 1,
+                        byte_len: states[p].byte_len + ch.len_utf8(),
+                        link: states[q].link,
+                        end: states[q].end,
+                        cloned: true,
+                        next: states[q].next.map_values_in(arena, |_, state| state),
+                        children: List::empty(),
+                    });
+
+                    while p != NONE && states[p].next.get(ch) == Some(q) {
+                        states[p].next.insert(arena, ch, clone);
+                        p = states[p].link;
+                    }
+
+                    states[q].link = clone;
+                    states[cur].link = clone;
+                }
+            }
+
+            last = cur;
+        }
+
+        let states = states.into_slice();
+
+        for index in 1..states.len() {
+            let link = states[index].link;
+
+            states[link].children.prepend(arena, index);
+        }
+
+        SubstringIndex {
+            text,
+            states,
+        }
+    }
+
+    /// Get the text this index was built for.
+    #[inline]
+    pub fn text(&self) -> &'arena str {
+        self.text
+    }
+
+    fn find_state(&self, pattern: &str) -> Option<usize> {
+        pattern.chars().try_fold(0, |state, ch| self.states[state].next.get(ch))
+    }
+
+    /// Returns `true` if the `pattern` is a substring of the indexed text.
+    #[inline]
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.find_state(pattern).is_some()
+    }
+
+    /// Find byte indices of all occurrences of the `pattern` in the indexed text,
+    /// allocated on the arena in ascending order. Occurrences may overlap.
+    pub fn find_all(&self, arena: &'arena Arena, pattern: &str) -> &'arena [usize] {
+        let state = match self.find_state(pattern) {
+            Some(state) => state,
+            None        => return &[],
+        };
+
+        let mut found = ArenaVec::new(arena);
+        let mut stack = ArenaVec::new(arena);
+
+        stack.push(state);
+
+        while let Some(state) = stack.pop() {
+            let state = &self.states[state];
+
+            if !state.cloned {
+                found.push(state.end - pattern.len());
+            }
+
+            stack.extend(state.children.iter().cloned());
+        }
+
+        let found = found.into_slice();
+
+        found.sort_unstable();
+        found
+    }
+
+    /// Find the longest substring of the indexed text that is also a substring
+    /// of `other`. If there are multiple substrings of the same length, the one
+    /// found first in `other` is returned.
+    pub fn longest_common_substring(&self, other: &str) -> &'arena str {
+        let mut state = 0;
+        let mut len = 0;
+        let mut best = (0, 0);
+
+        for ch in other.chars() {
+            while state != 0 && !self.states[state].next.contains_key(ch) {
+                state = self.states[state].link;
+                len = self.states[state].byte_len;
+            }
+
+            if let Some(next) = self.states[state].next.get(ch) {
+                state = next;
+                len += ch.len_utf8();
+            }
+
+            if len > best.1 {
+                best = (self.states[state].end, len);
+            }
+        }
+
+        let (end, len) = best;
+
+        &self.text[end - len..end]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        let arena = Arena::new();
+        let index = SubstringIndex::new(&arena, "abcbc");
+
+        for pattern in &["", "a", "b", "c", "ab", "bc", "cb", "abcbc", "bcbc", "cbc"] {
+            assert!(index.contains(pattern), "{}", pattern);
+        }
+
+        for pattern in &["d", "ac", "ba", "cc", "abcbcb", "abcc"] {
+            assert!(!index.contains(pattern), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn find_all() {
+        let arena = Arena::new();
+        let index = SubstringIndex::new(&arena, "abababa");
+
+        assert_eq!(index.find_all(&arena, "aba"), &[0, 2, 4]);
+        assert_eq!(index.find_all(&arena, "b"), &[1, 3, 5]);
+        assert_eq!(index.find_all(&arena, "abababa"), &[0]);
+        assert!(index.find_all(&arena, "c").is_empty());
+    }
+
+    #[test]
+    fn find_all_unicode() {
+        let arena = Arena::new();
+        let index = SubstringIndex::new(&arena, "żółć i żółw");
+
+        assert_eq!(index.find_all(&arena, "żół"), &[0, 11]);
+        assert_eq!(&index.text()[11..], "żółw");
+    }
+
+    #[test]
+    fn longest_common_substring() {
+        let arena = Arena::new();
+        let index = SubstringIndex::new(&arena, "xabxac");
+
+        assert_eq!(index.longest_common_substring("abcabxabcd"), "abxa");
+        assert_eq!(index.longest_common_substring("qqq"), "");
+        assert_eq!(index.longest_common_substring("ac"), "ac");
+
+        let index = SubstringIndex::new(&arena, "zażółć gęślą jaźń");
+
+        assert_eq!(index.longest_common_substring("gęś"), "gęś");
+        assert_eq!(index.longest_common_substring("óść"), "ó");
+    }
+}
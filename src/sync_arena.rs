@@ -0,0 +1,249 @@
+use std::mem::{align_of, size_of, size_of_val};
+use std::pin::Pin;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::arena::{padded_size, Page, Uninitialized, ARENA_BLOCK};
+
+/// A page of the `SyncArena` along with its own atomic bump offset.
+struct Chunk {
+    page: Page,
+    offset: AtomicUsize,
+}
+
+impl Chunk {
+    /// Take `size` bytes aligned to `align` from the chunk, if they fit.
+    #[inline]
+    fn bump(&self, size: usize, align: usize) -> Option<*mut u8> {
+        let base = self.page.as_ptr() as usize;
+        let mut offset = self.offset.load(Ordering::Relaxed);
+
+        loop {
+            let start = ((base + offset + (align - 1)) & !(align - 1)) - base;
+            let end = start + size;
+
+            if end > self.page.size() {
+                return None;
+            }
+
+            // Every thread bumps to a disjoint range, so there is nothing
+            // else to synchronize here.
+            match self.offset.compare_exchange_weak(offset, end, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Some(unsafe { self.page.as_ptr().add(start) }),
+                Err(current) => offset = current,
+            }
+        }
+    }
+}
+
+/// A thread-safe variant of the `Arena`. Multiple threads can allocate on a
+/// shared `&SyncArena` concurrently, and the references it returns can be
+/// shared across threads, as long as the allocated types allow it.
+///
+/// Allocations bump an atomic offset on the current page, only taking a
+/// lock when a new page needs to be added, which makes `SyncArena` somewhat
+/// slower than the `Arena`.
+///
+/// ```rust
+/// use toolshed::SyncArena;
+///
+/// let arena = SyncArena::new();
+///
+/// let numbers: Vec<&u64> = std::thread::scope(|scope| {
+///     let handles: Vec<_> = (0..4u64)
+///         .map(|n| {
+///             let arena = &arena;
+///
+///             scope.spawn(move || &*arena.alloc(n))
+///         })
+///         .collect();
+///
+///     handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+/// });
+///
+/// assert_eq!(numbers, [&0, &1, &2, &3]);
+/// ```
+pub struct SyncArena {
+    current: AtomicPtr<Chunk>,
+    chunks: Mutex<Vec<Pin<Box<Chunk>>>>,
+    allocated: AtomicUsize,
+}
+
+/// All mutation happens through atomics or under the `Mutex`, and chunks
+/// are never freed until the `SyncArena` is dropped.
+unsafe impl Send for SyncArena {}
+unsafe impl Sync for SyncArena {}
+
+impl Default for SyncArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyncArena {
+    /// Create a new `SyncArena` with a single preallocated 64KiB page.
+    pub fn new() -> Self {
+        let arena = SyncArena {
+            current: AtomicPtr::new(ptr::null_mut()),
+            chunks: Mutex::new(Vec::new()),
+            allocated: AtomicUsize::new(0),
+        };
+
+        let chunk = arena.push_chunk(&mut arena.chunks.lock().unwrap(), ARENA_BLOCK, 1);
+
+        arena.current.store(chunk, Ordering::Release);
+        arena
+    }
+
+    /// Total number of bytes of heap memory held by the arena.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
+    /// Put the value onto the page of the arena and return a reference to it.
+    #[inline]
+    pub fn alloc<T: Sized + Copy>(&self, value: T) -> &mut T {
+        self.alloc_uninitialized().init(value)
+    }
+
+    /// Allocate enough bytes for the type `T`, then return an `Uninitialized` pointer to the memory.
+    #[inline]
+    pub fn alloc_uninitialized<'arena, T: Sized + Copy>(&'arena self) -> Uninitialized<'arena, T> {
+        unsafe { Uninitialized::from_raw(self.require(size_of::<T>(), align_of::<T>()) as *mut T) }
+    }
+
+    /// Allocate a slice of `T` onto the arena and return a reference to it.
+    pub fn alloc_slice<'arena, T: Copy>(&'arena self, val: &[T]) -> &'arena [T] {
+        let ptr = self.require(size_of_val(val), align_of::<T>()) as *mut T;
+
+        unsafe {
+            ptr::copy_nonoverlapping(val.as_ptr(), ptr, val.len());
+            std::slice::from_raw_parts(ptr, val.len())
+        }
+    }
+
+    /// Allocate an `&str` slice onto the arena and return a reference to it.
+    pub fn alloc_str<'arena>(&'arena self, val: &str) -> &'arena str {
+        unsafe { std::str::from_utf8_unchecked(self.alloc_slice(val.as_bytes())) }
+    }
+
+    fn require(&self, size: usize, align: usize) -> *mut u8 {
+        if size > ARENA_BLOCK || align > ARENA_BLOCK {
+            return self.alloc_dedicated(size, align);
+        }
+
+        let size = padded_size(size);
+
+        loop {
+            let current = self.current.load(Ordering::Acquire);
+
+            if let Some(ptr) = unsafe { (*current).bump(size, align) } {
+                return ptr;
+            }
+
+            let mut chunks = self.chunks.lock().unwrap_or_else(|err| err.into_inner());
+
+            // Another thread might have already added a new page while
+            // this one was waiting for the lock.
+            if self.current.load(Ordering::Acquire) == current {
+                let chunk = self.push_chunk(&mut chunks, ARENA_BLOCK, 1);
+
+                self.current.store(chunk, Ordering::Release);
+
+                if let Some(ptr) = unsafe { (*chunk).bump(size, align) } {
+                    return ptr;
+                }
+
+                // Padding required for the alignment doesn't fit on a fresh page
+                drop(chunks);
+
+                return self.alloc_dedicated(size, align);
+            }
+        }
+    }
+
+    /// Allocate a chunk just for a single allocation.
+    fn alloc_dedicated(&self, size: usize, align: usize) -> *mut u8 {
+        let mut chunks = self.chunks.lock().unwrap_or_else(|err| err.into_inner());
+        let chunk = self.push_chunk(&mut chunks, size, align);
+
+        unsafe { (*chunk).page.as_ptr() }
+    }
+
+    /// Add a new chunk, returning a pointer to it. Chunks are pinned, so the
+    /// pointer remains valid for as long as the `SyncArena` is alive.
+    fn push_chunk(&self, chunks: &mut Vec<Pin<Box<Chunk>>>, size: usize, align: usize) -> *mut Chunk {
+        let page = Page::alloc(size, align).unwrap_or_else(|err| err.handle());
+
+        self.allocated.fetch_add(page.size(), Ordering::Relaxed);
+
+        let chunk = Box::pin(Chunk {
+            page,
+            offset: AtomicUsize::new(0),
+        });
+        let ptr = &*chunk as *const Chunk as *mut Chunk;
+
+        chunks.push(chunk);
+
+        ptr
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn alloc() {
+        let arena = SyncArena::new();
+
+        assert_eq!(arena.alloc(42u64), &42);
+        assert_eq!(arena.alloc_slice(&[1u16, 2, 3]), &[1, 2, 3]);
+        assert_eq!(arena.alloc_str("doge"), "doge");
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK);
+    }
+
+    #[test]
+    fn alloc_huge() {
+        let arena = SyncArena::new();
+        let big = [7u8; ARENA_BLOCK * 2];
+
+        assert_eq!(arena.alloc_slice(&big).len(), ARENA_BLOCK * 2);
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 3);
+
+        // Still allocates on the first page
+        arena.alloc(1u8);
+
+        assert_eq!(arena.allocated_bytes(), ARENA_BLOCK * 3);
+    }
+
+    #[test]
+    fn alloc_from_many_threads() {
+        let arena = SyncArena::new();
+
+        let lists: Vec<Vec<&u64>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8u64)
+                .map(|n| {
+                    let arena = &arena;
+
+                    scope.spawn(move || {
+                        (0..10_000u64).map(|i| &*arena.alloc(n * 10_000 + i)).collect()
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        for (n, list) in lists.iter().enumerate() {
+            for (i, value) in list.iter().enumerate() {
+                assert_eq!(**value, (n * 10_000 + i) as u64);
+            }
+        }
+
+        assert!(arena.allocated_bytes() >= 8 * 10_000 * 8);
+    }
+}
@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::arena::ARENA_BLOCK;
+
+/// An arena that holds values of a single type `T` in contiguous chunks.
+///
+/// Unlike the `Arena`, the `TypedArena` remembers everything that has been
+/// allocated on it, and can iterate over all the values in order of
+/// allocation. Since values of the same type are stored next to each other,
+/// walking them is very cache friendly, which makes it a good fit for passes
+/// over all AST nodes of one kind. Values don't need to be `Copy`, and their
+/// destructors are run when the `TypedArena` is dropped.
+///
+/// ```rust
+/// use toolshed::TypedArena;
+///
+/// let arena = TypedArena::new();
+///
+/// let foo: &String = arena.alloc(String::from("foo"));
+/// let bar: &String = arena.alloc(String::from("bar"));
+///
+/// assert_eq!(foo, "foo");
+/// assert_eq!(bar, "bar");
+///
+/// let all: Vec<&String> = arena.iter().collect();
+///
+/// assert_eq!(all, ["foo", "bar"]);
+/// ```
+pub struct TypedArena<T> {
+    chunks: RefCell<Vec<Vec<T>>>,
+}
+
+impl<T> Default for TypedArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TypedArena<T> {
+    /// Create a new `TypedArena`, with the first chunk taking up about 64KiB.
+    pub fn new() -> Self {
+        Self::with_capacity(ARENA_BLOCK / cmp::max(size_of::<T>(), 1))
+    }
+
+    /// Create a new `TypedArena` with room for `capacity` values in the first
+    /// chunk. Each subsequent chunk is twice as big as the previous one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        TypedArena {
+            chunks: RefCell::new(vec![Vec::with_capacity(cmp::max(capacity, 1))]),
+        }
+    }
+
+    /// Put the value onto the arena and return a reference to it.
+    #[inline]
+    pub fn alloc(&self, value: T) -> &T {
+        let mut chunks = self.chunks.borrow_mut();
+
+        let capacity = match chunks.last() {
+            Some(chunk) if chunk.len() < chunk.capacity() => None,
+            Some(chunk) => Some(chunk.capacity().saturating_mul(2)),
+            None        => Some(1),
+        };
+
+        if let Some(capacity) = capacity {
+            chunks.push(Vec::with_capacity(capacity));
+        }
+
+        let chunk = chunks.last_mut().expect("TypedArena always has a chunk");
+
+        // Chunks never grow past their capacity, so the values never move.
+        chunk.push(value);
+
+        unsafe { &*chunk.as_ptr().add(chunk.len() - 1) }
+    }
+
+    /// Get the number of values allocated on the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if nothing has been allocated on the arena yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over all values allocated on the arena, in order
+    /// of allocation. Values allocated while iterating are also included.
+    #[inline]
+    pub fn iter(&self) -> TypedArenaIter<'_, T> {
+        TypedArenaIter {
+            arena: self,
+            chunk: 0,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator over mutable references to all values allocated
+    /// on the arena, in order of allocation.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks.get_mut().iter_mut().flat_map(|chunk| chunk.iter_mut())
+    }
+}
+
+/// An iterator over the values allocated on a `TypedArena`.
+pub struct TypedArenaIter<'arena, T> {
+    arena: &'arena TypedArena<T>,
+    chunk: usize,
+    index: usize,
+}
+
+impl<'arena, T> Iterator for TypedArenaIter<'arena, T> {
+    type Item = &'arena T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'arena T> {
+        let chunks = self.arena.chunks.borrow();
+
+        loop {
+            let chunk = chunks.get(self.chunk)?;
+
+            if self.index < chunk.len() {
+                let value = unsafe { &*chunk.as_ptr().add(self.index) };
+
+                self.index += 1;
+
+                return Some(value);
+            }
+
+            // Only the last chunk can have room for more values
+            if self.chunk + 1 == chunks.len() {
+                return None;
+            }
+
+            self.chunk += 1;
+            self.index = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn alloc_and_iter() {
+        let arena = TypedArena::with_capacity(4);
+
+        for n in 0..100u64 {
+            assert_eq!(arena.alloc(n), &n);
+        }
+
+        assert_eq!(arena.len(), 100);
+        assert!(arena.iter().copied().eq(0..100));
+
+        // 4 + 8 + 16 + 32 + 64
+        assert_eq!(arena.chunks.borrow().len(), 5);
+    }
+
+    #[test]
+    fn references_are_stable() {
+        let arena = TypedArena::with_capacity(1);
+
+        let first = arena.alloc(1u32);
+        let second = arena.alloc(2u32);
+
+        for n in 3..1000 {
+            arena.alloc(n);
+        }
+
+        assert_eq!(*first, 1);
+        assert_eq!(*second, 2);
+    }
+
+    #[test]
+    fn alloc_while_iterating() {
+        let arena = TypedArena::with_capacity(2);
+
+        arena.alloc(1u32);
+
+        let mut seen = Vec::new();
+
+        for n in arena.iter() {
+            if *n < 5 {
+                arena.alloc(n + 1);
+            }
+
+            seen.push(*n);
+        }
+
+        assert_eq!(seen, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut arena = TypedArena::with_capacity(2);
+
+        for n in 0..5u32 {
+            arena.alloc(n);
+        }
+
+        for n in arena.iter_mut() {
+            *n *= 10;
+        }
+
+        assert!(arena.iter().copied().eq([0, 10, 20, 30, 40]));
+    }
+
+    #[test]
+    fn empty() {
+        let arena: TypedArena<()> = TypedArena::new();
+
+        assert_eq!(arena.is_empty(), true);
+        assert_eq!(arena.iter().next(), None);
+
+        arena.alloc(());
+
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn drops_values() {
+        let counter = Rc::new(());
+
+        {
+            let arena = TypedArena::with_capacity(1);
+
+            for _ in 0..10 {
+                arena.alloc(counter.clone());
+            }
+
+            assert_eq!(Rc::strong_count(&counter), 11);
+        }
+
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,220 @@
+//! A dynamically typed, JSON-like document model on the `Arena`.
+
+use crate::list::List;
+use crate::map::Map;
+
+/// A dynamically typed value, with all of its contents allocated on the
+/// `Arena`. This is useful when the structure of the data being parsed is
+/// not known ahead of time.
+///
+/// ```rust
+/// use toolshed::Arena;
+/// use toolshed::list::List;
+/// use toolshed::map::Map;
+/// use toolshed::value::Value;
+///
+/// let arena = Arena::new();
+/// let mut map = Map::new();
+///
+/// map.insert(&arena, "name", Value::Str("doge"));
+/// map.insert(&arena, "tags", Value::List(List::from_iter(&arena, vec![
+///     Value::Str("to"),
+///     Value::Str("the"),
+///     Value::Str("moon"),
+/// ])));
+///
+/// let doc = Value::Map(map);
+///
+/// assert_eq!(doc.get("name"), Some(Value::Str("doge")));
+/// assert_eq!(doc.get("tags").unwrap().index(2), Some(Value::Str("moon")));
+/// assert_eq!(doc.get("age"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'arena> {
+    /// Absence of a value.
+    Null,
+
+    /// A boolean.
+    Bool(bool),
+
+    /// An integer.
+    Int(i64),
+
+    /// A floating point number.
+    Float(f64),
+
+    /// A string.
+    Str(&'arena str),
+
+    /// A list of values.
+    List(List<'arena, Value<'arena>>),
+
+    /// A map of string keys to values, in order of insertion.
+    Map(Map<'arena, &'arena str, Value<'arena>>),
+}
+
+impl<'arena> Value<'arena> {
+    /// Returns `true` if the value is `Null`.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Get the boolean, if the value is a `Bool`.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _                  => None,
+        }
+    }
+
+    /// Get the integer, if the value is an `Int`.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(value) => Some(value),
+            _                 => None,
+        }
+    }
+
+    /// Get the number as a float, if the value is a `Float` or an `Int`.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Float(value) => Some(value),
+            Value::Int(value)   => Some(value as f64),
+            _                   => None,
+        }
+    }
+
+    /// Get the string, if the value is a `Str`.
+    #[inline]
+    pub fn as_str(&self) -> Option<&'arena str> {
+        match *self {
+            Value::Str(value) => Some(value),
+            _                 => None,
+        }
+    }
+
+    /// Get the list, if the value is a `List`.
+    #[inline]
+    pub fn as_list(&self) -> Option<List<'arena, Value<'arena>>> {
+        match *self {
+            Value::List(list) => Some(list),
+            _                 => None,
+        }
+    }
+
+    /// Get the map, if the value is a `Map`.
+    #[inline]
+    pub fn as_map(&self) -> Option<Map<'arena, &'arena str, Value<'arena>>> {
+        match *self {
+            Value::Map(map) => Some(map),
+            _               => None,
+        }
+    }
+
+    /// Get the value for the `key`, if the value is a `Map` containing it.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<Value<'arena>> {
+        self.as_map()?.get_by(key)
+    }
+
+    /// Get the element at `index`, if the value is a `List` long enough.
+    #[inline]
+    pub fn index(&self, index: usize) -> Option<Value<'arena>> {
+        self.as_list()?.get(index).copied()
+    }
+}
+
+impl<'arena> Default for Value<'arena> {
+    #[inline]
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
+impl<'arena> From<bool> for Value<'arena> {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl<'arena> From<i64> for Value<'arena> {
+    #[inline]
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl<'arena> From<f64> for Value<'arena> {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl<'arena> From<&'arena str> for Value<'arena> {
+    #[inline]
+    fn from(value: &'arena str) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl<'arena> From<List<'arena, Value<'arena>>> for Value<'arena> {
+    #[inline]
+    fn from(value: List<'arena, Value<'arena>>) -> Self {
+        Value::List(value)
+    }
+}
+
+impl<'arena> From<Map<'arena, &'arena str, Value<'arena>>> for Value<'arena> {
+    #[inline]
+    fn from(value: Map<'arena, &'arena str, Value<'arena>>) -> Self {
+        Value::Map(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Arena;
+
+    #[test]
+    fn accessors() {
+        assert_eq!(Value::Null.is_null(), true);
+        assert_eq!(Value::Bool(true).as_bool(), Some(true));
+        assert_eq!(Value::Int(42).as_i64(), Some(42));
+        assert_eq!(Value::Int(42).as_f64(), Some(42.0));
+        assert_eq!(Value::Float(0.5).as_f64(), Some(0.5));
+        assert_eq!(Value::Float(0.5).as_i64(), None);
+        assert_eq!(Value::Str("doge").as_str(), Some("doge"));
+        assert_eq!(Value::Str("doge").as_list(), None);
+        assert_eq!(Value::default(), Value::Null);
+    }
+
+    #[test]
+    fn nested() {
+        let arena = Arena::new();
+        let mut inner = Map::new();
+        let mut outer = Map::new();
+
+        inner.insert(&arena, "count", Value::from(3));
+        outer.insert(&arena, "inner", Value::from(inner));
+        outer.insert(&arena, "list", Value::from(List::from_iter(&arena, vec![
+            Value::from(true),
+            Value::Null,
+        ])));
+
+        let doc = Value::from(outer);
+        let key = String::from("inner");
+
+        assert_eq!(doc.get(&key).and_then(|inner| inner.get("count")), Some(Value::Int(3)));
+        assert_eq!(doc.get("list").unwrap().index(0), Some(Value::Bool(true)));
+        assert_eq!(doc.get("list").unwrap().index(1), Some(Value::Null));
+        assert_eq!(doc.get("list").unwrap().index(2), None);
+        assert_eq!(doc.index(0), None);
+    }
+}
@@ -0,0 +1,304 @@
+//! A growable vector that can be built directly on the `Arena`.
+
+use std::fmt::{self, Debug};
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::slice;
+
+use crate::arena::{Arena, padded_size};
+
+/// A growable vector of `Copy` elements that keeps its contents on the `Arena`.
+///
+/// If the vector is the last thing allocated on the current page of the
+/// arena, it will grow in place. Otherwise the contents are copied to a
+/// new region of the arena with double the capacity, leaving the old one
+/// behind.
+///
+/// ```rust
+/// # use toolshed::Arena;
+/// # use toolshed::vec::ArenaVec;
+/// # fn main() {
+/// let arena = Arena::new();
+/// let mut vec = ArenaVec::new(&arena);
+///
+/// vec.push(10);
+/// vec.push(20);
+/// vec.extend_from_slice(&[30, 40]);
+///
+/// let slice: &[u64] = vec.into_slice();
+///
+/// assert_eq!(slice, &[10, 20, 30, 40]);
+/// # }
+/// ```
+pub struct ArenaVec<'arena, T: Copy> {
+    arena: &'arena Arena,
+    ptr: *mut T,
+    len: usize,
+    cap: usize,
+}
+
+impl<'arena, T: Copy> ArenaVec<'arena, T> {
+    /// Create a new, empty `ArenaVec`. Nothing is allocated until
+    /// the first push.
+    #[inline]
+    pub fn new(arena: &'arena Arena) -> Self {
+        ArenaVec {
+            arena,
+            ptr: ptr::NonNull::dangling().as_ptr(),
+            len: 0,
+            cap: if size_of::<T>() == 0 { usize::MAX } else { 0 },
+        }
+    }
+
+    /// Create a new, empty `ArenaVec` with space for at least
+    /// `capacity` elements.
+    #[inline]
+    pub fn with_capacity(arena: &'arena Arena, capacity: usize) -> Self {
+        let mut vec = ArenaVec::new(arena);
+
+        vec.reserve(capacity);
+        vec
+    }
+
+    /// Number of elements in the vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of elements the vector can hold without having to grow.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Make sure the vector has enough space to push at least `additional`
+    /// more elements without growing.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+
+        if required <= self.cap {
+            return;
+        }
+
+        let new_cap = required.max(self.cap.saturating_mul(2));
+
+        if self.cap != 0 && self.arena.resize_in_place(
+            self.ptr as *mut u8,
+            Self::size_in_bytes(self.cap),
+            Self::size_in_bytes(new_cap),
+        ) {
+            self.cap = new_cap;
+            return;
+        }
+
+        let new_ptr = unsafe {
+            self.arena.alloc_uninitialized_slice::<T>(new_cap).as_mut_slice().as_mut_ptr()
+        };
+
+        unsafe { ptr::copy_nonoverlapping(self.ptr, new_ptr, self.len) };
+
+        self.ptr = new_ptr;
+        self.cap = new_cap;
+    }
+
+    /// Append an element to the end of the vector.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.cap {
+            self.reserve(1);
+        }
+
+        unsafe { self.ptr.add(self.len).write(value) };
+
+        self.len += 1;
+    }
+
+    /// Remove the last element from the vector and return it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    /// Append all elements of a slice to the end of the vector.
+    #[inline]
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+
+        unsafe { ptr::copy_nonoverlapping(values.as_ptr(), self.ptr.add(self.len), values.len()) };
+
+        self.len += values.len();
+    }
+
+    /// Remove all elements from the vector, keeping its capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Get the contents of the vector as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Get the contents of the vector as a mutable slice.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    /// Finish building the vector and return a reference to its contents.
+    /// Unused capacity is returned to the arena, if possible.
+    #[inline]
+    pub fn into_slice(self) -> &'arena mut [T] {
+        if self.cap != 0 && size_of::<T>() != 0 {
+            self.arena.resize_in_place(
+                self.ptr as *mut u8,
+                Self::size_in_bytes(self.cap),
+                Self::size_in_bytes(self.len),
+            );
+        }
+
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    /// Size of `len` elements in bytes, padded. Panics if that doesn't fit
+    /// in an `isize`, same as the standard `Vec` does.
+    #[inline]
+    fn size_in_bytes(len: usize) -> usize {
+        let size = len
+            .checked_mul(size_of::<T>())
+            .filter(|&size| size <= isize::MAX as usize)
+            .expect("capacity overflow");
+
+        padded_size(size)
+    }
+}
+
+impl<'arena, T: Copy> Deref for ArenaVec<'arena, T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<'arena, T: Copy> DerefMut for ArenaVec<'arena, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<'arena, T: Copy> Extend<T> for ArenaVec<'arena, T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'arena, T: Copy + Debug> Debug for ArenaVec<'arena, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push() {
+        let arena = Arena::new();
+        let mut vec = ArenaVec::new(&arena);
+
+        assert_eq!(vec.is_empty(), true);
+        assert_eq!(unsafe { arena.offset() }, 0);
+
+        vec.push(10u64);
+        vec.push(20);
+        vec.push(30);
+
+        assert_eq!(vec.as_slice(), &[10, 20, 30]);
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.pop(), Some(30));
+        assert_eq!(vec.into_slice(), &[10, 20]);
+        assert_eq!(unsafe { arena.offset() }, 16);
+    }
+
+    #[test]
+    fn grows_in_place() {
+        let arena = Arena::new();
+        let mut vec = ArenaVec::with_capacity(&arena, 1);
+
+        let ptr = vec.as_ptr();
+
+        vec.extend(0..100u32);
+
+        // Nothing else got allocated, vec didn't have to move
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.into_slice().len(), 100);
+        assert_eq!(unsafe { arena.offset() }, 400);
+    }
+
+    #[test]
+    fn moves_when_not_last() {
+        let arena = Arena::new();
+        let mut vec = ArenaVec::with_capacity(&arena, 2);
+
+        vec.extend_from_slice(&[1u64, 2]);
+
+        let other = arena.alloc(42u64);
+
+        vec.push(3);
+
+        assert_eq!(other, &42);
+        assert_eq!(vec.into_slice(), &[1, 2, 3]);
+
+        // Old buffer is left behind, new one is shrunk to fit
+        assert_eq!(unsafe { arena.offset() }, 16 + 8 + 24);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let arena = Arena::new();
+        let mut vec = ArenaVec::new(&arena);
+
+        vec.push(());
+        vec.push(());
+
+        assert_eq!(vec.into_slice(), &[(), ()]);
+        assert_eq!(unsafe { arena.offset() }, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn reserve_overflow() {
+        let arena = Arena::new();
+        let mut vec = ArenaVec::<u64>::new(&arena);
+
+        vec.push(1);
+        vec.reserve(1 << 61);
+    }
+}
@@ -0,0 +1,113 @@
+//! Handles to values on the `Arena` that can be logically deleted.
+
+use crate::arena::Arena;
+use crate::cell::CopyCell;
+
+struct Tracked<T> {
+    alive: CopyCell<bool>,
+    value: T,
+}
+
+/// A handle to a value on the `Arena` that can be invalidated.
+///
+/// Memory on the `Arena` can never be freed, so `WeakRef` provides a
+/// tombstone instead: an "alive" flag is allocated next to the value, and
+/// once any copy of the handle is invalidated, `get` returns `None` for
+/// all of its copies.
+///
+/// ```rust
+/// use toolshed::{Arena, WeakRef};
+///
+/// let arena = Arena::new();
+/// let node = WeakRef::new(&arena, "doge");
+/// let copy = node;
+///
+/// assert_eq!(copy.get(), Some(&"doge"));
+///
+/// node.invalidate();
+///
+/// assert_eq!(copy.get(), None);
+/// ```
+#[derive(Clone, Copy)]
+pub struct WeakRef<'arena, T> {
+    inner: &'arena Tracked<T>,
+}
+
+impl<'arena, T: Copy> WeakRef<'arena, T> {
+    /// Put the value on the `Arena` and get a live handle to it.
+    #[inline]
+    pub fn new(arena: &'arena Arena, value: T) -> Self {
+        WeakRef {
+            inner: arena.alloc_node(Tracked {
+                alive: CopyCell::new(true),
+                value,
+            }),
+        }
+    }
+}
+
+impl<'arena, T> WeakRef<'arena, T> {
+    /// Get a reference to the value, unless the handle has been invalidated.
+    #[inline]
+    pub fn get(&self) -> Option<&'arena T> {
+        if self.inner.alive.get() {
+            Some(&self.inner.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the handle hasn't been invalidated yet.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.inner.alive.get()
+    }
+
+    /// Invalidate the handle along with all of its copies. The value itself
+    /// stays in the `Arena` memory, but is no longer reachable through `get`.
+    #[inline]
+    pub fn invalidate(&self) {
+        self.inner.alive.set(false);
+    }
+
+    /// Returns `true` if both handles point to the same value.
+    #[inline]
+    pub fn ptr_eq(&self, other: &WeakRef<'arena, T>) -> bool {
+        std::ptr::eq(self.inner, other.inner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weak_ref() {
+        let arena = Arena::new();
+        let foo = WeakRef::new(&arena, 42u64);
+        let bar = WeakRef::new(&arena, 42u64);
+
+        assert_eq!(foo.is_alive(), true);
+        assert_eq!(foo.get(), Some(&42));
+
+        bar.invalidate();
+
+        assert_eq!(foo.get(), Some(&42));
+        assert_eq!(bar.get(), None);
+        assert_eq!(bar.is_alive(), false);
+    }
+
+    #[test]
+    fn copies_share_liveness() {
+        let arena = Arena::new();
+        let foo = WeakRef::new(&arena, "foo");
+        let copy = foo;
+
+        assert!(foo.ptr_eq(&copy));
+        assert!(!foo.ptr_eq(&WeakRef::new(&arena, "foo")));
+
+        copy.invalidate();
+
+        assert_eq!(foo.get(), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Derive macros for the `toolshed` crate.
+//!
+//! Use them through the `derive` feature of `toolshed` rather than
+//! depending on this crate directly.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, format_ident};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Path};
+
+/// Derive `toolshed::AllocIn` for a struct or an enum.
+///
+/// The arena counterpart of the type has to be provided with the
+/// `#[alloc_in(Target)]` attribute. The target must have the same shape as
+/// the derived type (same field names, same variants), with the types of
+/// all fields being the `AllocIn::Output` of the original field types.
+#[proc_macro_derive(AllocIn, attributes(alloc_in))]
+pub fn derive_alloc_in(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "deriving `AllocIn` for generic types is not supported",
+        ));
+    }
+
+    let target = target(&input)?;
+    let name = &input.ident;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, construct) = fields(&data.fields);
+
+            quote! {
+                let #name #pattern = self;
+
+                #target #construct
+            }
+        },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let (pattern, construct) = fields(&variant.fields);
+
+                quote! {
+                    #name::#ident #pattern => #target::#ident #construct,
+                }
+            });
+
+            quote! {
+                match self {
+                    #( #arms )*
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "deriving `AllocIn` for unions is not supported",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl<'arena> ::toolshed::AllocIn<'arena> for #name {
+            type Output = #target<'arena>;
+
+            #[inline]
+            fn alloc_in(&self, arena: &'arena ::toolshed::Arena) -> Self::Output {
+                #body
+            }
+        }
+    })
+}
+
+/// Read the target type from the `#[alloc_in(Target)]` attribute.
+fn target(input: &DeriveInput) -> syn::Result<Path> {
+    let mut target = None;
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("alloc_in") {
+            if target.is_some() {
+                return Err(Error::new_spanned(attr, "duplicate `alloc_in` attribute"));
+            }
+
+            target = Some(attr.parse_args::<Path>()?);
+        }
+    }
+
+    target.ok_or_else(|| Error::new_spanned(
+        &input.ident,
+        "missing `#[alloc_in(Target)]` attribute naming the arena type",
+    ))
+}
+
+/// Produce a destructuring pattern for the fields, and the matching
+/// expression constructing the target from the bindings.
+fn fields(fields: &Fields) -> (TokenStream2, TokenStream2) {
+    match fields {
+        Fields::Named(named) => {
+            let names: Vec<_> = named.named.iter().map(|field| &field.ident).collect();
+
+            (
+                quote! { { #( #names ),* } },
+                quote! { { #( #names: ::toolshed::AllocIn::alloc_in(#names, arena) ),* } },
+            )
+        },
+        Fields::Unnamed(unnamed) => {
+            let names: Vec<_> = (0..unnamed.unnamed.len())
+                .map(|index| format_ident!("field{}", index))
+                .collect();
+
+            (
+                quote! { ( #( #names ),* ) },
+                quote! { ( #( ::toolshed::AllocIn::alloc_in(#names, arena) ),* ) },
+            )
+        },
+        Fields::Unit => (quote! {}, quote! {}),
+    }
+}